@@ -29,4 +29,20 @@ pub struct Args {
     /// Bind to localhost only (127.0.0.1) instead of all interfaces (0.0.0.0 + :::)
     #[arg(long)]
     pub localhost: bool,
+
+    /// Watch media directories and update the library live as files change
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Start serving immediately and fill the library while scanning (allows an empty library)
+    #[arg(long)]
+    pub background_scan: bool,
+
+    /// Metadata extraction workers per media directory [default: 2 on spinning disks, else CPU count up to 8]
+    #[arg(long, value_name = "N")]
+    pub scan_threads: Option<usize>,
+
+    /// Discard the cached metadata index and re-probe every file
+    #[arg(long)]
+    pub rebuild_index: bool,
 }
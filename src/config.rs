@@ -1,6 +1,10 @@
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
+use crate::media::filter::SymlinkPolicy;
+use crate::media::library::NamedLibrary;
+use crate::media::title::RewriteRule;
+
 const DEFAULT_PORT: u16 = 8200;
 
 fn default_name() -> String {
@@ -21,14 +25,56 @@ pub struct FileConfig {
     pub port: Option<u16>,
     pub name: Option<String>,
     pub localhost: Option<bool>,
+    pub watch: Option<bool>,
+    /// Media directories used when none are given on the command line.
+    pub paths: Option<Vec<PathBuf>>,
+    /// Persist extracted metadata between runs [default: true].
+    pub index: Option<bool>,
+    pub scan_threads: Option<usize>,
+    pub background_scan: Option<bool>,
+    /// Only index files matching one of these globs (relative to their media directory).
+    pub include: Option<Vec<String>>,
+    /// Skip files and directories matching any of these globs.
+    pub exclude: Option<Vec<String>>,
+    /// Skip dotfiles and dot-directories [default: false].
+    pub skip_hidden: Option<bool>,
+    /// "within-roots" (default), "never" or "always".
+    pub follow_symlinks: Option<SymlinkPolicy>,
+    /// Do not cross into other mounted filesystems below a media directory.
+    pub same_filesystem: Option<bool>,
+    /// Maximum directory depth below a media directory (1 = top level only).
+    pub max_depth: Option<usize>,
+    /// `[[library]]` tables: named libraries shown as their own Browse containers.
+    pub library: Option<Vec<NamedLibrary>>,
+    /// Clean up video file names used as titles [default: true].
+    pub normalize_titles: Option<bool>,
+    /// `[[title_rewrite]]` tables: regex replacements applied to file names used as titles.
+    pub title_rewrite: Option<Vec<RewriteRule>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Config {
     pub port: u16,
     pub name: String,
     pub paths: Vec<PathBuf>,
     pub localhost: bool,
+    pub watch: bool,
+    pub index: bool,
+    pub rebuild_index: bool,
+    /// None = pick per root from the storage type.
+    pub scan_threads: Option<usize>,
+    /// Serve while the initial scan runs instead of blocking (and exiting if empty).
+    pub background_scan: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub skip_hidden: bool,
+    pub follow_symlinks: SymlinkPolicy,
+    pub same_filesystem: bool,
+    pub max_depth: Option<usize>,
+    /// Named libraries from the config file, in addition to the unnamed `paths`.
+    pub libraries: Vec<NamedLibrary>,
+    pub normalize_titles: bool,
+    pub title_rewrites: Vec<RewriteRule>,
 }
 
 impl Config {
@@ -37,9 +83,59 @@ impl Config {
         Config {
             port: args.port.or(file.port).unwrap_or(DEFAULT_PORT),
             name: args.name.clone().or(file.name).unwrap_or_else(default_name),
-            paths: args.paths.clone(),
+            // CLI paths replace (not extend) the config file's list
+            paths: if args.paths.is_empty() {
+                file.paths.unwrap_or_default()
+            } else {
+                args.paths.clone()
+            },
             localhost: args.localhost || file.localhost.unwrap_or(false),
+            watch: args.watch || file.watch.unwrap_or(false),
+            index: file.index.unwrap_or(true),
+            rebuild_index: args.rebuild_index,
+            scan_threads: args.scan_threads.or(file.scan_threads),
+            background_scan: args.background_scan || file.background_scan.unwrap_or(false),
+            include: file.include.unwrap_or_default(),
+            exclude: file.exclude.unwrap_or_default(),
+            skip_hidden: file.skip_hidden.unwrap_or(false),
+            follow_symlinks: file.follow_symlinks.unwrap_or_default(),
+            same_filesystem: file.same_filesystem.unwrap_or(false),
+            max_depth: file.max_depth,
+            libraries: file.library.unwrap_or_default(),
+            normalize_titles: file.normalize_titles.unwrap_or(true),
+            title_rewrites: file.title_rewrite.unwrap_or_default(),
+        }
+    }
+
+    /// Every directory to scan: the unnamed `paths` followed by the named libraries'
+    /// paths, without duplicates.
+    pub fn all_paths(&self) -> Vec<PathBuf> {
+        let mut all: Vec<PathBuf> = Vec::new();
+        for path in self.paths.iter().chain(self.libraries.iter().flat_map(|l| &l.paths)) {
+            if !all.contains(path) {
+                all.push(path.clone());
+            }
+        }
+        all
+    }
+
+    /// Names of settings that differ from `newer` but only take effect on restart
+    /// (listening sockets, the device UUID, and tasks spawned at startup).
+    pub fn restart_required_changes(&self, newer: &Config) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.port != newer.port {
+            changed.push("port");
+        }
+        if self.name != newer.name {
+            changed.push("name");
+        }
+        if self.localhost != newer.localhost {
+            changed.push("localhost");
         }
+        if self.watch != newer.watch {
+            changed.push("watch");
+        }
+        changed
     }
 }
 
@@ -60,6 +156,12 @@ pub fn find_config_file(explicit: Option<&Path>) -> Option<PathBuf> {
     None
 }
 
+/// Location of the persistent metadata index: `~/.cache/udlna/index.json` on Linux
+/// (XDG cache dir, the cache counterpart of the config dir searched above).
+pub fn default_index_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("udlna").join("index.json"))
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
     #[error("failed to read config file: {0}")]
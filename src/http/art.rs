@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::http::media::{lookup_item, resolve_servable};
+use crate::http::state::AppState;
+use crate::media::art::{dlna_art_profile, embedded_art};
+
+// Album art is fetched whole for display: Interactive transfer, no seeking.
+// DLNA.ORG_FLAGS 00D00000 = BACKGROUND_TRANSFER_MODE | INTERACTIVE_TRANSFER_MODE | DLNA_V15
+const ART_TRANSFER_MODE: &str = "Interactive";
+const ART_FLAGS: &str = "00D00000000000000000000000000000";
+
+/// GET /art/{id} — the cover image of a media item, read from the folder image or
+/// extracted from the media file on each request.
+pub async fn serve_art(State(state): State<AppState>, Path(id_str): Path<String>) -> Response {
+    let Some(item) = lookup_item(&state, &id_str) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(art) = item.meta.art.clone() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let source = art.path.as_deref().unwrap_or(&item.path);
+    let path = match resolve_servable(&state, source).await {
+        Ok(p) => p,
+        Err(status) => return status.into_response(),
+    };
+    let data = if art.path.is_some() {
+        tokio::fs::read(&path).await.ok()
+    } else {
+        let mime = item.mime;
+        tokio::task::spawn_blocking(move || embedded_art(&path, mime))
+            .await
+            .ok()
+            .flatten()
+    };
+    let Some(data) = data else {
+        tracing::warn!("Album art for {} is no longer readable", item.path.display());
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Ok(mime) = HeaderValue::from_str(&art.mime) {
+        headers.insert(header::CONTENT_TYPE, mime);
+    }
+    let profile = dlna_art_profile(&art)
+        .map(|p| format!("DLNA.ORG_PN={p};"))
+        .unwrap_or_default();
+    let features = format!("{profile}DLNA.ORG_OP=00;DLNA.ORG_CI=0;DLNA.ORG_FLAGS={ART_FLAGS}");
+    if let Ok(features) = HeaderValue::from_str(&features) {
+        headers.insert(HeaderName::from_static("contentfeatures.dlna.org"), features);
+    }
+    headers.insert(
+        HeaderName::from_static("transfermode.dlna.org"),
+        HeaderValue::from_static(ART_TRANSFER_MODE),
+    );
+    (StatusCode::OK, headers, data).into_response()
+}
@@ -0,0 +1,636 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use uuid::Uuid;
+
+use crate::http::soap;
+use crate::media::episode::{self, Episode};
+use crate::media::library::{MediaItem, MediaLibrary, MediaMeta, NamedLibrary};
+use crate::media::mime::MediaKind;
+
+/// UPnP class for filesystem folders and the top-level virtual containers.
+pub const CLASS_STORAGE_FOLDER: &str = "object.container.storageFolder";
+
+/// UPnP classes of the Music hierarchy containers.
+pub const CLASS_MUSIC_ARTIST: &str = "object.container.person.musicArtist";
+pub const CLASS_MUSIC_ALBUM: &str = "object.container.album.musicAlbum";
+pub const CLASS_MUSIC_GENRE: &str = "object.container.genre.musicGenre";
+/// UPnP class of the day containers in the Photos date tree.
+pub const CLASS_PHOTO_ALBUM: &str = "object.container.album.photoAlbum";
+/// UPnP class of the series and season containers in the TV Shows tree.
+pub const CLASS_VIDEO_ALBUM: &str = "object.container.album.videoAlbum";
+
+/// Titles for tracks missing the tag a view groups by.
+const UNKNOWN_ARTIST: &str = "Unknown Artist";
+const UNKNOWN_ALBUM: &str = "Unknown Album";
+const UNKNOWN_GENRE: &str = "Unknown Genre";
+const UNKNOWN_YEAR: &str = "Unknown Year";
+
+/// A child entry of a container: another container, a media item, or a chapter of
+/// one. Indexes refer to `BrowseTree::containers`, `MediaLibrary::items` and the
+/// item's `MediaMeta::chapters` respectively.
+#[derive(Debug, Clone, Copy)]
+pub enum Child {
+    Container(usize),
+    Item(usize),
+    Chapter(usize, usize),
+}
+
+/// One DIDL-Lite container with its children in presentation order.
+#[derive(Debug)]
+pub struct Container {
+    pub id: String,
+    pub parent_id: String,
+    pub title: String,
+    pub class: &'static str,
+    pub children: Vec<Child>,
+    /// Library index of the item whose album art represents this container.
+    pub art: Option<usize>,
+}
+
+/// A tree root for `BrowseTree::add_tree`: a directory that gets its own container
+/// (with this ID), or one whose contents go straight into an existing container.
+enum Root<'a> {
+    Container(&'a Path, String),
+    Inline(&'a Path, usize),
+}
+
+/// The full CDS object hierarchy for one library snapshot.
+///
+/// Building it walks every item once; Browse then only pages through precomputed
+/// child lists. Item indexes are only valid against the library it was built from,
+/// so callers must hold that library's read lock while using the tree.
+#[derive(Debug)]
+pub struct BrowseTree {
+    update_id: u32,
+    item_count: usize,
+    pub containers: Vec<Container>,
+    by_id: HashMap<String, usize>,
+    item_by_id: HashMap<Uuid, usize>,
+    /// (library index, chapter index) of each chapter object.
+    chapter_by_id: HashMap<Uuid, (usize, usize)>,
+    /// Container of each item's directory in the Folders view, by library index.
+    item_folder: Vec<Option<usize>>,
+}
+
+impl BrowseTree {
+    /// Build the hierarchy: root "0" → Videos, Music, Photos, All Media, Folders, then
+    /// one container per named library. Videos starts with a TV Shows tree when any
+    /// file is named like an episode, followed by every video. Music starts with the
+    /// Artists, Album Artists, Albums, Genres and Years views, followed by every track;
+    /// Photos starts with a Year → Month → Day tree, followed by every photo.
+    pub fn build(lib: &MediaLibrary) -> Self {
+        let mut tree = BrowseTree {
+            update_id: lib.update_id,
+            item_count: lib.items.len(),
+            containers: Vec::new(),
+            by_id: HashMap::new(),
+            item_by_id: lib.items.iter().enumerate().map(|(i, it)| (it.id, i)).collect(),
+            chapter_by_id: HashMap::new(),
+            item_folder: vec![None; lib.items.len()],
+        };
+        let root = tree.push_container("0".to_string(), "-1".to_string(), "Root", CLASS_STORAGE_FOLDER);
+
+        let videos = tree.add_named(root, soap::CONTAINER_VIDEOS);
+        let music = tree.add_named(root, soap::CONTAINER_MUSIC);
+        let photos = tree.add_named(root, soap::CONTAINER_PHOTOS);
+        let all = tree.add_named(root, soap::CONTAINER_ALL_MEDIA);
+        tree.add_tv_shows(videos, lib);
+        tree.add_music(music, lib);
+        tree.add_photo_dates(photos, lib);
+        for (i, item) in lib.items.iter().enumerate() {
+            let kind_container = match item.kind {
+                MediaKind::Video => videos,
+                MediaKind::Audio => music,
+                MediaKind::Image => photos,
+                MediaKind::Subtitle => continue,
+            };
+            tree.containers[kind_container].children.push(Child::Item(i));
+            tree.containers[all].children.push(Child::Item(i));
+        }
+
+        let folders = tree.add_named(root, soap::CONTAINER_FOLDERS);
+        tree.add_folders(folders, lib);
+        tree.add_chapters(lib);
+
+        for library in &lib.libraries {
+            tree.add_library(root, library, lib);
+        }
+
+        tree
+    }
+
+    /// Look up a container by ObjectID.
+    pub fn container(&self, id: &str) -> Option<&Container> {
+        self.by_id.get(id).map(|&i| &self.containers[i])
+    }
+
+    /// Look up a chapter by ObjectID: the library index of its item and its index
+    /// among the item's chapters.
+    pub fn chapter(&self, id: &str) -> Option<(usize, usize)> {
+        let id = Uuid::parse_str(id).ok()?;
+        self.chapter_by_id.get(&id).copied()
+    }
+
+    /// Look up a media item's index in the library by ObjectID.
+    pub fn item_index(&self, id: &str) -> Option<usize> {
+        let id = Uuid::parse_str(id).ok()?;
+        self.item_by_id.get(&id).copied()
+    }
+
+    /// The container the item at library index `index` reports as its parentID in
+    /// BrowseMetadata: its directory in the Folders view, else the top-level container
+    /// of its kind (an item outside every root, reached through a symlink).
+    pub fn item_parent(&self, index: usize, kind: MediaKind) -> String {
+        if let Some(folder) = self.item_folder.get(index).copied().flatten() {
+            return self.containers[folder].id.clone();
+        }
+        let name = match kind {
+            MediaKind::Video => soap::CONTAINER_VIDEOS,
+            MediaKind::Audio => soap::CONTAINER_MUSIC,
+            MediaKind::Image => soap::CONTAINER_PHOTOS,
+            MediaKind::Subtitle => soap::CONTAINER_ALL_MEDIA,
+        };
+        soap::container_uuid(name).to_string()
+    }
+
+    fn push_container(&mut self, id: String, parent_id: String, title: &str, class: &'static str) -> usize {
+        let index = self.containers.len();
+        self.by_id.insert(id.clone(), index);
+        self.containers.push(Container {
+            id,
+            parent_id,
+            title: title.to_string(),
+            class,
+            children: Vec::new(),
+            art: None,
+        });
+        index
+    }
+
+    /// Add a child container under `parent` and return its index.
+    fn add_container(&mut self, parent: usize, id: String, title: &str, class: &'static str) -> usize {
+        let parent_id = self.containers[parent].id.clone();
+        let index = self.push_container(id, parent_id, title, class);
+        self.containers[parent].children.push(Child::Container(index));
+        index
+    }
+
+    /// Add a top-level storage folder whose ID is `container_uuid(name)`.
+    fn add_named(&mut self, parent: usize, name: &str) -> usize {
+        let id = soap::container_uuid(name).to_string();
+        self.add_container(parent, id, name, CLASS_STORAGE_FOLDER)
+    }
+
+    /// TV Shows: Series → Season → Episode for videos named like episodes. Series are
+    /// grouped case-insensitively and sorted by name, seasons by number (Specials, season
+    /// 0, first) and episodes by number or air date.
+    fn add_tv_shows(&mut self, videos: usize, lib: &MediaLibrary) {
+        let mut episodes: Vec<(Episode, usize)> = (0..lib.items.len())
+            .filter(|&i| lib.items[i].kind == MediaKind::Video)
+            .filter_map(|i| Some((episode::parse(&lib.items[i].path)?, i)))
+            .collect();
+        if episodes.is_empty() {
+            return;
+        }
+        episodes.sort_by_cached_key(|(e, i)| (fold(Some(&e.series)), e.season, e.episode, e.date.clone(), *i));
+
+        let name = soap::CONTAINER_TV_SHOWS;
+        let shows = self.add_video_container(videos, &[name], name, CLASS_STORAGE_FOLDER);
+        let mut containers: HashMap<(String, u32), usize> = HashMap::new();
+        let mut series: HashMap<String, usize> = HashMap::new();
+        for (episode, i) in episodes {
+            let key = fold(Some(&episode.series));
+            let show = match series.get(&key) {
+                Some(&index) => index,
+                None => {
+                    let path = [soap::CONTAINER_TV_SHOWS, &key];
+                    let index = self.add_video_container(shows, &path, &episode.series, CLASS_VIDEO_ALBUM);
+                    series.insert(key.clone(), index);
+                    index
+                }
+            };
+            let season = match containers.get(&(key.clone(), episode.season)) {
+                Some(&index) => index,
+                None => {
+                    let title = match episode.season {
+                        0 => "Specials".to_string(),
+                        year if episode.date.is_some() => year.to_string(),
+                        number => format!("Season {number}"),
+                    };
+                    let path = [soap::CONTAINER_TV_SHOWS, &key, &episode.season.to_string()];
+                    let index = self.add_video_container(show, &path, &title, CLASS_VIDEO_ALBUM);
+                    containers.insert((key, episode.season), index);
+                    index
+                }
+            };
+            self.containers[season].children.push(Child::Item(i));
+            for index in [show, season] {
+                if self.containers[index].art.is_none() && lib.items[i].meta.art.is_some() {
+                    self.containers[index].art = Some(i);
+                }
+            }
+        }
+    }
+
+    fn add_video_container(&mut self, parent: usize, path: &[&str], title: &str, class: &'static str) -> usize {
+        let id = soap::video_container_uuid(path).to_string();
+        self.add_container(parent, id, title, class)
+    }
+
+    /// Music views over the tagged audio items. Groups are keyed case-insensitively
+    /// and titled with the first spelling seen; tracks without the grouping tag land
+    /// in an "Unknown ..." group sorted last.
+    fn add_music(&mut self, music: usize, lib: &MediaLibrary) {
+        let tracks: Vec<usize> = (0..lib.items.len())
+            .filter(|&i| lib.items[i].kind == MediaKind::Audio)
+            .collect();
+        if tracks.is_empty() {
+            return;
+        }
+
+        let artists = self.add_music_view(music, soap::CONTAINER_ARTISTS);
+        for group in group_tracks(lib, &tracks, |m| m.artist.as_deref()) {
+            let title = group.title.as_deref().unwrap_or(UNKNOWN_ARTIST);
+            let path = [soap::CONTAINER_ARTISTS, &group.key];
+            let artist = self.add_music_container(artists, &path, title, CLASS_MUSIC_ARTIST);
+            self.add_albums(artist, &path, lib, &group.items);
+        }
+
+        let album_artists = self.add_music_view(music, soap::CONTAINER_ALBUM_ARTISTS);
+        for group in group_tracks(lib, &tracks, album_artist) {
+            let title = group.title.as_deref().unwrap_or(UNKNOWN_ARTIST);
+            let path = [soap::CONTAINER_ALBUM_ARTISTS, &group.key];
+            let artist = self.add_music_container(album_artists, &path, title, CLASS_MUSIC_ARTIST);
+            self.add_albums(artist, &path, lib, &group.items);
+        }
+
+        let albums = self.add_music_view(music, soap::CONTAINER_ALBUMS);
+        self.add_albums(albums, &[soap::CONTAINER_ALBUMS], lib, &tracks);
+
+        let genres = self.add_music_view(music, soap::CONTAINER_GENRES);
+        for group in group_tracks(lib, &tracks, |m| m.genre.as_deref()) {
+            let title = group.title.as_deref().unwrap_or(UNKNOWN_GENRE);
+            let path = [soap::CONTAINER_GENRES, &group.key];
+            let genre = self.add_music_container(genres, &path, title, CLASS_MUSIC_GENRE);
+            self.add_sorted_tracks(genre, lib, group.items, true);
+        }
+
+        let years = self.add_music_view(music, soap::CONTAINER_YEARS);
+        let mut by_year: Vec<(Option<i32>, Vec<usize>)> = Vec::new();
+        for &i in &tracks {
+            let year = lib.items[i].meta.year;
+            match by_year.iter_mut().find(|(y, _)| *y == year) {
+                Some((_, items)) => items.push(i),
+                None => by_year.push((year, vec![i])),
+            }
+        }
+        // Unknown (None) last.
+        by_year.sort_by_key(|(year, _)| (year.is_none(), *year));
+        for (year, items) in by_year {
+            let key = year.map(|y| y.to_string()).unwrap_or_default();
+            let title = year.map(|y| y.to_string()).unwrap_or_else(|| UNKNOWN_YEAR.to_string());
+            let path = [soap::CONTAINER_YEARS, &key];
+            let index = self.add_music_container(years, &path, &title, CLASS_STORAGE_FOLDER);
+            self.add_sorted_tracks(index, lib, items, true);
+        }
+    }
+
+    /// One musicAlbum container per album among `items`, IDs scoped below `path`. An
+    /// album is its title and album artist: same-named albums by different album
+    /// artists stay apart, with the artist added to their title and ID.
+    fn add_albums(&mut self, parent: usize, path: &[&str], lib: &MediaLibrary, items: &[usize]) {
+        for group in group_tracks(lib, items, |m| m.album.as_deref()) {
+            let title = group.title.as_deref().unwrap_or(UNKNOWN_ALBUM);
+            let by_artist = group_tracks(lib, &group.items, album_artist);
+            let shared = by_artist.len() > 1;
+            for artist in by_artist {
+                let mut album_path = path.to_vec();
+                let title = if shared {
+                    album_path.push(&artist.key);
+                    format!("{title} ({})", artist.title.as_deref().unwrap_or(UNKNOWN_ARTIST))
+                } else {
+                    title.to_string()
+                };
+                album_path.push(&group.key);
+                let album = self.add_music_container(parent, &album_path, &title, CLASS_MUSIC_ALBUM);
+                self.add_sorted_tracks(album, lib, artist.items, false);
+                self.containers[album].art = self.containers[album].children.iter().find_map(|c| match c {
+                    Child::Item(i) if lib.items[*i].meta.art.is_some() => Some(*i),
+                    _ => None,
+                });
+            }
+        }
+    }
+
+    /// A top-level Music view, e.g. Artists.
+    fn add_music_view(&mut self, music: usize, name: &str) -> usize {
+        self.add_music_container(music, &[name], name, CLASS_STORAGE_FOLDER)
+    }
+
+    fn add_music_container(&mut self, parent: usize, path: &[&str], title: &str, class: &'static str) -> usize {
+        let id = soap::music_container_uuid(path).to_string();
+        self.add_container(parent, id, title, class)
+    }
+
+    /// Append tracks in album order: disc, then track number, then library order.
+    /// Untagged tracks go after numbered ones. Groups `across_albums` (genres, years)
+    /// are ordered by artist and album first; an album's own tracks are not, so a
+    /// compilation plays in track order.
+    fn add_sorted_tracks(&mut self, parent: usize, lib: &MediaLibrary, mut items: Vec<usize>, across_albums: bool) {
+        items.sort_by_cached_key(|&i| {
+            let meta = &lib.items[i].meta;
+            let (artist, album) = if across_albums {
+                (fold(meta.artist.as_deref()), fold(meta.album.as_deref()))
+            } else {
+                Default::default()
+            };
+            (
+                artist,
+                album,
+                meta.disc.unwrap_or(1),
+                meta.track.unwrap_or(u32::MAX),
+                i,
+            )
+        });
+        self.containers[parent].children.extend(items.into_iter().map(Child::Item));
+    }
+
+    /// Photos by capture date (EXIF or file name), else modification date. Containers
+    /// are chronological; photos within a day are ordered by time.
+    fn add_photo_dates(&mut self, photos: usize, lib: &MediaLibrary) {
+        let mut dated: Vec<(String, usize)> = (0..lib.items.len())
+            .filter(|&i| lib.items[i].kind == MediaKind::Image)
+            .filter_map(|i| Some((photo_date(&lib.items[i])?, i)))
+            .collect();
+        dated.sort();
+
+        // Year "2023", month "2023-01" and day "2023-01-15" are date prefixes, used
+        // as titles and (split on '-') as ID seeds.
+        let mut containers: HashMap<String, usize> = HashMap::new();
+        for (date, i) in dated {
+            let mut parent = photos;
+            for (len, class) in [(4, CLASS_STORAGE_FOLDER), (7, CLASS_STORAGE_FOLDER), (10, CLASS_PHOTO_ALBUM)] {
+                let key = &date[..len];
+                parent = match containers.get(key) {
+                    Some(&index) => index,
+                    None => {
+                        let parts: Vec<&str> = key.split('-').collect();
+                        let id = soap::photo_container_uuid(&parts).to_string();
+                        let index = self.add_container(parent, id, key, class);
+                        containers.insert(key.to_string(), index);
+                        index
+                    }
+                };
+            }
+            self.containers[parent].children.push(Child::Item(i));
+        }
+    }
+
+    /// Folders view: one container per scan root, then one per subdirectory that
+    /// (transitively) holds media. Directories are created on demand from item
+    /// paths, so empty and media-free directories never appear.
+    fn add_folders(&mut self, folders: usize, lib: &MediaLibrary) {
+        let roots: Vec<Root> = lib
+            .roots
+            .iter()
+            .map(|root| Root::Container(root, soap::folder_container_uuid(root).to_string()))
+            .collect();
+        self.add_tree(folders, &roots, lib, |_| true, |dir| soap::folder_container_uuid(dir).to_string());
+    }
+
+    /// A "<title> - Chapters" container after each item with chapter marks, in the
+    /// item's folder, holding one object per chapter. A single chapter is not a list.
+    fn add_chapters(&mut self, lib: &MediaLibrary) {
+        for (i, item) in lib.items.iter().enumerate() {
+            let Some(folder) = self.item_folder[i].filter(|_| item.meta.chapters.len() > 1) else {
+                continue;
+            };
+            let stem = item.path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+            let title = format!("{} - Chapters", lib.titles.normalize(&stem, item.kind).title);
+            let id = soap::chapters_container_uuid(&item.id).to_string();
+            let index = self.add_container(folder, id, &title, CLASS_STORAGE_FOLDER);
+            for n in 0..item.meta.chapters.len() {
+                self.chapter_by_id.insert(soap::chapter_uuid(&item.id, n), (i, n));
+                self.containers[index].children.push(Child::Chapter(i, n));
+            }
+        }
+    }
+
+    /// A named library: its container holds the folder tree of its single path
+    /// directly, or one folder per path when it has several.
+    fn add_library(&mut self, parent: usize, library: &NamedLibrary, lib: &MediaLibrary) {
+        let name = &library.name;
+        let id = soap::library_container_uuid(name).to_string();
+        let index = self.add_container(parent, id, name, CLASS_STORAGE_FOLDER);
+        let roots: Vec<Root> = match library.paths.as_slice() {
+            [single] => vec![Root::Inline(single, index)],
+            paths => paths
+                .iter()
+                .map(|root| Root::Container(root, soap::library_folder_uuid(name, root).to_string()))
+                .collect(),
+        };
+        self.add_tree(
+            index,
+            &roots,
+            lib,
+            |item| library.accepts(item.kind),
+            |dir| soap::library_folder_uuid(name, dir).to_string(),
+        );
+    }
+
+    /// Build a directory tree under `parent` from the items below `roots` that `keep`
+    /// accepts. Root containers without any such items are pruned.
+    fn add_tree(
+        &mut self,
+        parent: usize,
+        roots: &[Root],
+        lib: &MediaLibrary,
+        keep: impl Fn(&MediaItem) -> bool,
+        folder_id: impl Fn(&Path) -> String,
+    ) {
+        let mut dirs: HashMap<PathBuf, usize> = HashMap::new();
+        let mut root_paths: Vec<&Path> = Vec::new();
+        for root in roots {
+            let (path, index) = match root {
+                Root::Inline(path, index) => (*path, *index),
+                Root::Container(path, id) => {
+                    if dirs.contains_key(*path) {
+                        continue;
+                    }
+                    let title = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.display().to_string());
+                    (*path, self.add_container(parent, id.clone(), &title, CLASS_STORAGE_FOLDER))
+                }
+            };
+            dirs.insert(path.to_path_buf(), index);
+            root_paths.push(path);
+        }
+
+        for (i, item) in lib.items.iter().enumerate() {
+            if !keep(item) {
+                continue;
+            }
+            let Some(root) = root_paths.iter().find(|r| item.path.starts_with(r)) else {
+                continue;
+            };
+            let Some(dir) = item.path.parent() else {
+                continue;
+            };
+            let index = self.folder_for(&mut dirs, root, dir, &folder_id);
+            self.containers[index].children.push(Child::Item(i));
+            // Folders is built first; named libraries show the same directories again.
+            self.item_folder[i].get_or_insert(index);
+        }
+
+        // Roots without any media are pruned like every other empty directory.
+        let containers = &self.containers;
+        let empty_roots: Vec<usize> = self.containers[parent]
+            .children
+            .iter()
+            .filter_map(|c| match c {
+                Child::Container(i) if containers[*i].children.is_empty() => Some(*i),
+                _ => None,
+            })
+            .collect();
+        for index in &empty_roots {
+            self.by_id.remove(&self.containers[*index].id);
+        }
+        self.containers[parent]
+            .children
+            .retain(|c| !matches!(c, Child::Container(i) if empty_roots.contains(i)));
+
+        // Subdirectories first (by name), then files in library (walk) order.
+        let folder_indexes: Vec<usize> = dirs.values().copied().collect();
+        for index in folder_indexes {
+            let mut children = std::mem::take(&mut self.containers[index].children);
+            children.sort_by(|a, b| match (a, b) {
+                (Child::Container(x), Child::Container(y)) => {
+                    let tx = self.containers[*x].title.to_lowercase();
+                    let ty = self.containers[*y].title.to_lowercase();
+                    tx.cmp(&ty)
+                }
+                (Child::Container(_), Child::Item(_)) => std::cmp::Ordering::Less,
+                (Child::Item(_), Child::Container(_)) => std::cmp::Ordering::Greater,
+                (Child::Item(x), Child::Item(y)) => x.cmp(y),
+                // Chapters only appear in chapter containers, added after this.
+                (Child::Chapter(..), _) | (_, Child::Chapter(..)) => std::cmp::Ordering::Equal,
+            });
+            self.containers[index].children = children;
+        }
+    }
+
+    /// Return the container for `dir`, creating it and any missing ancestors up to `root`.
+    fn folder_for(
+        &mut self,
+        dirs: &mut HashMap<PathBuf, usize>,
+        root: &Path,
+        dir: &Path,
+        folder_id: &impl Fn(&Path) -> String,
+    ) -> usize {
+        if let Some(&index) = dirs.get(dir) {
+            return index;
+        }
+        // dir is strictly below root here: the root itself is always present in `dirs`.
+        let parent = match dir.parent() {
+            Some(p) if p.starts_with(root) => self.folder_for(dirs, root, p, folder_id),
+            _ => dirs[root],
+        };
+        let title = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let id = folder_id(dir);
+        let index = self.add_container(parent, id, &title, CLASS_STORAGE_FOLDER);
+        dirs.insert(dir.to_path_buf(), index);
+        index
+    }
+}
+
+/// "YYYY-MM-DD..." for the Photos date tree: capture time, else modification time.
+fn photo_date(item: &MediaItem) -> Option<String> {
+    let date = match &item.meta.taken {
+        Some(taken) => taken.clone(),
+        None => {
+            let modified: chrono::DateTime<chrono::Utc> = item.modified?.into();
+            modified.format("%Y-%m-%dT%H:%M:%S").to_string()
+        }
+    };
+    (date.len() >= 10 && date.is_char_boundary(10)).then_some(date)
+}
+
+/// Tracks sharing one (case-folded) tag value.
+struct TrackGroup {
+    /// Case-folded tag value; empty for tracks without the tag.
+    key: String,
+    /// First spelling seen, or None for the unknown group.
+    title: Option<String>,
+    items: Vec<usize>,
+}
+
+/// Group `items` by the tag `tag` picks, sorted by key with the unknown group last.
+fn group_tracks<'a>(
+    lib: &'a MediaLibrary,
+    items: &[usize],
+    tag: impl Fn(&'a MediaMeta) -> Option<&'a str>,
+) -> Vec<TrackGroup> {
+    let mut groups: Vec<TrackGroup> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    for &i in items {
+        let value = tag(&lib.items[i].meta);
+        let key = fold(value);
+        match by_key.get(&key) {
+            Some(&g) => groups[g].items.push(i),
+            None => {
+                by_key.insert(key.clone(), groups.len());
+                groups.push(TrackGroup {
+                    key,
+                    title: value.map(str::to_string),
+                    items: vec![i],
+                });
+            }
+        }
+    }
+    groups.sort_by(|a, b| (a.key.is_empty(), &a.key).cmp(&(b.key.is_empty(), &b.key)));
+    groups
+}
+
+/// The artist an album is filed under: the album artist tag, else the track artist.
+fn album_artist(meta: &MediaMeta) -> Option<&str> {
+    meta.album_artist.as_deref().or(meta.artist.as_deref())
+}
+
+/// Case-folded grouping key for a tag value; empty when absent.
+fn fold(value: Option<&str>) -> String {
+    value.map(|v| v.trim().to_lowercase()).unwrap_or_default()
+}
+
+/// Caches the BrowseTree for the current library snapshot.
+///
+/// Keyed by SystemUpdateID: every writer (watcher, background scan, SIGHUP rescan)
+/// bumps it when the item set changes, so a matching ID means the tree is current.
+#[derive(Debug, Default)]
+pub struct BrowseCache {
+    tree: Mutex<Option<Arc<BrowseTree>>>,
+}
+
+impl BrowseCache {
+    /// Return the tree for `lib`, rebuilding it if the library changed since last time.
+    /// Call with `lib` borrowed from a held read guard.
+    pub fn get(&self, lib: &MediaLibrary) -> Arc<BrowseTree> {
+        let mut cached = self.tree.lock().expect("browse cache lock poisoned");
+        match cached.as_ref() {
+            Some(tree) if tree.update_id == lib.update_id && tree.item_count == lib.items.len() => {
+                Arc::clone(tree)
+            }
+            _ => {
+                let tree = Arc::new(BrowseTree::build(lib));
+                *cached = Some(Arc::clone(&tree));
+                tree
+            }
+        }
+    }
+}
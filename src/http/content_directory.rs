@@ -5,9 +5,15 @@ use axum::{
     response::Response,
 };
 use crate::http::soap::{self, soap_response, soap_fault, extract_soap_param, apply_pagination};
+use crate::http::browse::{BrowseTree, Child, Container};
 use crate::http::state::AppState;
-use crate::media::library::MediaItem;
+use crate::media::library::{MediaItem, MediaLibrary, MediaMeta};
+use crate::media::art::dlna_art_profile;
+use crate::media::episode::{self, Episode};
+use crate::media::metadata::{format_seconds, parse_upnp_duration};
 use crate::media::mime::MediaKind;
+use crate::media::seek;
+use crate::media::title::TitleNormalizer;
 
 // ── Helper ────────────────────────────────────────────────────────────────────
 
@@ -59,7 +65,7 @@ pub async fn cds_control(
         Some("Browse") => handle_browse(&state, &headers, &body).await,
         Some("GetSearchCapabilities") => handle_get_search_capabilities(),
         Some("GetSortCapabilities") => handle_get_sort_capabilities(),
-        Some("GetSystemUpdateID") => handle_get_system_update_id(),
+        Some("GetSystemUpdateID") => handle_get_system_update_id(&state),
         _ => {
             tracing::warn!("Unknown CDS action: {:?}", action);
             soap_fault(402, "InvalidArgs").into_response()
@@ -85,124 +91,290 @@ fn handle_get_sort_capabilities() -> Response {
     ))
 }
 
-/// GetSystemUpdateID: returns a fixed counter of 1.
+/// GetSystemUpdateID: returns the library's change counter (bumped by the watcher).
 /// The element name is `Id` (capital I, lowercase d) per the UPnP CDS spec.
-fn handle_get_system_update_id() -> Response {
-    ok_xml(soap_response("GetSystemUpdateID", "<Id>1</Id>"))
+fn handle_get_system_update_id(state: &AppState) -> Response {
+    let update_id = state.library.read().expect("library lock poisoned").update_id;
+    ok_xml(soap_response("GetSystemUpdateID", &format!("<Id>{}</Id>", update_id)))
 }
 
 // ── DIDL-Lite generation helpers ──────────────────────────────────────────────
 
-/// Wrap inner XML content in a DIDL-Lite root element with all four required namespaces.
+/// Wrap inner XML content in a DIDL-Lite root element with all four required namespaces,
+/// plus Samsung's `sec` namespace for subtitle captions.
 ///
 /// CRITICAL: All four namespaces are required. Samsung TVs reject missing xmlns:dlna silently.
 fn didl_lite_wrap(inner: &str) -> String {
     format!(
-        r#"<DIDL-Lite xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns:dlna="urn:schemas-dlna-org:metadata-1-0/">{inner}</DIDL-Lite>"#,
+        r#"<DIDL-Lite xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/" xmlns:dlna="urn:schemas-dlna-org:metadata-1-0/" xmlns:sec="http://www.sec.co.kr/">{inner}</DIDL-Lite>"#,
         inner = inner,
     )
 }
 
-/// Generate a single DIDL-Lite <container> element.
-fn container_element(id: &str, parent_id: &str, title: &str, child_count: usize) -> String {
+/// Generate a single DIDL-Lite <container> element. `extra` holds additional
+/// pre-rendered properties (e.g. upnp:albumArtURI).
+fn container_element(id: &str, parent_id: &str, title: &str, class: &str, child_count: usize, extra: &str) -> String {
     format!(
-        r#"<container id="{id}" parentID="{parent_id}" restricted="1" childCount="{child_count}"><dc:title>{title}</dc:title><upnp:class>object.container.storageFolder</upnp:class></container>"#,
+        r#"<container id="{id}" parentID="{parent_id}" restricted="1" childCount="{child_count}"><dc:title>{title}</dc:title><upnp:class>{class}</upnp:class>{extra}</container>"#,
         id = id,
         parent_id = parent_id,
         title = soap::xml_escape(title),
+        class = class,
         child_count = child_count,
+        extra = extra,
     )
 }
 
+/// Render a BrowseTree container, with the album art of its representative item.
+fn render_container(c: &Container, lib: &MediaLibrary, headers: &HeaderMap) -> String {
+    let art = c.art.map(|i| album_art_element(&lib.items[i], headers)).unwrap_or_default();
+    container_element(&c.id, &c.parent_id, &c.title, c.class, c.children.len(), &art)
+}
+
+/// `<upnp:albumArtURI>` pointing at `/art/{id}`, or empty when the item has no art.
+/// dlna:profileID is omitted for images without a DLNA profile.
+fn album_art_element(item: &MediaItem, headers: &HeaderMap) -> String {
+    let Some(art) = &item.meta.art else {
+        return String::new();
+    };
+    let profile = dlna_art_profile(art)
+        .map(|p| format!(r#" dlna:profileID="{p}""#))
+        .unwrap_or_default();
+    let url = soap::build_art_url(headers, &item.id);
+    format!(r#"<upnp:albumArtURI{profile}>{}</upnp:albumArtURI>"#, soap::xml_escape(&url))
+}
+
 /// Generate a single DIDL-Lite <item> element for a MediaItem.
 ///
-/// - dc:title uses file_stem() not file_name() (no extension) — RESEARCH.md Pitfall 8
-/// - dc:date is always present — RESEARCH.md Pitfall 5
+/// - dc:title is the .nfo or tag title, else file_stem() not file_name() (no extension) — RESEARCH.md Pitfall 8;
+///   videos named like episodes use "S02E05 - Title" from the file name before the tag title,
+///   and file stems are cleaned up by `titles`; a changed stem is logged at debug level
+/// - dc:date is always present — RESEARCH.md Pitfall 5; photo capture time, an episode's
+///   air date, the .nfo or tag year or a year in a video's file name wins over the mtime
 /// - protocolInfo uses DLNA.ORG_PN when dlna_profile is Some, omits when None
 /// - res URL is built from Host header
-fn item_element(item: &MediaItem, parent_id: &str, headers: &HeaderMap) -> String {
-    let title = item
-        .path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("");
+/// - sidecar subtitles follow as further `<res>` elements, after the media's own
+fn item_element(item: &MediaItem, parent_id: &str, headers: &HeaderMap, titles: &TitleNormalizer) -> String {
+    let nfo = item.meta.nfo.as_ref();
+    let episode = (item.kind == MediaKind::Video).then(|| episode::parse(&item.path)).flatten();
+    let episode_title = episode.as_ref().map(Episode::display_title);
+    let stem = item.path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let from_name = titles.normalize(stem, item.kind);
+    let title = nfo
+        .and_then(|n| n.title.as_deref())
+        .or(episode_title.as_deref())
+        .or(item.meta.title.as_deref())
+        .unwrap_or(&from_name.title);
+    if title == from_name.title && title != stem {
+        tracing::debug!("Title {:?} from file name {:?}", title, stem);
+    }
     let upnp_class = match item.kind {
+        MediaKind::Video if episode.is_some() => "object.item.videoItem.videoBroadcast",
         MediaKind::Video => "object.item.videoItem",
         MediaKind::Audio => "object.item.audioItem.musicTrack",
         MediaKind::Image => "object.item.imageItem.photo",
         _ => "object.item",
     };
-    let dc_date = soap::format_dc_date(&item.path);
-    let protocol_info = soap::build_protocol_info(item.mime, item.meta.dlna_profile);
+    let year = nfo.and_then(|n| n.year).or(item.meta.year).or(from_name.year);
+    let aired = episode.as_ref().and_then(|e| e.date.clone());
+    let dc_date = match (&item.meta.taken, aired, year) {
+        (Some(taken), _, _) => taken.clone(),
+        (None, Some(aired), _) => aired,
+        (None, None, Some(year)) => format!("{year:04}-01-01"),
+        (None, None, None) => soap::format_dc_date(&item.path),
+    };
+    let mut tags = tag_elements(&item.meta);
+    if let Some(episode) = &episode {
+        tags.push_str(&episode_elements(episode));
+    }
+    let art = album_art_element(item, headers);
+    let (caption, subtitle_res) = subtitle_elements(item, headers);
+    let time_seek = seek::seekable_duration(item).is_some();
+    let protocol_info = soap::build_protocol_info(item.mime, item.meta.dlna_profile, time_seek);
     let res_url = soap::build_res_url(headers, &item.id);
 
-    // Build optional res attributes
-    let duration_attr = if let Some(ref d) = item.meta.duration {
-        format!(r#" duration="{}""#, d)
-    } else {
-        String::new()
-    };
-    let resolution_attr = if let Some(ref r) = item.meta.resolution {
-        format!(r#" resolution="{}""#, r)
-    } else {
-        String::new()
-    };
-    let bitrate_attr = if let Some(b) = item.meta.bitrate {
-        format!(r#" bitrate="{}""#, b)
-    } else {
-        String::new()
-    };
+    let res_attrs = res_attributes(&item.meta);
 
     format!(
-        r#"<item id="{id}" parentID="{parent_id}" restricted="1"><dc:title>{title}</dc:title><upnp:class>{upnp_class}</upnp:class><dc:date>{dc_date}</dc:date><res protocolInfo="{protocol_info}" size="{size}"{duration_attr}{resolution_attr}{bitrate_attr}>{res_url}</res></item>"#,
+        r#"<item id="{id}" parentID="{parent_id}" restricted="1"><dc:title>{title}</dc:title>{tags}<upnp:class>{upnp_class}</upnp:class><dc:date>{dc_date}</dc:date>{art}{caption}<res protocolInfo="{protocol_info}" size="{size}"{res_attrs}>{res_url}</res>{subtitle_res}</item>"#,
         id = item.id,
         parent_id = parent_id,
         title = soap::xml_escape(title),
+        tags = tags,
         upnp_class = upnp_class,
         dc_date = dc_date,
+        art = art,
+        caption = caption,
         protocol_info = protocol_info,
         size = item.file_size,
-        duration_attr = duration_attr,
-        resolution_attr = resolution_attr,
-        bitrate_attr = bitrate_attr,
+        res_attrs = res_attrs,
         res_url = soap::xml_escape(&res_url),
+        subtitle_res = subtitle_res,
     )
 }
 
+/// Generate the DIDL-Lite <item> for chapter `index` of `item`: the item's own stream,
+/// titled by the chapter, with the chapter length as its duration and the start time
+/// in whole seconds as Samsung's `BM` (bookmark) in `<sec:dcmInfo>`, where a renderer
+/// resumes playback from.
+fn chapter_element(item: &MediaItem, index: usize, parent_id: &str, headers: &HeaderMap) -> String {
+    let chapters = &item.meta.chapters;
+    let chapter = &chapters[index];
+    let title = match &chapter.title {
+        Some(title) => title.clone(),
+        None => format!("Chapter {}", index + 1),
+    };
+    let upnp_class = match item.kind {
+        MediaKind::Video => "object.item.videoItem",
+        MediaKind::Audio => "object.item.audioItem",
+        _ => "object.item",
+    };
+    let end = chapters
+        .get(index + 1)
+        .map(|next| next.start)
+        .or_else(|| item.meta.duration.as_deref().and_then(parse_upnp_duration));
+    let duration = end
+        .filter(|&end| end > chapter.start)
+        .map(|end| format!(r#" duration="{}""#, format_seconds(end - chapter.start)))
+        .unwrap_or_default();
+    let time_seek = seek::seekable_duration(item).is_some();
+    let protocol_info = soap::build_protocol_info(item.mime, item.meta.dlna_profile, time_seek);
+    let res_url = soap::build_res_url(headers, &item.id);
+    format!(
+        r#"<item id="{id}" parentID="{parent_id}" restricted="1"><dc:title>{title}</dc:title><upnp:class>{upnp_class}</upnp:class><sec:dcmInfo>BM={bookmark}</sec:dcmInfo><res protocolInfo="{protocol_info}" size="{size}"{duration}>{res_url}</res></item>"#,
+        id = soap::chapter_uuid(&item.id, index),
+        title = soap::xml_escape(&title),
+        bookmark = chapter.start as u64,
+        size = item.file_size,
+        res_url = soap::xml_escape(&res_url),
+    )
+}
+
+/// `<sec:CaptionInfoEx>` for the first sidecar subtitle, which Samsung TVs load with
+/// the video, and a `<res>` for every subtitle, which other renderers list. Both empty
+/// without subtitles.
+fn subtitle_elements(item: &MediaItem, headers: &HeaderMap) -> (String, String) {
+    let Some(first) = item.meta.subtitles.first() else {
+        return (String::new(), String::new());
+    };
+    let url = |id| soap::xml_escape(&soap::build_subtitle_url(headers, id)).to_string();
+    let caption = format!(r#"<sec:CaptionInfoEx sec:type="{}">{}</sec:CaptionInfoEx>"#, first.format(), url(&first.id));
+    let res = item
+        .meta
+        .subtitles
+        .iter()
+        .map(|s| format!(r#"<res protocolInfo="http-get:*:{}:*">{}</res>"#, s.mime, url(&s.id)))
+        .collect();
+    (caption, res)
+}
+
+/// Optional `<res>` attributes, each omitted when unknown. `bitrate` is in bytes per
+/// second as the UPnP AV ContentDirectory spec defines it. A video's channel count is
+/// that of its first audio track.
+///
+/// UPnP defines no attribute for the video profile, bit depth, frame rate or HDR
+/// transfer, so those stay in `MediaMeta` only.
+fn res_attributes(meta: &MediaMeta) -> String {
+    let mut out = String::new();
+    let mut attr = |name: &str, value: Option<String>| {
+        if let Some(value) = value {
+            out.push_str(&format!(r#" {name}="{value}""#));
+        }
+    };
+    attr("duration", meta.duration.clone());
+    attr("resolution", meta.resolution.clone());
+    attr("bitrate", meta.bitrate.map(|b| b.to_string()));
+    attr("sampleFrequency", meta.sample_rate.map(|r| r.to_string()));
+    let channels = meta.channels.or_else(|| meta.audio_tracks.first()?.channels);
+    attr("nrAudioChannels", channels.map(|c| c.to_string()));
+    attr("bitsPerSample", meta.bits_per_sample.map(|b| b.to_string()));
+    out
+}
+
+/// Render the tag-derived DIDL-Lite properties of an item; empty when it has no tags.
+///
+/// The artist doubles as dc:creator, which is what most renderers show under the title.
+/// Each distinct audio track language of a video becomes a dc:language. A video's
+/// .nfo sidecar adds its plot and credits, and its genres replace the tag genre;
+/// dc:description is the outline, else the plot.
+fn tag_elements(meta: &MediaMeta) -> String {
+    let mut out = String::new();
+    let mut text = |element: &str, attrs: &str, value: Option<&str>| {
+        if let Some(value) = value {
+            out.push_str(&format!("<{element}{attrs}>{}</{element}>", soap::xml_escape(value)));
+        }
+    };
+    text("dc:creator", "", meta.artist.as_deref());
+    text("upnp:artist", "", meta.artist.as_deref());
+    text("upnp:artist", r#" role="AlbumArtist""#, meta.album_artist.as_deref());
+    text("upnp:author", r#" role="Composer""#, meta.composer.as_deref());
+    text("upnp:album", "", meta.album.as_deref());
+    match meta.nfo.as_ref().filter(|n| !n.genres.is_empty()) {
+        Some(nfo) => nfo.genres.iter().for_each(|g| text("upnp:genre", "", Some(g))),
+        None => text("upnp:genre", "", meta.genre.as_deref()),
+    }
+    if let Some(nfo) = &meta.nfo {
+        text("dc:description", "", nfo.outline.as_deref().or(nfo.plot.as_deref()));
+        text("upnp:longDescription", "", nfo.plot.as_deref());
+        text("upnp:rating", "", nfo.rating.as_deref());
+        nfo.directors.iter().for_each(|d| text("upnp:director", "", Some(d)));
+        nfo.actors.iter().for_each(|a| text("upnp:actor", "", Some(a)));
+    }
+    let mut languages: Vec<&str> = Vec::new();
+    for language in meta.audio_tracks.iter().filter_map(|t| t.language.as_deref()) {
+        if !languages.contains(&language) {
+            languages.push(language);
+            text("dc:language", "", Some(language));
+        }
+    }
+    if let Some(track) = meta.track {
+        out.push_str(&format!("<upnp:originalTrackNumber>{track}</upnp:originalTrackNumber>"));
+    }
+    if let Some(disc) = meta.disc {
+        out.push_str(&format!("<upnp:originalDiscNumber>{disc}</upnp:originalDiscNumber>"));
+    }
+    out
+}
+
+/// Series, season and episode number of an episode. Date-based episodes have neither
+/// season nor number.
+fn episode_elements(episode: &Episode) -> String {
+    let mut out = format!("<upnp:seriesTitle>{}</upnp:seriesTitle>", soap::xml_escape(&episode.series));
+    if let Some(number) = episode.episode {
+        out.push_str(&format!("<upnp:episodeSeason>{}</upnp:episodeSeason>", episode.season));
+        out.push_str(&format!("<upnp:episodeNumber>{number}</upnp:episodeNumber>"));
+    }
+    out
+}
+
 // ── Browse helpers ─────────────────────────────────────────────────────────────
 
-/// Build a Browse response for a list of media items with pagination.
-fn browse_items_response(
-    items: &[&MediaItem],
+/// Render one child of a container as a DIDL-Lite fragment.
+fn child_element(
+    tree: &BrowseTree,
+    lib: &MediaLibrary,
+    child: &Child,
     parent_id: &str,
     headers: &HeaderMap,
-    starting_index: u32,
-    requested_count: u32,
-) -> Response {
-    let total_matches = items.len();
-    let paged = apply_pagination(items, starting_index, requested_count);
-    let number_returned = paged.len();
-    let elements: String = paged
-        .iter()
-        .map(|item| item_element(item, parent_id, headers))
-        .collect();
-    let didl_xml = didl_lite_wrap(&elements);
-    let inner = format!(
-        "<Result>{}</Result><NumberReturned>{}</NumberReturned><TotalMatches>{}</TotalMatches><UpdateID>1</UpdateID>",
-        soap::xml_escape(&didl_xml),
-        number_returned,
-        total_matches,
-    );
-    ok_xml(soap_response("Browse", &inner))
+) -> String {
+    match *child {
+        Child::Container(i) => {
+            render_container(&tree.containers[i], lib, headers)
+        }
+        Child::Item(i) => item_element(&lib.items[i], parent_id, headers, &lib.titles),
+        Child::Chapter(i, n) => chapter_element(&lib.items[i], n, parent_id, headers),
+    }
 }
 
-/// Build a BrowseMetadata response for a single container element.
-fn browse_metadata_container(id: &str, parent_id: &str, title: &str, child_count: usize) -> Response {
-    let element = container_element(id, parent_id, title, child_count);
-    let didl_xml = didl_lite_wrap(&element);
+/// Wrap rendered DIDL-Lite elements into a Browse SOAP response.
+fn browse_response(elements: &str, number_returned: usize, total_matches: usize, update_id: u32) -> Response {
+    let didl_xml = didl_lite_wrap(elements);
     let inner = format!(
-        "<Result>{}</Result><NumberReturned>1</NumberReturned><TotalMatches>1</TotalMatches><UpdateID>1</UpdateID>",
+        "<Result>{}</Result><NumberReturned>{}</NumberReturned><TotalMatches>{}</TotalMatches><UpdateID>{}</UpdateID>",
         soap::xml_escape(&didl_xml),
+        number_returned,
+        total_matches,
+        update_id,
     );
     ok_xml(soap_response("Browse", &inner))
 }
@@ -210,14 +382,8 @@ fn browse_metadata_container(id: &str, parent_id: &str, title: &str, child_count
 // ── Browse handler ────────────────────────────────────────────────────────────
 
 /// Full Browse handler implementing BrowseDirectChildren and BrowseMetadata
-/// with pagination, four-container hierarchy, and 701 fault for unknown ObjectIDs.
+/// with pagination over the cached BrowseTree, and 701 fault for unknown ObjectIDs.
 async fn handle_browse(state: &AppState, headers: &HeaderMap, body: &str) -> Response {
-    // Derive container UUIDs (deterministic, cheap)
-    let videos_id = soap::container_uuid(soap::CONTAINER_VIDEOS);
-    let music_id  = soap::container_uuid(soap::CONTAINER_MUSIC);
-    let photos_id = soap::container_uuid(soap::CONTAINER_PHOTOS);
-    let all_id    = soap::container_uuid(soap::CONTAINER_ALL_MEDIA);
-
     // Parse Browse parameters from SOAP body
     let object_id: &str = match extract_soap_param(body, "ObjectID") {
         Some(v) => v,
@@ -234,110 +400,43 @@ async fn handle_browse(state: &AppState, headers: &HeaderMap, body: &str) -> Res
         .and_then(|s| s.parse().ok())
         .unwrap_or(0);
 
-    // Acquire library read guard
+    // Acquire library read guard; the tree's item indexes are valid while it is held.
     let lib = state.library.read().expect("library lock poisoned");
-
-    // Pre-compute filtered item lists for use in both BrowseDirectChildren and BrowseMetadata
-    let video_items: Vec<&MediaItem> = lib.items.iter()
-        .filter(|it| it.kind == MediaKind::Video)
-        .collect();
-    let audio_items: Vec<&MediaItem> = lib.items.iter()
-        .filter(|it| it.kind == MediaKind::Audio)
-        .collect();
-    let image_items: Vec<&MediaItem> = lib.items.iter()
-        .filter(|it| it.kind == MediaKind::Image)
-        .collect();
-    let all_items: Vec<&MediaItem> = lib.items.iter().collect();
-
-    let videos_id_str = videos_id.to_string();
-    let music_id_str = music_id.to_string();
-    let photos_id_str = photos_id.to_string();
-    let all_id_str = all_id.to_string();
+    let update_id = lib.update_id;
+    let tree = state.browse_cache.get(&lib);
 
     match browse_flag {
-        "BrowseDirectChildren" => {
-            // Build the four containers definition (for root browsing)
-            let containers: Vec<(String, String, usize)> = vec![
-                (videos_id_str.clone(), "Videos".to_string(), video_items.len()),
-                (music_id_str.clone(),  "Music".to_string(),  audio_items.len()),
-                (photos_id_str.clone(), "Photos".to_string(), image_items.len()),
-                (all_id_str.clone(),    "All Media".to_string(), all_items.len()),
-            ];
-
-            match object_id {
-                "0" => {
-                    // Root: return four containers with pagination
-                    let total_matches = 4;
-                    let paged = apply_pagination(&containers, starting_index, requested_count);
-                    let number_returned = paged.len();
-                    let elements: String = paged
-                        .iter()
-                        .map(|(id, title, count)| container_element(id, "0", title, *count))
-                        .collect();
-                    let didl_xml = didl_lite_wrap(&elements);
-                    let inner = format!(
-                        "<Result>{}</Result><NumberReturned>{}</NumberReturned><TotalMatches>{}</TotalMatches><UpdateID>1</UpdateID>",
-                        soap::xml_escape(&didl_xml),
-                        number_returned,
-                        total_matches,
-                    );
-                    ok_xml(soap_response("Browse", &inner))
-                }
-                id if id == videos_id_str => {
-                    browse_items_response(&video_items, &videos_id_str, headers, starting_index, requested_count)
-                }
-                id if id == music_id_str => {
-                    browse_items_response(&audio_items, &music_id_str, headers, starting_index, requested_count)
-                }
-                id if id == photos_id_str => {
-                    browse_items_response(&image_items, &photos_id_str, headers, starting_index, requested_count)
-                }
-                id if id == all_id_str => {
-                    browse_items_response(&all_items, &all_id_str, headers, starting_index, requested_count)
-                }
-                _ => {
-                    tracing::debug!("Browse unknown ObjectID: {}", object_id);
-                    soap_fault(701, "No such object").into_response()
-                }
+        "BrowseDirectChildren" => match tree.container(object_id) {
+            Some(container) => {
+                let paged = apply_pagination(&container.children, starting_index, requested_count);
+                let elements: String = paged
+                    .iter()
+                    .map(|child| child_element(&tree, &lib, child, &container.id, headers))
+                    .collect();
+                browse_response(&elements, paged.len(), container.children.len(), update_id)
             }
-        }
+            None => {
+                tracing::debug!("Browse unknown ObjectID: {}", object_id);
+                soap_fault(701, "No such object").into_response()
+            }
+        },
         "BrowseMetadata" => {
-            match object_id {
-                "0" => browse_metadata_container("0", "-1", "Root", 4),
-                id if id == videos_id_str => {
-                    browse_metadata_container(&videos_id_str, "0", "Videos", video_items.len())
-                }
-                id if id == music_id_str => {
-                    browse_metadata_container(&music_id_str, "0", "Music", audio_items.len())
-                }
-                id if id == photos_id_str => {
-                    browse_metadata_container(&photos_id_str, "0", "Photos", image_items.len())
-                }
-                id if id == all_id_str => {
-                    browse_metadata_container(&all_id_str, "0", "All Media", all_items.len())
-                }
-                _ => {
-                    // Search lib.items for a media item with matching UUID
-                    if let Some(item) = lib.items.iter().find(|it| it.id.to_string() == object_id) {
-                        // Determine parent container for this item
-                        let parent_id = match item.kind {
-                            MediaKind::Video => &videos_id_str,
-                            MediaKind::Audio => &music_id_str,
-                            MediaKind::Image => &photos_id_str,
-                            _ => &all_id_str,
-                        };
-                        let element = item_element(item, parent_id, headers);
-                        let didl_xml = didl_lite_wrap(&element);
-                        let inner = format!(
-                            "<Result>{}</Result><NumberReturned>1</NumberReturned><TotalMatches>1</TotalMatches><UpdateID>1</UpdateID>",
-                            soap::xml_escape(&didl_xml),
-                        );
-                        ok_xml(soap_response("Browse", &inner))
-                    } else {
-                        tracing::debug!("BrowseMetadata unknown ObjectID: {}", object_id);
-                        soap_fault(701, "No such object").into_response()
-                    }
-                }
+            if let Some(c) = tree.container(object_id) {
+                let element = render_container(c, &lib, headers);
+                browse_response(&element, 1, 1, update_id)
+            } else if let Some(i) = tree.item_index(object_id) {
+                let item = &lib.items[i];
+                let parent_id = tree.item_parent(i, item.kind);
+                let element = item_element(item, &parent_id, headers, &lib.titles);
+                browse_response(&element, 1, 1, update_id)
+            } else if let Some((i, n)) = tree.chapter(object_id) {
+                let item = &lib.items[i];
+                let parent_id = soap::chapters_container_uuid(&item.id).to_string();
+                let element = chapter_element(item, n, &parent_id, headers);
+                browse_response(&element, 1, 1, update_id)
+            } else {
+                tracing::debug!("BrowseMetadata unknown ObjectID: {}", object_id);
+                soap_fault(701, "No such object").into_response()
             }
         }
         _ => {
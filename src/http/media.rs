@@ -7,27 +7,56 @@ use axum::{
 use http_range_header::parse_range_header;
 use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio_util::io::ReaderStream;
+use std::path::PathBuf;
 use uuid::Uuid;
+use crate::http::soap;
 use crate::http::state::AppState;
 use crate::media::library::MediaItem;
+use crate::media::metadata::format_seconds;
+use crate::media::seek;
 
-// DLNA header constants — values per DLNA spec and cross-verified with working DLNA servers
-// DLNA.ORG_OP=01: byte seek supported (bit 0), time seek not supported (bit 1)
+// DLNA header values — per DLNA spec and cross-verified with working DLNA servers
+// DLNA.ORG_OP: byte seek supported (bit 0), time seek (bit 1) where media::seek can map times
 // DLNA.ORG_CI=0: content is not converted/transcoded
 // DLNA.ORG_FLAGS: 32 hex chars (8 significant + 24 zero padding, required length)
 //   01700000 = STREAMING_TRANSFER_MODE | BACKGROUND_TRANSFER_MODE | CONNECTION_STALL | DLNA_V15
-const DLNA_CONTENT_FEATURES: &str =
-    "DLNA.ORG_OP=01;DLNA.ORG_CI=0;DLNA.ORG_FLAGS=01700000000000000000000000000000";
 const DLNA_TRANSFER_MODE: &str = "Streaming";
+const TIME_SEEK_RANGE: &str = "timeseekrange.dlna.org";
+// Samsung TVs ask for the subtitle URL of a video with getCaptionInfo.sec: 1.
+const GET_CAPTION_INFO: &str = "getcaptioninfo.sec";
+const CAPTION_INFO: &str = "captioninfo.sec";
 
 /// Look up MediaItem by UUID string. Returns None if UUID is invalid or item not found.
 /// Lock is acquired and released within this function — safe to call before any .await.
-fn lookup_item(state: &AppState, id_str: &str) -> Option<MediaItem> {
+pub fn lookup_item(state: &AppState, id_str: &str) -> Option<MediaItem> {
     let id = Uuid::parse_str(id_str).ok()?;
     let lib = state.library.read().unwrap();
     lib.items.iter().find(|i| i.id == id).cloned()
 }
 
+/// Re-resolve a scanned path and check it against the library's symlink policy, so a
+/// file swapped for a link after the scan cannot expose anything outside the roots.
+/// Returns the resolved path to open, or the status to respond with.
+pub async fn resolve_servable(state: &AppState, path: &std::path::Path) -> Result<PathBuf, StatusCode> {
+    let resolved = tokio::fs::canonicalize(path).await.map_err(|e| {
+        tracing::error!("Failed to resolve {}: {}", path.display(), e);
+        StatusCode::NOT_FOUND
+    })?;
+    let (policy, roots) = {
+        let lib = state.library.read().unwrap();
+        (lib.symlinks, lib.roots.clone())
+    };
+    if !policy.allows(path, &resolved, &roots) {
+        tracing::warn!(
+            "Refusing to serve {}: resolves to {} (symlink policy)",
+            path.display(),
+            resolved.display()
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(resolved)
+}
+
 /// Build the standard DLNA response headers present on ALL media responses (GET + HEAD).
 /// Returns a HeaderMap with: Content-Type, Content-Length, Accept-Ranges,
 /// transferMode.dlna.org, contentFeatures.dlna.org.
@@ -49,25 +78,51 @@ fn dlna_headers(item: &MediaItem) -> HeaderMap {
         HeaderName::from_static("transfermode.dlna.org"),
         HeaderValue::from_static(DLNA_TRANSFER_MODE),
     );
+    let op = soap::dlna_op(seek::seekable_duration(item).is_some());
+    let features = format!("DLNA.ORG_OP={op};DLNA.ORG_CI=0;DLNA.ORG_FLAGS={}", soap::DLNA_FLAGS);
     headers.insert(
         HeaderName::from_static("contentfeatures.dlna.org"),
-        HeaderValue::from_static(DLNA_CONTENT_FEATURES),
+        HeaderValue::from_str(&features).expect("features are ASCII"),
     );
     headers
 }
 
+/// Add `CaptionInfo.sec` with the URL of the item's first subtitle when the request
+/// asks for it; Samsung TVs load subtitles this way rather than from the DIDL-Lite.
+fn add_caption_info(item: &MediaItem, req_headers: &HeaderMap, headers: &mut HeaderMap) {
+    if !req_headers.contains_key(GET_CAPTION_INFO) {
+        return;
+    }
+    let Some(subtitle) = item.meta.subtitles.first() else { return };
+    let url = soap::build_subtitle_url(req_headers, &subtitle.id);
+    if let Ok(url) = HeaderValue::from_str(&url) {
+        headers.insert(HeaderName::from_static(CAPTION_INFO), url);
+    }
+}
+
 /// HEAD /media/{id} — returns 200 with all DLNA headers and NO body.
-/// Does NOT open the file (avoids unnecessary disk I/O on Samsung TV pre-flight checks).
+/// Does NOT open the file (avoids unnecessary disk I/O on Samsung TV pre-flight checks),
+/// unless a TimeSeekRange.dlna.org header asks which bytes a time range maps to.
 pub async fn serve_media_head(
     State(state): State<AppState>,
     Path(id_str): Path<String>,
+    req_headers: HeaderMap,
 ) -> Response {
     let item = match lookup_item(&state, &id_str) {
         Some(i) => i,
         None => return StatusCode::NOT_FOUND.into_response(),
     };
+    let mut headers = dlna_headers(&item);
+    add_caption_info(&item, &req_headers, &mut headers);
+    if let Some(npt) = req_headers.get(TIME_SEEK_RANGE) {
+        let path = match resolve_servable(&state, &item.path).await {
+            Ok(p) => p,
+            Err(status) => return status.into_response(),
+        };
+        return time_seek_response(&item, &path, npt, headers, false).await;
+    }
     // Return 200 with all DLNA headers and NO body. Do NOT open the file.
-    (StatusCode::OK, dlna_headers(&item)).into_response()
+    (StatusCode::OK, headers).into_response()
 }
 
 /// GET /media/{id} — stream full file or partial content per RFC 7233 Range header.
@@ -82,7 +137,17 @@ pub async fn serve_media_get(
         None => return StatusCode::NOT_FOUND.into_response(),
     };
 
-    let headers = dlna_headers(&item);
+    let path = match resolve_servable(&state, &item.path).await {
+        Ok(p) => p,
+        Err(status) => return status.into_response(),
+    };
+    let mut headers = dlna_headers(&item);
+    add_caption_info(&item, &req_headers, &mut headers);
+
+    // A time range wins over a byte range; renderers send one or the other.
+    if let Some(npt) = req_headers.get(TIME_SEEK_RANGE) {
+        return time_seek_response(&item, &path, npt, headers, true).await;
+    }
 
     // Check for Range header
     if let Some(range_val) = req_headers.get(axum::http::header::RANGE) {
@@ -100,11 +165,11 @@ pub async fn serve_media_get(
                     .into_response();
             }
         };
-        return range_response(&item, &range_str, headers).await;
+        return range_response(&item, &path, &range_str, headers).await;
     }
 
     // Full GET — stream entire file
-    let file = match tokio::fs::File::open(&item.path).await {
+    let file = match tokio::fs::File::open(&path).await {
         Ok(f) => f,
         Err(e) => {
             tracing::error!("Failed to open file {}: {}", item.path.display(), e);
@@ -119,7 +184,7 @@ pub async fn serve_media_get(
 /// Handle a Range request. Returns 206 Partial Content or 416 Range Not Satisfiable.
 /// Decision (CONTEXT.md): multi-part ranges — validate requires non-overlapping but we serve first
 /// valid range only by cloning the vec and taking the first element.
-async fn range_response(item: &MediaItem, range_str: &str, mut headers: HeaderMap) -> Response {
+async fn range_response(item: &MediaItem, path: &std::path::Path, range_str: &str, mut headers: HeaderMap) -> Response {
     // Parse Range header string (e.g., "bytes=0-99", "bytes=-500")
     let parsed = match parse_range_header(range_str) {
         Ok(p) => p,
@@ -163,7 +228,7 @@ async fn range_response(item: &MediaItem, range_str: &str, mut headers: HeaderMa
     let length = end - start + 1;
 
     // Open file, seek to start, read exactly `length` bytes
-    let mut file = match tokio::fs::File::open(&item.path).await {
+    let mut file = match tokio::fs::File::open(path).await {
         Ok(f) => f,
         Err(e) => {
             tracing::error!("Range response: failed to open file {}: {}", item.path.display(), e);
@@ -194,3 +259,67 @@ async fn range_response(item: &MediaItem, range_str: &str, mut headers: HeaderMa
     let body = Body::from_stream(stream);
     (StatusCode::PARTIAL_CONTENT, headers, body).into_response()
 }
+
+/// Handle a DLNA time-seek request (`TimeSeekRange.dlna.org: npt=START-[END]`).
+/// Returns 200 with the bytes the time range maps to (none for HEAD) and a
+/// TimeSeekRange.dlna.org header stating the times and bytes served; 406 if the item
+/// cannot be time-seeked, 400 for a malformed range, 416 for one past the end.
+async fn time_seek_response(
+    item: &MediaItem,
+    path: &std::path::Path,
+    npt: &HeaderValue,
+    mut headers: HeaderMap,
+    with_body: bool,
+) -> Response {
+    let Some(duration) = seek::seekable_duration(item) else {
+        return StatusCode::NOT_ACCEPTABLE.into_response();
+    };
+    let Some(range) = npt.to_str().ok().and_then(seek::parse_npt) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if range.start >= duration {
+        return StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+    }
+    let end_time = range.end.map_or(duration, |end| end.min(duration));
+
+    let size = item.file_size;
+    let start = seek::byte_offset(path, item.mime, size, duration, range.start);
+    if start >= size {
+        return StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+    }
+    let end = range.end.map_or(size, |end| seek::byte_offset(path, item.mime, size, duration, end));
+    let end = end.clamp(start + 1, size);
+    let length = end - start;
+
+    let time_seek_range = format!(
+        "npt={}-{}/{} bytes={}-{}/{}",
+        format_seconds(range.start),
+        format_seconds(end_time),
+        format_seconds(duration),
+        start,
+        end - 1,
+        size
+    );
+    headers.insert(
+        HeaderName::from_static(TIME_SEEK_RANGE),
+        HeaderValue::from_str(&time_seek_range).expect("range is ASCII"),
+    );
+    headers.insert(axum::http::header::CONTENT_LENGTH, HeaderValue::from(length));
+    if !with_body {
+        return (StatusCode::OK, headers).into_response();
+    }
+
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(f) => f,
+        Err(e) => {
+            tracing::error!("Time seek: failed to open file {}: {}", item.path.display(), e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if let Err(e) = file.seek(std::io::SeekFrom::Start(start)).await {
+        tracing::error!("Time seek: failed to seek in file {}: {}", item.path.display(), e);
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    let body = Body::from_stream(ReaderStream::new(file.take(length)));
+    (StatusCode::OK, headers, body).into_response()
+}
@@ -1,7 +1,10 @@
+pub mod art;
+pub mod browse;
 pub mod content_directory;
 pub mod media;
 pub mod soap;
 pub mod state;
+pub mod subtitle;
 pub mod description;
 
 use axum::{routing::get, Router};
@@ -12,6 +15,8 @@ pub fn build_router(state: AppState) -> Router {
     Router::new()
         // Phase 3 — implemented in media.rs
         .route("/media/{id}", get(media::serve_media_get).head(media::serve_media_head))
+        .route("/art/{id}", get(art::serve_art))
+        .route("/subtitle/{id}", get(subtitle::serve_subtitle))
         // Phase 4 — device and service description XML
         .route("/device.xml", get(description::serve_device_xml))
         .route("/cds/scpd.xml", get(description::serve_cds_scpd))
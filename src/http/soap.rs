@@ -12,6 +12,21 @@ pub const CONTAINER_VIDEOS: &str = "Videos";
 pub const CONTAINER_MUSIC: &str = "Music";
 pub const CONTAINER_PHOTOS: &str = "Photos";
 pub const CONTAINER_ALL_MEDIA: &str = "All Media";
+pub const CONTAINER_FOLDERS: &str = "Folders";
+/// Seed prefix for named library containers; never shown as a title.
+pub const CONTAINER_LIBRARY: &str = "Library";
+/// Sub-containers of Music. Their IDs are scoped under `CONTAINER_MUSIC`
+/// (see `music_container_uuid`).
+pub const CONTAINER_ARTISTS: &str = "Artists";
+pub const CONTAINER_ALBUM_ARTISTS: &str = "Album Artists";
+pub const CONTAINER_ALBUMS: &str = "Albums";
+pub const CONTAINER_GENRES: &str = "Genres";
+pub const CONTAINER_YEARS: &str = "Years";
+/// Series → Season → Episode view under Videos; IDs scoped under `CONTAINER_VIDEOS`
+/// (see `video_container_uuid`).
+pub const CONTAINER_TV_SHOWS: &str = "TV Shows";
+/// Seed prefix for the chapter list of an item and its chapters; never shown as a title.
+pub const CONTAINER_CHAPTERS: &str = "Chapters";
 
 // ── SOAP envelope builder ─────────────────────────────────────────────────────
 
@@ -122,23 +137,35 @@ pub fn apply_pagination<T>(
 
 /// Build the DLNA protocolInfo fourth-field string for a `<res>` element.
 ///
-/// - With profile: `http-get:*:{mime}:DLNA.ORG_PN={profile};DLNA.ORG_OP=01;DLNA.ORG_CI=0;DLNA.ORG_FLAGS={DLNA_FLAGS}`
-/// - Without profile: `http-get:*:{mime}:DLNA.ORG_OP=01;DLNA.ORG_CI=0;DLNA.ORG_FLAGS={DLNA_FLAGS}`
+/// - With profile: `http-get:*:{mime}:DLNA.ORG_PN={profile};DLNA.ORG_OP={op};DLNA.ORG_CI=0;DLNA.ORG_FLAGS={DLNA_FLAGS}`
+/// - Without profile: `http-get:*:{mime}:DLNA.ORG_OP={op};DLNA.ORG_CI=0;DLNA.ORG_FLAGS={DLNA_FLAGS}`
 ///
+/// `op` is `01` (byte seek), or `11` when `time_seek` is set (see `dlna_op`).
 /// Never uses wildcard `*` as a profile fallback (CONTEXT.md locked decision).
-pub fn build_protocol_info(mime: &'static str, dlna_profile: Option<&'static str>) -> String {
+pub fn build_protocol_info(mime: &'static str, dlna_profile: Option<&'static str>, time_seek: bool) -> String {
+    let op = dlna_op(time_seek);
     match dlna_profile {
         Some(profile) => format!(
-            "http-get:*:{}:DLNA.ORG_PN={};DLNA.ORG_OP=01;DLNA.ORG_CI=0;DLNA.ORG_FLAGS={}",
-            mime, profile, DLNA_FLAGS
+            "http-get:*:{}:DLNA.ORG_PN={};DLNA.ORG_OP={};DLNA.ORG_CI=0;DLNA.ORG_FLAGS={}",
+            mime, profile, op, DLNA_FLAGS
         ),
         None => format!(
-            "http-get:*:{}:DLNA.ORG_OP=01;DLNA.ORG_CI=0;DLNA.ORG_FLAGS={}",
-            mime, DLNA_FLAGS
+            "http-get:*:{}:DLNA.ORG_OP={};DLNA.ORG_CI=0;DLNA.ORG_FLAGS={}",
+            mime, op, DLNA_FLAGS
         ),
     }
 }
 
+/// The DLNA.ORG_OP value: bit 0 is byte seek (Range), always supported; bit 1 is time
+/// seek (TimeSeekRange.dlna.org), for items `media::seek` can map.
+pub fn dlna_op(time_seek: bool) -> &'static str {
+    if time_seek {
+        "11"
+    } else {
+        "01"
+    }
+}
+
 // ── Container UUID derivation ─────────────────────────────────────────────────
 
 /// Derive a stable UUIDv5 for a container by name, using the machine-specific namespace.
@@ -149,6 +176,66 @@ pub fn container_uuid(name: &str) -> uuid::Uuid {
     uuid::Uuid::new_v5(&crate::media::metadata::MACHINE_NAMESPACE, name.as_bytes())
 }
 
+/// Derive a stable UUIDv5 for a directory in the Folders view from its canonical path.
+///
+/// Seeded with `CONTAINER_FOLDERS` plus a NUL separator so a directory can never collide
+/// with a top-level container name. Same directory on the same machine → same ID.
+pub fn folder_container_uuid(dir: &std::path::Path) -> uuid::Uuid {
+    scoped_uuid(&[CONTAINER_FOLDERS.as_bytes(), dir.as_os_str().as_encoded_bytes()])
+}
+
+/// Derive a stable UUIDv5 for a named library's top-level container.
+pub fn library_container_uuid(name: &str) -> uuid::Uuid {
+    scoped_uuid(&[CONTAINER_LIBRARY.as_bytes(), name.as_bytes()])
+}
+
+/// Derive a stable UUIDv5 for a directory inside a named library. Scoped by library
+/// name so a directory shared with the Folders view (or another library) gets its
+/// own ID in each place, keeping every parentID chain unambiguous.
+pub fn library_folder_uuid(name: &str, dir: &std::path::Path) -> uuid::Uuid {
+    scoped_uuid(&[CONTAINER_LIBRARY.as_bytes(), name.as_bytes(), dir.as_os_str().as_encoded_bytes()])
+}
+
+/// Derive a stable UUIDv5 for a container in the Music hierarchy from its path of
+/// grouping keys, e.g. `["Artists", "miles davis", "kind of blue"]`. Keys are the
+/// case-folded tag values, so the ID survives restarts and rescans.
+pub fn music_container_uuid(parts: &[&str]) -> uuid::Uuid {
+    view_container_uuid(CONTAINER_MUSIC, parts)
+}
+
+/// Derive a stable UUIDv5 for a container under Videos, e.g. `["TV Shows", "the office", "2"]`.
+/// Series keys are case-folded names, season keys the season number or year.
+pub fn video_container_uuid(parts: &[&str]) -> uuid::Uuid {
+    view_container_uuid(CONTAINER_VIDEOS, parts)
+}
+
+/// Derive a stable UUIDv5 for a date container under Photos, e.g. `["2023", "01"]`.
+pub fn photo_container_uuid(parts: &[&str]) -> uuid::Uuid {
+    view_container_uuid(CONTAINER_PHOTOS, parts)
+}
+
+/// Derive a stable UUIDv5 for the container listing the chapters of item `item_id`.
+pub fn chapters_container_uuid(item_id: &uuid::Uuid) -> uuid::Uuid {
+    scoped_uuid(&[CONTAINER_CHAPTERS.as_bytes(), item_id.as_bytes()])
+}
+
+/// Derive a stable UUIDv5 for chapter `index` (from 0) of item `item_id`.
+pub fn chapter_uuid(item_id: &uuid::Uuid, index: usize) -> uuid::Uuid {
+    scoped_uuid(&[CONTAINER_CHAPTERS.as_bytes(), item_id.as_bytes(), index.to_string().as_bytes()])
+}
+
+fn view_container_uuid(view: &str, parts: &[&str]) -> uuid::Uuid {
+    let mut seed: Vec<&[u8]> = vec![view.as_bytes()];
+    seed.extend(parts.iter().map(|p| p.as_bytes()));
+    scoped_uuid(&seed)
+}
+
+/// UUIDv5 over NUL-joined parts, in the machine namespace.
+fn scoped_uuid(parts: &[&[u8]]) -> uuid::Uuid {
+    let seed = parts.join(&0u8);
+    uuid::Uuid::new_v5(&crate::media::metadata::MACHINE_NAMESPACE, &seed)
+}
+
 // ── dc:date formatting ────────────────────────────────────────────────────────
 
 /// Return an ISO 8601 date string (YYYY-MM-DD) from the file modification time.
@@ -172,11 +259,24 @@ pub fn format_dc_date(path: &std::path::Path) -> String {
 /// Reads the Host header from the request; falls back to `localhost:8200` if absent.
 /// This is the most portable approach for dual-stack IPv4/IPv6 binds (CONTEXT.md discretion).
 pub fn build_res_url(headers: &axum::http::HeaderMap, item_id: &uuid::Uuid) -> String {
-    let host = headers
+    format!("http://{}/media/{}", request_host(headers), item_id)
+}
+
+/// Build the `upnp:albumArtURI` URL for a media item's cover.
+pub fn build_art_url(headers: &axum::http::HeaderMap, item_id: &uuid::Uuid) -> String {
+    format!("http://{}/art/{}", request_host(headers), item_id)
+}
+
+/// Build the URL of a sidecar subtitle, served from `/subtitle/{id}`.
+pub fn build_subtitle_url(headers: &axum::http::HeaderMap, subtitle_id: &uuid::Uuid) -> String {
+    format!("http://{}/subtitle/{}", request_host(headers), subtitle_id)
+}
+
+fn request_host(headers: &axum::http::HeaderMap) -> &str {
+    headers
         .get(axum::http::header::HOST)
         .and_then(|v| v.to_str().ok())
-        .unwrap_or("localhost:8200");
-    format!("http://{}/media/{}", host, item_id)
+        .unwrap_or("localhost:8200")
 }
 
 // ── XML escaping ──────────────────────────────────────────────────────────────
@@ -1,12 +1,15 @@
 use std::sync::{Arc, RwLock};
+use crate::http::browse::BrowseCache;
 use crate::media::library::MediaLibrary;
 
 /// Shared application state injected into all route handlers via axum::extract::State.
 /// Arc provides cheap clone; RwLock provides thread-safe read access.
-/// Write-once at startup (scan), then read-only for server lifetime.
+/// The library is written by the scanner, watcher and SIGHUP rescan; the browse
+/// cache follows it via SystemUpdateID.
 #[derive(Clone)]
 pub struct AppState {
     pub library: Arc<RwLock<MediaLibrary>>,
     pub server_uuid: String,   // Stable UUID v5 derived from hostname (Phase 8)
     pub server_name: String,   // Friendly name from --name / config / default (Phase 8)
+    pub browse_cache: Arc<BrowseCache>,
 }
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use uuid::Uuid;
+
+use crate::http::media::resolve_servable;
+use crate::http::state::AppState;
+use crate::media::subtitle::Subtitle;
+
+// Subtitles are fetched whole alongside the video: Interactive transfer, no seeking.
+// DLNA.ORG_FLAGS 00D00000 = BACKGROUND_TRANSFER_MODE | INTERACTIVE_TRANSFER_MODE | DLNA_V15
+const SUBTITLE_TRANSFER_MODE: &str = "Interactive";
+const SUBTITLE_FEATURES: &str = "DLNA.ORG_OP=00;DLNA.ORG_CI=0;DLNA.ORG_FLAGS=00D00000000000000000000000000000";
+
+/// Look up a sidecar subtitle by its UUID among the videos' subtitles.
+/// Lock is acquired and released within this function — safe to call before any .await.
+pub fn lookup_subtitle(state: &AppState, id_str: &str) -> Option<Subtitle> {
+    let id = Uuid::parse_str(id_str).ok()?;
+    let lib = state.library.read().unwrap();
+    lib.items.iter().flat_map(|i| &i.meta.subtitles).find(|s| s.id == id).cloned()
+}
+
+/// GET /subtitle/{id} — a sidecar subtitle file, read on each request.
+pub async fn serve_subtitle(State(state): State<AppState>, Path(id_str): Path<String>) -> Response {
+    let Some(subtitle) = lookup_subtitle(&state, &id_str) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let path = match resolve_servable(&state, &subtitle.path).await {
+        Ok(p) => p,
+        Err(status) => return status.into_response(),
+    };
+    let data = match tokio::fs::read(&path).await {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::warn!("Subtitle {} is no longer readable: {}", subtitle.path.display(), e);
+            return StatusCode::NOT_FOUND.into_response();
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(subtitle.mime));
+    headers.insert(
+        HeaderName::from_static("contentfeatures.dlna.org"),
+        HeaderValue::from_static(SUBTITLE_FEATURES),
+    );
+    headers.insert(
+        HeaderName::from_static("transfermode.dlna.org"),
+        HeaderValue::from_static(SUBTITLE_TRANSFER_MODE),
+    );
+    (StatusCode::OK, headers, data).into_response()
+}
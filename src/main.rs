@@ -26,6 +26,150 @@ async fn wait_for_shutdown() {
     // first Ctrl+C: proceed with graceful shutdown
 }
 
+/// Locate and parse the TOML config file. A parse failure is logged and treated
+/// as "no config file" so a typo never prevents startup or a reload.
+fn load_file_config(explicit: Option<&std::path::Path>) -> Option<config::FileConfig> {
+    config::find_config_file(explicit).and_then(|path| match config::load_config(&path) {
+        Ok(cfg) => {
+            tracing::debug!("Loaded config from {}", path.display());
+            Some(cfg)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to parse config file: {}", e);
+            None
+        }
+    })
+}
+
+/// SIGHUP handler task (OPER-01): re-read the config file, rescan the media roots on
+/// the blocking pool, then swap the new library in under a single short write lock.
+/// Changes the watcher applied meanwhile are replayed onto the new library first.
+///
+/// In-flight /media/{id} streams are unaffected: handlers clone their MediaItem and
+/// release the lock before opening the file, so swapping the library never touches them.
+/// Settings that need a restart (port, name, ...) are reported and otherwise ignored.
+#[cfg(unix)]
+async fn reload_on_sighup(
+    args: cli::Args,
+    mut current: config::Config,
+    library: Arc<RwLock<MediaLibrary>>,
+    scope_tx: tokio::sync::watch::Sender<media::watcher::WatchScope>,
+    mut initial_scan_done: tokio::sync::watch::Receiver<bool>,
+    change_log: media::watcher::ChangeLog,
+) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    // Install the handler before waiting so an early SIGHUP is queued, not fatal.
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("Cannot install SIGHUP handler — reload disabled: {}", e);
+            return;
+        }
+    };
+    let _ = initial_scan_done.wait_for(|done| *done).await;
+
+    while hangup.recv().await.is_some() {
+        tracing::info!("SIGHUP received — reloading config and rescanning");
+
+        let mut reloaded = config::Config::resolve(load_file_config(args.config.as_deref()), &args);
+        let ignored = current.restart_required_changes(&reloaded);
+        if !ignored.is_empty() {
+            tracing::warn!("Config changes require a restart to take effect: {}", ignored.join(", "));
+        }
+        // Keep restart-only settings as they are so the next diff stays accurate.
+        reloaded.port = current.port;
+        reloaded.name = current.name.clone();
+        reloaded.localhost = current.localhost;
+        reloaded.watch = current.watch;
+        // --rebuild-index applies to the startup scan only.
+        reloaded.rebuild_index = false;
+
+        let all_paths = reloaded.all_paths();
+        if all_paths.is_empty() {
+            tracing::warn!("Reload aborted: no media directories configured");
+            continue;
+        }
+
+        let scan_cfg = match scan_config(&reloaded) {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!("Reload aborted: {}", e);
+                continue;
+            }
+        };
+        let scope = media::watcher::WatchScope {
+            roots: all_paths.clone(),
+            config: scan_cfg.clone(),
+        };
+        let paths = all_paths;
+        // Changes the watcher applies from here on may predate what the rescan reads.
+        change_log.start();
+        let mut fresh = match tokio::task::spawn_blocking(move || {
+            media::scanner::scan_with(&paths, &scan_cfg)
+        })
+        .await
+        {
+            Ok(lib) => lib,
+            Err(e) => {
+                tracing::error!("Rescan failed: {}", e);
+                change_log.stop();
+                continue;
+            }
+        };
+
+        let diff = {
+            let mut lib = library.write().expect("library lock poisoned");
+            let replayed = change_log.replay_into(&mut fresh);
+            if replayed > 0 {
+                tracing::debug!("Replayed {} watcher change(s) made during the rescan", replayed);
+            }
+            let diff = lib.diff(&fresh);
+            fresh.update_id = lib.update_id;
+            // New title rules, libraries or roots change what Browse shows (the library
+            // containers and the Folders view) even when no file changed.
+            if !diff.is_empty()
+                || lib.titles != fresh.titles
+                || lib.libraries != fresh.libraries
+                || lib.roots != fresh.roots
+            {
+                fresh.bump_update_id();
+            }
+            *lib = fresh;
+            diff
+        };
+        tracing::info!(
+            "Rescan complete: {} added, {} removed, {} changed",
+            diff.added,
+            diff.removed,
+            diff.changed
+        );
+
+        // Filters may have changed even if the roots did not; unchanged roots stay watched.
+        scope_tx.send_replace(scope);
+        current = reloaded;
+    }
+}
+
+/// Scanner options derived from the resolved config. Fails on an invalid glob or
+/// title rewrite pattern.
+fn scan_config(
+    config: &config::Config,
+) -> Result<media::scanner::ScanConfig, Box<dyn std::error::Error>> {
+    Ok(media::scanner::ScanConfig {
+        index_path: if config.index { config::default_index_path() } else { None },
+        rebuild_index: config.rebuild_index,
+        threads: config.scan_threads,
+        filter: media::filter::ScanFilter::new(&config.include, &config.exclude, config.skip_hidden)?,
+        symlinks: config.follow_symlinks,
+        same_filesystem: config.same_filesystem,
+        max_depth: config.max_depth,
+        libraries: config.libraries.clone(),
+        kind_limits: media::scanner::kind_limits(&config.paths, &config.libraries),
+        titles: media::title::TitleNormalizer::new(config.normalize_titles, &config.title_rewrites)?,
+    })
+}
+
 /// Derive a stable UUID v5 from hostname + server name using DNS namespace.
 /// Combines both inputs so the UUID is stable across restarts on the same machine
 /// with the same name, but changes if the name changes on a different machine or
@@ -55,23 +199,32 @@ async fn main() {
 
     let args = cli::Args::parse();
 
-    let file_config = config::find_config_file(args.config.as_deref())
-        .and_then(|path| {
-            match config::load_config(&path) {
-                Ok(cfg) => {
-                    tracing::debug!("Loaded config from {}", path.display());
-                    Some(cfg)
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to parse config file: {}", e);
-                    None
-                }
-            }
-        });
+    let file_config = load_file_config(args.config.as_deref());
 
     let config = config::Config::resolve(file_config, &args);
 
-    for path in &config.paths {
+    let all_paths = config.all_paths();
+    if all_paths.is_empty() {
+        eprintln!("error: no media directories given (pass paths or set `paths` in the config file)");
+        std::process::exit(1);
+    }
+
+    for (i, library) in config.libraries.iter().enumerate() {
+        if library.name.trim().is_empty() {
+            eprintln!("error: [[library]] entry {} has an empty name", i + 1);
+            std::process::exit(1);
+        }
+        if config.libraries[..i].iter().any(|l| l.name == library.name) {
+            eprintln!("error: duplicate library name: {}", library.name);
+            std::process::exit(1);
+        }
+        if library.paths.is_empty() {
+            eprintln!("error: library \"{}\" has no paths", library.name);
+            std::process::exit(1);
+        }
+    }
+
+    for path in &all_paths {
         if !path.exists() {
             eprintln!("error: path does not exist: {}", path.display());
             std::process::exit(1);
@@ -94,27 +247,97 @@ async fn main() {
         config.port
     );
     tracing::info!("Scanning media directories:");
-    for path in &config.paths {
+    for path in &all_paths {
         tracing::info!("  {}", path.display());
     }
+    for library in &config.libraries {
+        let paths: Vec<String> = library.paths.iter().map(|p| p.display().to_string()).collect();
+        tracing::info!("Library \"{}\": {}", library.name, paths.join(", "));
+    }
 
-    // Synchronous scan -- blocks the thread; acceptable since server has not started yet
-    let library = media::scanner::scan(&config.paths);
-
-    // LOCKED: zero media files found must be an error exit (not a silent empty server)
-    if library.items.is_empty() {
-        eprintln!("error: no media files found in the provided paths -- exiting");
+    let scan_cfg = scan_config(&config).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
         std::process::exit(1);
-    }
+    });
+
+    // Roots and filters shared with the watcher so a SIGHUP reload can re-point it.
+    let (scope_tx, scope_rx) = tokio::sync::watch::channel(media::watcher::WatchScope {
+        roots: all_paths.clone(),
+        config: scan_cfg.clone(),
+    });
+    // Set once the initial scan has finished; SIGHUP rescans and watcher updates wait
+    // for it so they never race a background scan that is still publishing items.
+    let (scan_done_tx, scan_done_rx) = tokio::sync::watch::channel(false);
+    let change_log = media::watcher::ChangeLog::default();
+    let watch_config = config.watch.then(|| media::watcher::WatchConfig {
+        scope: scope_rx,
+        debounce: media::watcher::DEFAULT_DEBOUNCE,
+        scan_done: scan_done_rx.clone(),
+        log: change_log.clone(),
+    });
 
     // Wrap in Arc<RwLock<>> for thread-safe sharing across route handlers.
-    // Write-once at startup, then read-only. std::sync::RwLock is safe here.
-    // If Phase 6+ needs writes (OPER-01 SIGHUP rescan), switch to tokio::sync::RwLock.
-    let library = Arc::new(RwLock::new(library));
+    // std::sync::RwLock is safe here: writers (background scan, watcher, SIGHUP rescan)
+    // hold the lock only to apply an already-computed result, never across an .await.
+    let library = if config.background_scan {
+        // Serve immediately; the library starts empty and fills in batches.
+        let library = Arc::new(RwLock::new(MediaLibrary::new()));
+        // Watch from the start: files changed while the scan runs are re-indexed after it.
+        if let Some(watch_config) = watch_config {
+            tokio::spawn(media::watcher::run(watch_config, Arc::clone(&library)));
+        }
+        let paths = all_paths.clone();
+        let scan_library = Arc::clone(&library);
+        tokio::spawn(async move {
+            let lib = Arc::clone(&scan_library);
+            let result = tokio::task::spawn_blocking(move || {
+                media::scanner::scan_into(&paths, &scan_cfg, &lib)
+            })
+            .await;
+            if let Err(e) = result {
+                tracing::error!("Background scan failed: {}", e);
+            }
+            if scan_library.read().expect("library lock poisoned").items.is_empty() {
+                tracing::warn!("No media files found yet -- serving an empty library");
+            }
+            scan_done_tx.send_replace(true);
+        });
+        library
+    } else {
+        // Synchronous scan -- blocks the thread; acceptable since server has not started yet
+        let library = media::scanner::scan_with(&all_paths, &scan_cfg);
+
+        // LOCKED: zero media files found must be an error exit (not a silent empty server).
+        // --background-scan opts out of this: it serves whatever the scan finds, even nothing.
+        if library.items.is_empty() {
+            eprintln!("error: no media files found in the provided paths -- exiting");
+            std::process::exit(1);
+        }
+
+        let library = Arc::new(RwLock::new(library));
+        scan_done_tx.send_replace(true);
+        if let Some(watch_config) = watch_config {
+            tokio::spawn(media::watcher::run(watch_config, Arc::clone(&library)));
+        }
+        library
+    };
+
+    #[cfg(unix)]
+    tokio::spawn(reload_on_sighup(
+        args,
+        config.clone(),
+        Arc::clone(&library),
+        scope_tx,
+        scan_done_rx,
+        change_log,
+    ));
+    #[cfg(not(unix))]
+    drop((scope_tx, scan_done_rx, change_log));
     let state = http::state::AppState {
         library: Arc::clone(&library),
         server_uuid: server_uuid.clone(),
         server_name: config.name.clone(),
+        browse_cache: Default::default(),
     };
     let app = http::build_router(state);
 
@@ -0,0 +1,134 @@
+//! Album art: cover pictures embedded in media files and cover images next to them.
+
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::media::library::AlbumArt;
+use crate::media::{dlna, mkv};
+use crate::media::sniff::{read_header, sniff};
+
+/// Folder image base names, most preferred first. Matched case-insensitively with
+/// one of `FOLDER_ART_EXTENSIONS`.
+pub const FOLDER_ART_NAMES: &[&str] = &["cover", "folder", "front"];
+const FOLDER_ART_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// Whether `path` is named like a folder image (`cover.jpg`, `Folder.PNG`, ...).
+pub fn is_folder_art(path: &Path) -> bool {
+    folder_art_rank(path).is_some()
+}
+
+fn folder_art_rank(path: &Path) -> Option<usize> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    if !FOLDER_ART_EXTENSIONS.contains(&extension.as_str()) {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?.to_ascii_lowercase();
+    FOLDER_ART_NAMES.iter().position(|name| *name == stem)
+}
+
+/// The preferred folder image in `dir`, if any. Images whose dimensions cannot be
+/// read are passed over.
+pub fn folder_art(dir: &Path) -> Option<AlbumArt> {
+    let mut candidates: Vec<(usize, PathBuf)> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| Some((folder_art_rank(&path)?, path)))
+        .collect();
+    candidates.sort();
+    candidates.into_iter().find_map(|(_, path)| {
+        let mime = read_header(&path).and_then(|header| sniff(&header))?;
+        if mime != "image/jpeg" && mime != "image/png" {
+            return None;
+        }
+        let size = imagesize::size(&path).ok()?;
+        Some(AlbumArt {
+            mime: mime.to_string(),
+            width: size.width as u32,
+            height: size.height as u32,
+            path: Some(path),
+        })
+    })
+}
+
+/// Describe an embedded picture, taking the MIME type from its content rather than
+/// from the tag (which is often missing or wrong).
+pub fn describe(data: &[u8]) -> Option<AlbumArt> {
+    let mime = sniff(data).filter(|m| m.starts_with("image/"))?;
+    let size = imagesize::blob_size(data).ok()?;
+    Some(AlbumArt {
+        mime: mime.to_string(),
+        width: size.width as u32,
+        height: size.height as u32,
+        path: None,
+    })
+}
+
+/// Read the cover picture embedded in the media file at `path`.
+///
+/// - Matroska/WebM: the `cover.*` attachment, else the first image attachment.
+/// - MP4 video: the `covr` atom.
+/// - Everything else (ID3 APIC, FLAC PICTURE, Vorbis, M4A `covr`): symphonia's visuals,
+///   preferring the front cover.
+pub fn embedded_art(path: &Path, mime: &str) -> Option<Vec<u8>> {
+    match mime {
+        "video/x-matroska" | "video/webm" => mkv::cover_attachment(path).map(|a| a.data),
+        "video/mp4" | "video/quicktime" | "video/3gpp" => mp4_poster(path),
+        _ => symphonia_cover(path, mime),
+    }
+}
+
+fn mp4_poster(path: &Path) -> Option<Vec<u8>> {
+    use mp4::Metadata;
+
+    let file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mp4 = mp4::Mp4Reader::read_header(BufReader::new(file), len).ok()?;
+    let poster = mp4.metadata().poster().map(<[u8]>::to_vec);
+    poster
+}
+
+fn symphonia_cover(path: &Path, mime: &str) -> Option<Vec<u8>> {
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::probe::Hint;
+
+    let file = std::fs::File::open(path).ok()?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    hint.mime_type(mime);
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(&hint, mss, &Default::default(), &Default::default())
+        .ok()?;
+    let mut format_metadata = probed.format.metadata();
+    if let Some(cover) = format_metadata.skip_to_latest().and_then(|r| pick_cover(r.visuals())) {
+        return Some(cover.to_vec());
+    }
+    let mut probe_metadata = probed.metadata.get()?;
+    let revision = probe_metadata.skip_to_latest()?;
+    pick_cover(revision.visuals()).map(<[u8]>::to_vec)
+}
+
+/// The front cover among `visuals`, else the first picture.
+pub fn pick_cover(visuals: &[symphonia::core::meta::Visual]) -> Option<&[u8]> {
+    use symphonia::core::meta::StandardVisualKey;
+
+    visuals
+        .iter()
+        .find(|v| v.usage == Some(StandardVisualKey::FrontCover))
+        .or(visuals.first())
+        .map(|v| &*v.data)
+}
+
+/// DLNA image profile for album art, from its type and dimensions. None for formats
+/// without a DLNA profile; the image is still served.
+pub fn dlna_art_profile(art: &AlbumArt) -> Option<&'static str> {
+    let fits = |w: u32, h: u32| art.width <= w && art.height <= h;
+    match art.mime.as_str() {
+        "image/jpeg" if fits(160, 160) => Some("JPEG_TN"),
+        "image/png" if fits(160, 160) => Some("PNG_TN"),
+        mime => dlna::image_profile(mime, art.width, art.height),
+    }
+}
@@ -0,0 +1,129 @@
+//! ASF header reader for Windows Media Audio (.wma): play duration, the audio stream
+//! format and the title/author of the Content Description object.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::media::avi::{self, le32};
+
+/// GUIDs in their on-disk (mixed-endian) byte order.
+const HEADER_OBJECT: [u8; 16] =
+    guid(0x75B22630, 0x668E, 0x11CF, [0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C]);
+const FILE_PROPERTIES: [u8; 16] =
+    guid(0x8CABDCA1, 0xA947, 0x11CF, [0x8E, 0xE4, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65]);
+const STREAM_PROPERTIES: [u8; 16] =
+    guid(0xB7DC0791, 0xA9B7, 0x11CF, [0x8E, 0xE6, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65]);
+const CONTENT_DESCRIPTION: [u8; 16] =
+    guid(0x75B22633, 0x668E, 0x11CF, [0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C]);
+const AUDIO_MEDIA: [u8; 16] =
+    guid(0xF8699E40, 0x5B4D, 0x11CF, [0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B]);
+
+/// GUID (16) + object size (8).
+const OBJECT_HEADER_LEN: usize = 24;
+/// Upper bound for the header object. It holds only metadata, but embedded cover
+/// pictures can make it large.
+const MAX_HEADER_LEN: u64 = 4 * 1024 * 1024;
+/// File Properties flag: the file is a live broadcast and its duration is meaningless.
+const FLAG_BROADCAST: u32 = 0x01;
+
+const fn guid(d1: u32, d2: u16, d3: u16, d4: [u8; 8]) -> [u8; 16] {
+    let (a, b, c) = (d1.to_le_bytes(), d2.to_le_bytes(), d3.to_le_bytes());
+    [a[0], a[1], a[2], a[3], b[0], b[1], c[0], c[1], d4[0], d4[1], d4[2], d4[3], d4[4], d4[5], d4[6], d4[7]]
+}
+
+/// What udlna reads from an ASF file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AsfInfo {
+    /// Play duration in seconds, preroll removed.
+    pub duration: Option<f64>,
+    /// The first audio stream.
+    pub audio: Option<AsfAudio>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+}
+
+/// The WAVEFORMATEX of an audio stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsfAudio {
+    /// Short codec name ("wmav2", "wmapro", ...). See `avi::audio_codec`.
+    pub codec: String,
+    pub channels: u32,
+    pub sample_rate: u32,
+    /// Average bytes per second.
+    pub bitrate: u32,
+    pub bits_per_sample: u32,
+}
+
+/// Read the header object of the ASF file at `path`. None if it is not ASF.
+pub fn read_info(path: &Path) -> Option<AsfInfo> {
+    let mut file = File::open(path).ok()?;
+    let mut head = [0u8; 30];
+    file.read_exact(&mut head).ok()?;
+    if head[..16] != HEADER_OBJECT {
+        return None;
+    }
+    let size = u64::from_le_bytes(head[16..24].try_into().ok()?);
+    if !(30..=MAX_HEADER_LEN).contains(&size) {
+        return None;
+    }
+    let mut objects = vec![0u8; size as usize - head.len()];
+    file.read_exact(&mut objects).ok()?;
+
+    let mut info = AsfInfo::default();
+    let mut at = 0;
+    while at + OBJECT_HEADER_LEN <= objects.len() {
+        let id = &objects[at..at + 16];
+        let len = u64::from_le_bytes(objects[at + 16..at + 24].try_into().ok()?);
+        let end = usize::try_from(len).ok().and_then(|len| at.checked_add(len)).filter(|&end| end <= objects.len());
+        let Some(end) = end.filter(|&end| end >= at + OBJECT_HEADER_LEN) else { break };
+        let body = &objects[at + OBJECT_HEADER_LEN..end];
+        if id == FILE_PROPERTIES && body.len() >= 68 {
+            let play = le64(body, 40) as f64 / 10_000_000.0;
+            let preroll = le64(body, 56) as f64 / 1000.0;
+            let broadcast = le32(body, 64) & FLAG_BROADCAST != 0;
+            info.duration = Some(play - preroll).filter(|&d| d > 0.0 && !broadcast);
+        } else if id == STREAM_PROPERTIES && info.audio.is_none() {
+            info.audio = audio_stream(body);
+        } else if id == CONTENT_DESCRIPTION {
+            (info.title, info.author) = content_description(body);
+        }
+        at = end;
+    }
+    Some(info)
+}
+
+/// The WAVEFORMATEX in the type-specific data of an audio Stream Properties object.
+fn audio_stream(body: &[u8]) -> Option<AsfAudio> {
+    if body.get(..16)? != AUDIO_MEDIA {
+        return None;
+    }
+    let len = le32(body, 40) as usize;
+    let wfx = body.get(54..54usize.checked_add(len)?).filter(|w| w.len() >= 16)?;
+    Some(AsfAudio {
+        codec: avi::audio_codec(u16::from_le_bytes([wfx[0], wfx[1]])),
+        channels: u16::from_le_bytes([wfx[2], wfx[3]]) as u32,
+        sample_rate: le32(wfx, 4),
+        bitrate: le32(wfx, 8),
+        bits_per_sample: u16::from_le_bytes([wfx[14], wfx[15]]) as u32,
+    })
+}
+
+/// Title and author: the first two of five UTF-16LE strings whose byte lengths lead
+/// the object.
+fn content_description(body: &[u8]) -> (Option<String>, Option<String>) {
+    let len = |i: usize| body.get(i * 2..i * 2 + 2).map_or(0, |b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    let text = |from: usize, len: usize| {
+        let bytes = body.get(from..from + len)?;
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        let text = String::from_utf16_lossy(&units);
+        let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+        (!text.is_empty()).then(|| text.to_string())
+    };
+    let (title_len, author_len) = (len(0), len(1));
+    (text(10, title_len), text(10 + title_len, author_len))
+}
+
+fn le64(data: &[u8], at: usize) -> u64 {
+    data.get(at..at + 8).map_or(0, |b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+}
@@ -0,0 +1,190 @@
+//! RIFF/AVI header reader: duration, frame size and stream codecs from the `hdrl` list.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::media::library::AudioTrack;
+
+/// Upper bound for the `hdrl` list. It holds only headers and index pointers, but
+/// some muxers pad it with large JUNK chunks.
+const MAX_HEADER_LIST: u32 = 1024 * 1024;
+
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// What udlna reads from an AVI file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AviInfo {
+    /// Seconds, from the video stream header, else the main header's frame count.
+    pub duration: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub video_codec: Option<String>,
+    pub audio: Vec<AudioTrack>,
+}
+
+/// Read the `avih` main header and the `strh`/`strf` stream headers of the AVI file
+/// at `path`. None if it is not a RIFF AVI file.
+pub fn read_info(path: &Path) -> Option<AviInfo> {
+    let mut file = File::open(path).ok()?;
+    let mut riff = [0u8; 12];
+    file.read_exact(&mut riff).ok()?;
+    if &riff[..4] != b"RIFF" || &riff[8..] != b"AVI " {
+        return None;
+    }
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header).ok()?;
+    let size = u32::from_le_bytes(header[4..8].try_into().ok()?);
+    if &header[..4] != b"LIST" || &header[8..] != b"hdrl" || size > MAX_HEADER_LIST {
+        return None;
+    }
+    let mut hdrl = vec![0u8; size.saturating_sub(4) as usize];
+    file.read_exact(&mut hdrl).ok()?;
+    parse_hdrl(&hdrl)
+}
+
+fn parse_hdrl(hdrl: &[u8]) -> Option<AviInfo> {
+    let mut info = AviInfo::default();
+    let mut frame_us = 0u32;
+    let mut total_frames = 0u32;
+    let mut video_duration = None;
+    let mut found_main_header = false;
+    for (id, body) in chunks(hdrl) {
+        match (id, list_type(id, body)) {
+            (b"avih", _) if body.len() >= 40 => {
+                found_main_header = true;
+                frame_us = le32(body, 0);
+                total_frames = le32(body, 16);
+                info.width = Some(le32(body, 32)).filter(|&w| w > 0);
+                info.height = Some(le32(body, 36)).filter(|&h| h > 0);
+            }
+            (_, Some(b"strl")) => match parse_strl(&body[4..]) {
+                Some(Stream::Video { codec, duration, size }) if info.video_codec.is_none() => {
+                    info.video_codec = codec;
+                    video_duration = duration;
+                    if let Some((width, height)) = size {
+                        info.width = info.width.or(Some(width));
+                        info.height = info.height.or(Some(height));
+                    }
+                }
+                Some(Stream::Audio(track)) => info.audio.push(track),
+                _ => {}
+            },
+            // OpenDML: the main header only counts the frames of the first RIFF chunk.
+            (_, Some(b"odml")) => {
+                if let Some((_, dmlh)) = chunks(&body[4..]).find(|(id, b)| *id == b"dmlh" && b.len() >= 4) {
+                    total_frames = total_frames.max(le32(dmlh, 0));
+                }
+            }
+            _ => {}
+        }
+    }
+    if !found_main_header {
+        return None;
+    }
+    info.duration = video_duration
+        .or_else(|| (frame_us > 0 && total_frames > 0).then(|| total_frames as f64 * frame_us as f64 / 1e6));
+    Some(info)
+}
+
+enum Stream {
+    Video {
+        codec: Option<String>,
+        duration: Option<f64>,
+        size: Option<(u32, u32)>,
+    },
+    Audio(AudioTrack),
+}
+
+fn parse_strl(strl: &[u8]) -> Option<Stream> {
+    let strh = chunks(strl).find(|(id, b)| *id == b"strh" && b.len() >= 36)?.1;
+    let strf = chunks(strl).find(|(id, _)| *id == b"strf").map(|(_, b)| b).unwrap_or_default();
+    match &strh[..4] {
+        b"vids" => {
+            let (scale, rate, length) = (le32(strh, 20), le32(strh, 24), le32(strh, 32));
+            let duration = (scale > 0 && rate > 0 && length > 0).then(|| length as f64 * scale as f64 / rate as f64);
+            // BITMAPINFOHEADER: biWidth, biHeight (negative for top-down), biCompression.
+            let (size, compression) = if strf.len() >= 20 {
+                let height = (le32(strf, 8) as i32).unsigned_abs();
+                (Some((le32(strf, 4), height)), Some(&strf[16..20]))
+            } else {
+                (None, None)
+            };
+            let fourcc = compression.filter(|c| c.iter().any(|&b| b != 0)).unwrap_or(&strh[4..8]);
+            Some(Stream::Video { codec: video_codec(fourcc), duration, size })
+        }
+        b"auds" if strf.len() >= 4 => {
+            // WAVEFORMATEX: wFormatTag, nChannels; the extensible form carries the real
+            // tag at the start of its SubFormat GUID.
+            let mut tag = u16::from_le_bytes([strf[0], strf[1]]);
+            if tag == WAVE_FORMAT_EXTENSIBLE && strf.len() >= 26 {
+                tag = u16::from_le_bytes([strf[24], strf[25]]);
+            }
+            let channels = u16::from_le_bytes([strf[2], strf[3]]) as u32;
+            Some(Stream::Audio(AudioTrack {
+                codec: audio_codec(tag),
+                language: None,
+                channels: Some(channels).filter(|&c| c > 0),
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// RIFF chunks of `data` as (FOURCC, body), honouring the word alignment padding.
+fn chunks(data: &[u8]) -> impl Iterator<Item = (&[u8; 4], &[u8])> {
+    let mut position = 0;
+    std::iter::from_fn(move || {
+        let header = data.get(position..position + 8)?;
+        let id: &[u8; 4] = header[..4].try_into().ok()?;
+        let size = le32(header, 4) as usize;
+        let body = data.get(position + 8..(position + 8).checked_add(size)?)?;
+        position += 8 + size + size % 2;
+        Some((id, body))
+    })
+}
+
+/// The list type of a `LIST` chunk.
+fn list_type<'a>(id: &[u8; 4], body: &'a [u8]) -> Option<&'a [u8]> {
+    (id == b"LIST").then(|| body.get(..4)).flatten()
+}
+
+/// Little-endian u32 at `at`, 0 past the end of `data`. Shared with the ASF reader.
+pub fn le32(data: &[u8], at: usize) -> u32 {
+    data.get(at..at + 4).map_or(0, |b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Short codec name for a video FOURCC; unknown FOURCCs are returned lowercased.
+fn video_codec(fourcc: &[u8]) -> Option<String> {
+    let fourcc = String::from_utf8_lossy(fourcc).trim_matches(['\0', ' ']).to_ascii_uppercase();
+    let name = match fourcc.as_str() {
+        "" => return None,
+        "XVID" | "DIVX" | "DX50" | "FMP4" | "MP4V" | "M4S2" => "mpeg4",
+        "DIV3" | "MP43" => "msmpeg4v3",
+        "H264" | "X264" | "AVC1" => "h264",
+        "HEVC" | "HVC1" | "H265" => "hevc",
+        "MJPG" => "mjpeg",
+        "MPG2" => "mpeg2video",
+        other => return Some(other.to_ascii_lowercase()),
+    };
+    Some(name.to_string())
+}
+
+/// Short codec name for a WAVE format tag; unknown tags are returned as hex.
+/// Also used for the WAVEFORMATEX of ASF (WMA) streams.
+pub fn audio_codec(tag: u16) -> String {
+    match tag {
+        0x0001 => "pcm",
+        0x0050 => "mp2",
+        0x0055 => "mp3",
+        0x00FF | 0x1610 | 0x706D => "aac",
+        0x0160 => "wmav1",
+        0x0161 => "wmav2",
+        0x0162 => "wmapro",
+        0x0163 => "wmalossless",
+        0x2000 => "ac3",
+        0x2001 => "dts",
+        _ => return format!("0x{tag:04x}"),
+    }
+    .to_string()
+}
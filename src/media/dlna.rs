@@ -0,0 +1,219 @@
+//! DLNA media format profiles (the DLNA.ORG_PN protocolInfo parameter), chosen from
+//! the codec, container and picture size found at scan time.
+//!
+//! Strict renderers (Sony, LG) reject an item whose profile does not match the file,
+//! so a file that fits no profile gets none rather than a near miss.
+
+use serde::{Deserialize, Deserializer};
+
+use crate::media::library::{AudioTrack, MediaMeta};
+
+/// Every profile udlna can emit. Names are assembled from parts and then looked up
+/// here, so a combination the DLNA guidelines do not define is never sent.
+const PROFILES: &[&str] = &[
+    // Images
+    "JPEG_SM",
+    "JPEG_MED",
+    "JPEG_LRG",
+    "PNG_LRG",
+    // Audio
+    "MP3",
+    "MP3X",
+    "AAC_ISO_320",
+    "AAC_ISO",
+    "AAC_MULT5_ISO",
+    "AAC_ADTS_320",
+    "AAC_ADTS",
+    "AAC_MULT5_ADTS",
+    "LPCM",
+    "WMABASE",
+    "WMAFULL",
+    "WMAPRO",
+    "WMALSL",
+    "WMALSL_MULT5",
+    // H.264 in MP4
+    "AVC_MP4_BL_CIF30_AAC_940",
+    "AVC_MP4_BL_L3L_SD_AAC",
+    "AVC_MP4_BL_L31_HD_AAC",
+    "AVC_MP4_MP_SD_AAC_MULT5",
+    "AVC_MP4_MP_SD_MPEG1_L3",
+    "AVC_MP4_MP_SD_AC3",
+    "AVC_MP4_MP_HD_720p_AAC",
+    "AVC_MP4_MP_HD_1080i_AAC",
+    "AVC_MP4_HP_HD_AAC",
+    // H.264 in MPEG-TS
+    "AVC_TS_MP_SD_AAC_MULT5_ISO",
+    "AVC_TS_MP_SD_AAC_MULT5_T",
+    "AVC_TS_MP_SD_MPEG1_L3_ISO",
+    "AVC_TS_MP_SD_MPEG1_L3_T",
+    "AVC_TS_MP_SD_AC3_ISO",
+    "AVC_TS_MP_SD_AC3_T",
+    "AVC_TS_MP_HD_AAC_MULT5_ISO",
+    "AVC_TS_MP_HD_AAC_MULT5_T",
+    "AVC_TS_MP_HD_MPEG1_L3_ISO",
+    "AVC_TS_MP_HD_MPEG1_L3_T",
+    "AVC_TS_MP_HD_AC3_ISO",
+    "AVC_TS_MP_HD_AC3_T",
+    // MPEG-2 in MPEG-TS
+    "MPEG_TS_SD_EU_ISO",
+    "MPEG_TS_SD_EU_T",
+    "MPEG_TS_SD_NA_ISO",
+    "MPEG_TS_SD_NA_T",
+    "MPEG_TS_HD_NA_ISO",
+    "MPEG_TS_HD_NA_T",
+    // MPEG program streams
+    "MPEG_PS_PAL",
+    "MPEG_PS_NTSC",
+    "MPEG1",
+];
+
+/// A profile name from `PROFILES`. Persisted in the metadata index; the alias also
+/// keeps serde from treating an `Option<&'static str>` field as borrowed from its input.
+pub type Profile = &'static str;
+
+/// H.264 `profile_idc` values.
+const AVC_BASELINE: u8 = 66;
+const AVC_HIGH: u8 = 100;
+
+/// The profile name `name` as a static string, if udlna knows it.
+pub fn known(name: &str) -> Option<&'static str> {
+    PROFILES.iter().copied().find(|p| *p == name)
+}
+
+/// `deserialize_with` for `MediaMeta::dlna_profile`. Names udlna no longer emits
+/// read back as None.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Profile>, D::Error> {
+    let name = Option::<String>::deserialize(deserializer)?;
+    Ok(name.as_deref().and_then(known))
+}
+
+/// Image profile from the pixel dimensions: JPEG_SM up to 640x480, JPEG_MED up to
+/// 1024x768, JPEG_LRG and PNG_LRG up to 4096x4096.
+pub fn image_profile(mime: &str, width: u32, height: u32) -> Option<&'static str> {
+    let fits = |w: u32, h: u32| width <= w && height <= h;
+    match mime {
+        "image/jpeg" if fits(640, 480) => Some("JPEG_SM"),
+        "image/jpeg" if fits(1024, 768) => Some("JPEG_MED"),
+        "image/jpeg" if fits(4096, 4096) => Some("JPEG_LRG"),
+        "image/png" if fits(4096, 4096) => Some("PNG_LRG"),
+        _ => None,
+    }
+}
+
+/// Audio profile for a file of type `mime` whose stream is `codec` (a short name as
+/// in `AudioTrack::codec`). Uses the sample rate, channels, bit depth and bitrate of
+/// `meta`; a limit is only checked when the value is known.
+pub fn audio_profile(mime: &str, codec: &str, meta: &MediaMeta) -> Option<&'static str> {
+    let rate_max = |max: u32| meta.sample_rate.is_none_or(|r| r <= max);
+    let channels = meta.channels.unwrap_or(2);
+    // Bitrate limits are in kbit/s, `meta.bitrate` in bytes per second.
+    let kbps_max = |max: u32| meta.bitrate.is_none_or(|b| b <= max * 1000 / 8);
+
+    match codec {
+        "mp3" if mime == "audio/mpeg" => match meta.sample_rate {
+            Some(32000 | 44100 | 48000) | None => Some("MP3"),
+            Some(_) => Some("MP3X"),
+        },
+        "aac" => {
+            let container = match mime {
+                "audio/mp4" => "ISO",
+                "audio/aac" => "ADTS",
+                _ => return None,
+            };
+            let name = match channels {
+                1..=2 if rate_max(48000) && kbps_max(320) => format!("AAC_{container}_320"),
+                1..=2 if rate_max(48000) && kbps_max(576) => format!("AAC_{container}"),
+                1..=6 if rate_max(48000) && kbps_max(1440) => format!("AAC_MULT5_{container}"),
+                _ => return None,
+            };
+            known(&name)
+        }
+        "pcm" if meta.bits_per_sample == Some(16) && channels <= 2 && rate_max(48000) => Some("LPCM"),
+        "wmav1" | "wmav2" if channels <= 2 && rate_max(48000) => {
+            if kbps_max(193) {
+                Some("WMABASE")
+            } else if kbps_max(385) {
+                Some("WMAFULL")
+            } else {
+                None
+            }
+        }
+        "wmapro" if channels <= 8 && rate_max(96000) && kbps_max(1500) => Some("WMAPRO"),
+        "wmalossless" if rate_max(96000) => Some(if channels <= 2 { "WMALSL" } else { "WMALSL_MULT5" }),
+        _ => None,
+    }
+}
+
+/// The container a video profile is defined for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    Mp4,
+    /// MPEG transport stream; `timestamped` for 192-byte M2TS packets.
+    Ts { timestamped: bool },
+    /// MPEG program stream (.mpg).
+    Ps,
+}
+
+/// The video stream a profile is chosen for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Video<'a> {
+    /// Short codec name, as in `MediaMeta::video_codec`.
+    pub codec: &'a str,
+    /// `Sps::profile` of the stream; 0 when unknown. Only H.264 profiles are told apart.
+    pub profile: u8,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Video profile for `video` in `container`, with `audio` the first audio stream.
+/// H.264 profiles name their audio codec, so they need one; MPEG-2 profiles do not.
+pub fn video_profile(container: Container, video: Video, audio: Option<&AudioTrack>) -> Option<&'static str> {
+    let fits = |w: u32, h: u32| video.width <= w && video.height <= h;
+    let sd = fits(720, 576);
+    // 576-line SD is the European (PAL) flavour, 480-line the North American one.
+    let pal = video.height > 480;
+
+    let name = match (container, video.codec) {
+        (Container::Mp4, "h264") => {
+            let audio = audio?.codec.as_str();
+            match video.profile {
+                AVC_BASELINE if audio == "aac" && fits(352, 288) => "AVC_MP4_BL_CIF30_AAC_940".to_string(),
+                AVC_BASELINE if audio == "aac" && sd => "AVC_MP4_BL_L3L_SD_AAC".to_string(),
+                AVC_BASELINE if audio == "aac" && fits(1280, 720) => "AVC_MP4_BL_L31_HD_AAC".to_string(),
+                AVC_BASELINE => return None,
+                _ if sd => format!("AVC_MP4_MP_SD_{}", avc_audio(audio)?),
+                _ if audio != "aac" => return None,
+                _ if fits(1280, 720) => "AVC_MP4_MP_HD_720p_AAC".to_string(),
+                profile if profile >= AVC_HIGH && fits(1920, 1080) => "AVC_MP4_HP_HD_AAC".to_string(),
+                _ if fits(1920, 1080) => "AVC_MP4_MP_HD_1080i_AAC".to_string(),
+                _ => return None,
+            }
+        }
+        (Container::Ts { timestamped }, codec) => {
+            let framing = if timestamped { "T" } else { "ISO" };
+            match codec {
+                "h264" if fits(1920, 1080) => {
+                    let size = if sd { "SD" } else { "HD" };
+                    format!("AVC_TS_MP_{size}_{}_{framing}", avc_audio(&audio?.codec)?)
+                }
+                "mpeg2video" if sd => format!("MPEG_TS_SD_{}_{framing}", if pal { "EU" } else { "NA" }),
+                "mpeg2video" if fits(1920, 1080) => format!("MPEG_TS_HD_NA_{framing}"),
+                _ => return None,
+            }
+        }
+        (Container::Ps, "mpeg2video") if sd => (if pal { "MPEG_PS_PAL" } else { "MPEG_PS_NTSC" }).to_string(),
+        (Container::Ps, "mpeg1video") if fits(352, 288) => "MPEG1".to_string(),
+        _ => return None,
+    };
+    known(&name)
+}
+
+/// The audio part of an H.264 profile name.
+fn avc_audio(codec: &str) -> Option<&'static str> {
+    match codec {
+        "aac" => Some("AAC_MULT5"),
+        "mp3" => Some("MPEG1_L3"),
+        "ac3" => Some("AC3"),
+        _ => None,
+    }
+}
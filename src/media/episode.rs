@@ -0,0 +1,154 @@
+//! TV episode detection from file names: `Show.Name.S02E05.Title.1080p.mkv`,
+//! `Show Name - 2x05 - Title.avi` and date-stamped daily shows such as
+//! `Show.Name.2024.03.01.Guest.mkv`.
+
+use std::path::Path;
+
+use crate::media::title::{clean, words_before_release};
+
+/// A video file recognised as one episode of a series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Episode {
+    /// Series name with separators turned into spaces.
+    pub series: String,
+    /// Season number; the year for date-based episodes.
+    pub season: u32,
+    /// Episode number; None for date-based episodes.
+    pub episode: Option<u32>,
+    /// Air date "YYYY-MM-DD" of date-based episodes.
+    pub date: Option<String>,
+    /// Episode title from the rest of the name, without release details.
+    pub title: Option<String>,
+}
+
+impl Episode {
+    /// "S02E05 - Title", "2024-03-01 - Title", or just the number or date without a title.
+    pub fn display_title(&self) -> String {
+        let number = match (&self.date, self.episode) {
+            (Some(date), _) => date.clone(),
+            (None, Some(episode)) => format!("S{:02}E{:02}", self.season, episode),
+            (None, None) => format!("S{:02}", self.season),
+        };
+        match &self.title {
+            Some(title) => format!("{number} - {title}"),
+            None => number,
+        }
+    }
+}
+
+/// Recognise `path` as an episode. The series name comes from the file name, else
+/// from the directory above any `Season N` / `Specials` folder, so `Show/Season 1/
+/// S01E01.mkv` is recognised too. None when the name has no episode marker or no
+/// series name can be found. A date without a series name in the file name is only
+/// an episode inside a season folder: `Holiday/2023-06-15 14.25.30.mp4` is a phone clip.
+pub fn parse(path: &Path) -> Option<Episode> {
+    let stem = path.file_stem()?.to_str()?;
+    let marker = find_marker(stem)?;
+    let mut series = clean(&stem[..marker.start]);
+    if series.is_empty() {
+        let in_season_dir = path.parent().and_then(|d| d.file_name()?.to_str()).is_some_and(is_season_dir);
+        if marker.date.is_some() && !in_season_dir {
+            return None;
+        }
+        series = series_from_dirs(path)?;
+    }
+    let title = clean(&words_before_release(&stem[marker.end..]).join(" "));
+    Some(Episode {
+        series,
+        season: marker.season,
+        episode: marker.episode,
+        date: marker.date,
+        title: (!title.is_empty()).then_some(title),
+    })
+}
+
+/// An episode marker and its byte range in the file stem.
+struct Marker {
+    start: usize,
+    end: usize,
+    season: u32,
+    episode: Option<u32>,
+    date: Option<String>,
+}
+
+/// The first episode marker starting at a word boundary: `S02E05` (also `S02E05E06`
+/// and `S02E05-E06`, numbered by the first episode), `2x05`, or `2024.03.01` with
+/// `.`, `-`, `_` or space between the parts.
+fn find_marker(stem: &str) -> Option<Marker> {
+    let bytes = stem.as_bytes();
+    let digits = |at: usize, min: usize, max: usize| -> Option<(u32, usize)> {
+        let len = bytes.get(at..)?.iter().take(max + 1).take_while(|b| b.is_ascii_digit()).count();
+        if len < min || len > max {
+            return None;
+        }
+        Some((stem[at..at + len].parse().ok()?, at + len))
+    };
+    let is = |at: usize, set: &[u8]| bytes.get(at).is_some_and(|b| set.contains(b));
+    let boundary = |at: usize| bytes.get(at).is_none_or(|b| !b.is_ascii_alphanumeric());
+
+    for start in (0..bytes.len()).filter(|&i| i == 0 || !bytes[i - 1].is_ascii_alphanumeric()) {
+        // S02E05
+        if is(start, b"sS") {
+            if let Some((season, at)) = digits(start + 1, 1, 2) {
+                if is(at, b"eE") {
+                    if let Some((episode, mut end)) = digits(at + 1, 1, 3) {
+                        // Further episodes of a multi-episode file.
+                        loop {
+                            let next = if is(end, b"-") { end + 1 } else { end };
+                            match is(next, b"eE").then(|| digits(next + 1, 1, 3)).flatten() {
+                                Some((_, after)) => end = after,
+                                None => break,
+                            }
+                        }
+                        if boundary(end) {
+                            return Some(Marker { start, end, season, episode: Some(episode), date: None });
+                        }
+                    }
+                }
+            }
+        }
+        // 2024.03.01
+        if let Some((year, at)) = digits(start, 4, 4).filter(|(y, _)| (1950..=2100).contains(y)) {
+            let sep = bytes.get(at).copied().filter(|b| b".-_ ".contains(b));
+            if let Some(sep) = sep {
+                let month = digits(at + 1, 2, 2);
+                let day = month.filter(|(_, m)| bytes.get(*m) == Some(&sep)).and_then(|(_, m)| digits(m + 1, 2, 2));
+                if let (Some((month, _)), Some((day, end))) = (month, day) {
+                    if boundary(end) && chrono::NaiveDate::from_ymd_opt(year as i32, month, day).is_some() {
+                        let date = format!("{year:04}-{month:02}-{day:02}");
+                        return Some(Marker { start, end, season: year, episode: None, date: Some(date) });
+                    }
+                }
+            }
+        }
+        // 2x05
+        if let Some((season, at)) = digits(start, 1, 2) {
+            if is(at, b"xX") {
+                if let Some((episode, end)) = digits(at + 1, 2, 3).filter(|(_, end)| boundary(*end)) {
+                    return Some(Marker { start, end, season, episode: Some(episode), date: None });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The name of the closest directory that is not a season folder.
+fn series_from_dirs(path: &Path) -> Option<String> {
+    path.ancestors()
+        .skip(1)
+        .filter_map(|dir| dir.file_name()?.to_str())
+        .find(|name| !is_season_dir(name))
+        .map(clean)
+        .filter(|series| !series.is_empty())
+}
+
+/// "Season 2", "Season.02", "S02" or "Specials".
+fn is_season_dir(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let number = lower
+        .strip_prefix("season")
+        .map(|rest| rest.trim_start_matches([' ', '.', '_']))
+        .or_else(|| lower.strip_prefix('s'));
+    lower == "specials" || number.is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use serde::Deserialize;
+
+/// Marker file that hides its directory (and everything below it) from the library,
+/// following the Android convention.
+pub const NOMEDIA_FILE: &str = ".nomedia";
+
+/// Per-directory ignore file with gitignore syntax. Rules apply to the directory
+/// containing it and all of its subdirectories; deeper files take precedence.
+pub const IGNORE_FILE: &str = ".udlnaignore";
+
+/// Why a path was left out of the library. Shown in debug logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exclusion {
+    Hidden,
+    ExcludePattern,
+    NotIncluded,
+    NoMedia,
+    IgnoreFile,
+    Symlink,
+    TooDeep,
+    OtherFilesystem,
+    LibraryKind,
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Exclusion::Hidden => "hidden",
+            Exclusion::ExcludePattern => "exclude pattern",
+            Exclusion::NotIncluded => "no include pattern matched",
+            Exclusion::NoMedia => ".nomedia marker",
+            Exclusion::IgnoreFile => ".udlnaignore rule",
+            Exclusion::Symlink => "symlink policy",
+            Exclusion::TooDeep => "max_depth",
+            Exclusion::OtherFilesystem => "other filesystem",
+            Exclusion::LibraryKind => "kind not in library",
+        })
+    }
+}
+
+/// Which symbolic links the scanner follows, and which resolved paths may be served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymlinkPolicy {
+    /// Follow a link only if its target lies inside one of the scan roots.
+    #[default]
+    WithinRoots,
+    /// Never follow links; files and directories reached through one are skipped.
+    Never,
+    /// Follow every link, wherever it points.
+    Always,
+}
+
+impl SymlinkPolicy {
+    /// Whether `path`, which resolves to the canonical `resolved`, is acceptable.
+    /// `path` must itself be built from a canonical root so that any difference from
+    /// `resolved` means a link was crossed.
+    pub fn allows(self, path: &Path, resolved: &Path, roots: &[PathBuf]) -> bool {
+        let contained = || roots.iter().any(|r| resolved.starts_with(r));
+        match self {
+            SymlinkPolicy::Always => true,
+            SymlinkPolicy::WithinRoots => contained(),
+            SymlinkPolicy::Never => path == resolved && contained(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid glob pattern `{pattern}`: {source}")]
+pub struct FilterError {
+    pattern: String,
+    source: globset::Error,
+}
+
+/// Path filter applied by the scanner and the watcher on top of the extension check
+/// in `mime::classify`.
+///
+/// Globs match the path relative to its scan root, and `*` also matches `/`, so
+/// `*.partial.mkv` applies at any depth while `**/Samples/**` drops whole folders.
+/// Include patterns only apply to files: directories are always descended into.
+/// `Default` filters nothing except `.nomedia` / `.udlnaignore` markers.
+#[derive(Debug, Clone, Default)]
+pub struct ScanFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    skip_hidden: bool,
+}
+
+impl ScanFilter {
+    pub fn new(include: &[String], exclude: &[String], skip_hidden: bool) -> Result<Self, FilterError> {
+        Ok(ScanFilter {
+            include: build_globs(include)?,
+            exclude: build_globs(exclude)?,
+            skip_hidden,
+        })
+    }
+
+    /// Check one walk entry. `ignores` must have seen every ancestor of `path` below
+    /// `root` (the walk order guarantees that); directories that pass are pushed onto it.
+    pub fn check(&self, root: &Path, path: &Path, is_dir: bool, ignores: &mut IgnoreStack) -> Option<Exclusion> {
+        ignores.leave_to(path);
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        if !relative.as_os_str().is_empty() {
+            if self.skip_hidden && is_hidden(relative) {
+                return Some(Exclusion::Hidden);
+            }
+            if self.exclude.as_ref().is_some_and(|g| g.is_match(relative)) {
+                return Some(Exclusion::ExcludePattern);
+            }
+            if !is_dir && self.include.as_ref().is_some_and(|g| !g.is_match(relative)) {
+                return Some(Exclusion::NotIncluded);
+            }
+            if ignores.is_ignored(path, is_dir) {
+                return Some(Exclusion::IgnoreFile);
+            }
+        }
+        if is_dir {
+            if path.join(NOMEDIA_FILE).exists() {
+                return Some(Exclusion::NoMedia);
+            }
+            ignores.push(path);
+        }
+        None
+    }
+
+    /// Check every directory from `root` down to (not including) `path`, leaving
+    /// `ignores` ready for a walk that starts at `path`.
+    pub fn check_ancestors(&self, root: &Path, path: &Path, ignores: &mut IgnoreStack) -> Option<Exclusion> {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return None;
+        };
+        let mut dir = root.to_path_buf();
+        if let Some(reason) = self.check(root, &dir, true, ignores) {
+            return Some(reason);
+        }
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                break;
+            }
+            dir.push(component);
+            if let Some(reason) = self.check(root, &dir, true, ignores) {
+                return Some(reason);
+            }
+        }
+        None
+    }
+
+    /// Full check for a single path outside of a walk (used by the watcher).
+    pub fn check_path(&self, root: &Path, path: &Path, is_dir: bool) -> Option<Exclusion> {
+        let mut ignores = IgnoreStack::default();
+        self.check_ancestors(root, path, &mut ignores)
+            .or_else(|| self.check(root, path, is_dir, &mut ignores))
+    }
+}
+
+fn build_globs(patterns: &[String]) -> Result<Option<GlobSet>, FilterError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|source| FilterError {
+            pattern: pattern.clone(),
+            source,
+        })?;
+        builder.add(glob);
+    }
+    builder.build().map(Some).map_err(|source| FilterError {
+        pattern: patterns.join(", "),
+        source,
+    })
+}
+
+fn is_hidden(relative: &Path) -> bool {
+    relative
+        .file_name()
+        .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+}
+
+/// `.udlnaignore` matchers of the directories enclosing the current walk position,
+/// outermost first.
+#[derive(Default)]
+pub struct IgnoreStack {
+    frames: Vec<(PathBuf, Option<Gitignore>)>,
+}
+
+impl IgnoreStack {
+    /// Drop the matchers of directories the walk has left.
+    fn leave_to(&mut self, path: &Path) {
+        while self
+            .frames
+            .last()
+            .is_some_and(|(dir, _)| !path.starts_with(dir) || path == dir)
+        {
+            self.frames.pop();
+        }
+    }
+
+    /// Enter `dir`, loading its ignore file if it has one.
+    fn push(&mut self, dir: &Path) {
+        let file = dir.join(IGNORE_FILE);
+        let matcher = file.is_file().then(|| {
+            let mut builder = GitignoreBuilder::new(dir);
+            if let Some(e) = builder.add(&file) {
+                tracing::warn!("Problem reading {}: {}", file.display(), e);
+            }
+            builder.build().unwrap_or_else(|e| {
+                tracing::warn!("Ignoring {}: {}", file.display(), e);
+                Gitignore::empty()
+            })
+        });
+        self.frames.push((dir.to_path_buf(), matcher));
+    }
+
+    /// The innermost ignore file with an opinion on `path` decides.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for matcher in self.frames.iter().rev().filter_map(|(_, m)| m.as_ref()) {
+            match matcher.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::media::library::{MediaItem, MediaMeta};
+use crate::media::mime::supported_mime;
+
+/// On-disk format version. Bump whenever `MediaMeta` changes shape or an extractor
+/// starts producing different results, so stale entries are re-probed instead of reused.
+pub const INDEX_VERSION: u32 = 13;
+
+/// One cached extraction result. `size` + `modified` decide whether it is still fresh.
+#[derive(Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+    /// MIME type chosen by content sniffing, so a hit needs no file access at all.
+    mime: String,
+    meta: MediaMeta,
+}
+
+impl IndexEntry {
+    /// The entry for `item`; None without a known mtime.
+    fn of(item: &MediaItem) -> Option<Self> {
+        Some(IndexEntry {
+            path: item.path.clone(),
+            size: item.file_size,
+            modified: item.modified?,
+            mime: item.mime.to_string(),
+            meta: item.meta.clone(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    entries: Vec<IndexEntry>,
+}
+
+/// Persistent metadata index loaded at scan start so unchanged files skip
+/// symphonia/mp4/imagesize probing entirely.
+///
+/// Keyed by canonical path; an entry is only reused when the file's size and
+/// modification time both match what was recorded. The index is rewritten from the
+/// finished library after every full scan, which also prunes deleted files below the
+/// scanned roots; entries for roots this run did not scan are kept.
+#[derive(Default)]
+pub struct MetadataIndex {
+    entries: HashMap<PathBuf, IndexEntry>,
+    hits: AtomicUsize,
+}
+
+impl MetadataIndex {
+    /// Load the index from `path`. A missing, unreadable, corrupt or outdated index
+    /// yields an empty one — the scan then simply probes every file.
+    pub fn load(path: &Path) -> Self {
+        let content = match std::fs::read(path) {
+            Ok(c) => c,
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("Cannot read metadata index {}: {}", path.display(), e);
+                }
+                return Self::default();
+            }
+        };
+        let file: IndexFile = match serde_json::from_slice(&content) {
+            Ok(f) => f,
+            Err(e) => {
+                tracing::warn!("Ignoring corrupt metadata index {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        if file.version != INDEX_VERSION {
+            tracing::info!(
+                "Metadata index format changed (v{} -> v{}) — rebuilding",
+                file.version,
+                INDEX_VERSION
+            );
+            return Self::default();
+        }
+        let entries = file
+            .entries
+            .into_iter()
+            .map(|e| (e.path.clone(), e))
+            .collect();
+        Self {
+            entries,
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// An in-memory index of `items`, so a rescan reuses what the library already
+    /// holds for files that have not changed since.
+    pub fn from_items<'a>(items: impl IntoIterator<Item = &'a MediaItem>) -> Self {
+        let entries = items.into_iter().filter_map(IndexEntry::of).map(|e| (e.path.clone(), e)).collect();
+        Self { entries, hits: AtomicUsize::new(0) }
+    }
+
+    /// Return the cached MIME type and metadata for `path` if its size and mtime are
+    /// unchanged.
+    pub fn lookup(&self, path: &Path, size: u64, modified: SystemTime) -> Option<(&'static str, MediaMeta)> {
+        let entry = self.entries.get(path)?;
+        if entry.size != size || entry.modified != modified {
+            return None;
+        }
+        let mime = supported_mime(&entry.mime)?;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some((mime, entry.meta.clone()))
+    }
+
+    /// Number of entries loaded from disk.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of successful `lookup` calls since load.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Write an index covering exactly `items` to `path`, atomically (temp file + rename).
+    /// Items without a known mtime or with non-UTF-8 paths (not representable in JSON)
+    /// are left out and will be probed again next time.
+    pub fn save(items: &[MediaItem], path: &Path) -> std::io::Result<()> {
+        Self::write(items.iter().filter_map(IndexEntry::of).collect(), path)
+    }
+
+    /// Write `items` to `path` like `save`, keeping this index's entries outside the
+    /// canonical `roots` that `items` were scanned from, so a run over fewer roots (or
+    /// with one unmounted) does not forget the others.
+    pub fn save_merged(&self, items: &[MediaItem], roots: &[PathBuf], path: &Path) -> std::io::Result<()> {
+        let kept = self.entries.values().filter(|e| !roots.iter().any(|root| e.path.starts_with(root)));
+        let entries = kept.cloned().chain(items.iter().filter_map(IndexEntry::of)).collect();
+        Self::write(entries, path)
+    }
+
+    fn write(mut entries: Vec<IndexEntry>, path: &Path) -> std::io::Result<()> {
+        entries.retain(|e| e.path.to_str().is_some());
+        // Sorted output keeps the file stable between runs with the same content.
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let file = IndexFile {
+            version: INDEX_VERSION,
+            entries,
+        };
+        let json = serde_json::to_vec(&file).map_err(std::io::Error::other)?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)
+    }
+}
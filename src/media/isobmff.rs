@@ -0,0 +1,459 @@
+//! ISO base media (MP4) track descriptions beyond what the mp4 crate parses: the
+//! codec of every sample entry (hvc1, av01, ac-3, Opus, ...), its decoder
+//! configuration record, colour information, frame rate, language and channels,
+//! plus chapter marks.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::media::library::Chapter;
+use crate::media::mkv::TrackKind;
+use crate::media::sps::{self, Sps};
+
+/// Upper bound for the `moov` box. Long recordings with many samples reach a few
+/// megabytes of sample tables.
+const MAX_MOOV_LEN: u64 = 64 * 1024 * 1024;
+/// SampleEntry fields before the VisualSampleEntry child boxes.
+const VISUAL_ENTRY_LEN: usize = 78;
+/// SampleEntry fields before the AudioSampleEntry child boxes (version 0).
+const AUDIO_ENTRY_LEN: usize = 28;
+/// More chapters than this are not chapter marks.
+const MAX_CHAPTERS: usize = 1000;
+
+/// One track of an MP4 file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mp4Track {
+    pub id: u32,
+    /// Video, Audio or Other; MP4 subtitle tracks are reported as Other.
+    pub kind: TrackKind,
+    /// Short codec name as in `MediaMeta::video_codec` / `AudioTrack::codec`; the
+    /// sample entry type for codecs udlna does not name.
+    pub codec: String,
+    /// ISO 639-2 code from the media header; None for "und".
+    pub language: Option<String>,
+    /// Profile, level and bit depth from the decoder configuration record.
+    pub config: Option<Sps>,
+    /// H.273 transfer characteristics from the `colr` box (or `vpcC` for VP9).
+    pub transfer: Option<u8>,
+    /// Frames per second from the sample durations.
+    pub frame_rate: Option<f64>,
+    pub channels: Option<u32>,
+}
+
+/// The tracks of the MP4 file at `path`, in file order. None without a `moov` box.
+pub fn read_tracks(path: &Path) -> Option<Vec<Mp4Track>> {
+    let moov = read_moov(path)?;
+    Some(children(&moov).filter(|(kind, _)| kind == b"trak").filter_map(|(_, trak)| track(trak)).collect())
+}
+
+/// Find the top-level `moov` box, seeking past `mdat` wherever it sits.
+fn read_moov(path: &Path) -> Option<Vec<u8>> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut at = 0u64;
+    while at.checked_add(8).is_some_and(|end| end <= len) {
+        file.seek(SeekFrom::Start(at)).ok()?;
+        let mut header = [0u8; 16];
+        file.read_exact(&mut header[..8]).ok()?;
+        let (size, header_len) = match u32::from_be_bytes(header[..4].try_into().ok()?) {
+            0 => (len - at, 8),
+            1 => {
+                file.read_exact(&mut header[8..]).ok()?;
+                (u64::from_be_bytes(header[8..].try_into().ok()?), 16)
+            }
+            size => (size as u64, 8),
+        };
+        if size < header_len {
+            return None;
+        }
+        if &header[4..8] == b"moov" {
+            if size > MAX_MOOV_LEN {
+                return None;
+            }
+            let mut moov = vec![0u8; (size - header_len) as usize];
+            file.read_exact(&mut moov).ok()?;
+            return Some(moov);
+        }
+        at = at.checked_add(size)?;
+    }
+    None
+}
+
+/// The child boxes in `data` as (type, payload). A truncated box ends the walk.
+fn children(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    let mut at = 0;
+    std::iter::from_fn(move || {
+        let header = data.get(at..at + 8)?;
+        let kind: [u8; 4] = header[4..].try_into().ok()?;
+        let (size, header_len) = match be32(header, 0)? {
+            0 => (data.len() - at, 8),
+            1 => (usize::try_from(be64(data, at + 8)?).ok()?, 16),
+            size => (size as usize, 8),
+        };
+        let payload = data.get(at + header_len..at.checked_add(size)?)?;
+        at += size;
+        Some((kind, payload))
+    })
+}
+
+/// The payload of the first child box of type `kind`.
+fn child<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    children(data).find(|(k, _)| k == kind).map(|(_, payload)| payload)
+}
+
+fn track(trak: &[u8]) -> Option<Mp4Track> {
+    let tkhd = child(trak, b"tkhd")?;
+    let id = be32(tkhd, if tkhd.first() == Some(&1) { 20 } else { 12 })?;
+    let mdia = child(trak, b"mdia")?;
+    let mdhd = child(mdia, b"mdhd")?;
+    let (timescale, language) = if mdhd.first() == Some(&1) {
+        (be32(mdhd, 20)?, be16(mdhd, 32)?)
+    } else {
+        (be32(mdhd, 12)?, be16(mdhd, 20)?)
+    };
+    let kind = match child(mdia, b"hdlr").and_then(|h| h.get(8..12)) {
+        Some(b"vide") => TrackKind::Video,
+        Some(b"soun") => TrackKind::Audio,
+        _ => TrackKind::Other,
+    };
+    let stbl = child(child(mdia, b"minf")?, b"stbl")?;
+    let stsd = child(stbl, b"stsd")?;
+    let (entry_type, entry) = children(stsd.get(8..)?).next()?;
+
+    let mut track = Mp4Track {
+        id,
+        kind,
+        codec: String::from_utf8_lossy(&entry_type).trim().to_string(),
+        language: unpack_language(language),
+        config: None,
+        transfer: None,
+        frame_rate: None,
+        channels: None,
+    };
+    match kind {
+        TrackKind::Video => {
+            track.frame_rate = child(stbl, b"stts").and_then(|stts| frame_rate(stts, timescale));
+            visual_entry(&mut track, &entry_type, entry.get(VISUAL_ENTRY_LEN..).unwrap_or_default());
+        }
+        TrackKind::Audio => audio_entry(&mut track, &entry_type, entry),
+        _ => {}
+    }
+    Some(track)
+}
+
+fn visual_entry(track: &mut Mp4Track, entry_type: &[u8; 4], boxes: &[u8]) {
+    let codec = match entry_type {
+        b"avc1" | b"avc3" => "h264",
+        b"hvc1" | b"hev1" | b"dvh1" | b"dvhe" => "hevc",
+        b"av01" => "av1",
+        b"vp09" => "vp9",
+        b"vp08" => "vp8",
+        b"mp4v" => "mpeg4",
+        _ => return,
+    };
+    track.codec = codec.to_string();
+    for (kind, payload) in children(boxes) {
+        match &kind {
+            b"avcC" => track.config = sps::avc_config(payload),
+            b"hvcC" => track.config = sps::hevc_config(payload),
+            b"av1C" => track.config = sps::av1_config(payload),
+            b"vpcC" => {
+                track.config = sps::vp9_config(payload);
+                track.transfer = track.transfer.or(payload.get(8).copied());
+            }
+            // nclx: colour primaries, transfer characteristics, matrix coefficients.
+            b"colr" if matches!(payload.get(..4), Some(b"nclx" | b"nclc")) => {
+                track.transfer = be16(payload, 6).and_then(|tc| u8::try_from(tc).ok());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn audio_entry(track: &mut Mp4Track, entry_type: &[u8; 4], entry: &[u8]) {
+    track.channels = be16(entry, 16).map(u32::from).filter(|&c| c > 0);
+    // QuickTime sound description versions 1 and 2 append fields before the boxes.
+    let boxes_at = match be16(entry, 8) {
+        Some(1) => AUDIO_ENTRY_LEN + 16,
+        Some(2) => {
+            track.channels = be32(entry, 40).filter(|&c| c > 0);
+            AUDIO_ENTRY_LEN + 36
+        }
+        _ => AUDIO_ENTRY_LEN,
+    };
+    let codec = match entry_type {
+        b"mp4a" => {
+            let esds = entry.get(boxes_at..).and_then(|boxes| child(boxes, b"esds"));
+            let (object_type, channels) = esds.and_then(decoder_config).unwrap_or((0x40, None));
+            track.channels = channels.or(track.channels);
+            match object_type {
+                0x69 | 0x6B => "mp3",
+                _ => "aac",
+            }
+        }
+        b"ac-3" => "ac3",
+        b"ec-3" => "eac3",
+        b"Opus" => "opus",
+        b"fLaC" => "flac",
+        b"alac" => "alac",
+        b".mp3" => "mp3",
+        b"lpcm" | b"sowt" | b"twos" | b"in24" | b"in32" | b"fl32" => "pcm",
+        _ => return,
+    };
+    track.codec = codec.to_string();
+}
+
+/// The objectTypeIndication of an `esds` DecoderConfigDescriptor and, for AAC, the
+/// channel count of its AudioSpecificConfig.
+fn decoder_config(esds: &[u8]) -> Option<(u8, Option<u32>)> {
+    let (tag, es) = descriptor(esds.get(4..)?)?;
+    if tag != 0x03 {
+        return None;
+    }
+    let flags = *es.get(2)?;
+    let mut at = 3;
+    if flags & 0x80 != 0 {
+        at += 2; // dependsOn_ES_ID
+    }
+    if flags & 0x40 != 0 {
+        at += 1 + *es.get(at)? as usize; // URL
+    }
+    if flags & 0x20 != 0 {
+        at += 2; // OCR_ES_Id
+    }
+    let (tag, config) = descriptor(es.get(at..)?)?;
+    if tag != 0x04 {
+        return None;
+    }
+    let object_type = *config.first()?;
+    // objectTypeIndication, streamType, bufferSizeDB, maxBitrate, avgBitrate.
+    let channels = descriptor(config.get(13..).unwrap_or_default())
+        .filter(|(tag, _)| *tag == 0x05)
+        .and_then(|(_, asc)| aac_channels(asc));
+    Some((object_type, channels))
+}
+
+/// A descriptor's tag and payload; sizes are 7 bits per byte with a continuation bit.
+fn descriptor(data: &[u8]) -> Option<(u8, &[u8])> {
+    let tag = *data.first()?;
+    let mut size = 0usize;
+    let mut at = 1;
+    loop {
+        let b = *data.get(at)?;
+        size = size << 7 | (b & 0x7F) as usize;
+        at += 1;
+        if b & 0x80 == 0 || at == 5 {
+            break;
+        }
+    }
+    Some((tag, data.get(at..at + size)?))
+}
+
+/// Channel count of an AAC AudioSpecificConfig; None when the configuration is
+/// carried in a program config element instead.
+fn aac_channels(asc: &[u8]) -> Option<u32> {
+    let len = asc.len().min(8) as u32;
+    let word = asc.iter().zip((0..8).rev()).fold(0u64, |w, (&b, i)| w | (b as u64) << (8 * i));
+    let field = |at: u32, bits: u32| (at + bits <= 8 * len).then(|| (word << at >> (64 - bits)) as u32);
+    let mut at = 5;
+    if field(0, 5)? == 31 {
+        at += 6; // audioObjectTypeExt
+    }
+    if field(at, 4)? == 15 {
+        at += 24; // explicit samplingFrequency
+    }
+    match field(at + 4, 4)? {
+        0 => None,
+        7 => Some(8),
+        c => Some(c),
+    }
+}
+
+/// Frames per second: samples over total duration in the media timescale.
+fn frame_rate(stts: &[u8], timescale: u32) -> Option<f64> {
+    let count = be32(stts, 4)? as usize;
+    let (mut frames, mut ticks) = (0u64, 0u64);
+    for i in 0..count {
+        let samples = be32(stts, 8 + i * 8)? as u64;
+        frames = frames.checked_add(samples)?;
+        ticks = ticks.checked_add(samples * be32(stts, 12 + i * 8)? as u64)?;
+    }
+    let fps = frames as f64 * timescale as f64 / ticks as f64;
+    (frames > 1 && fps.is_finite() && fps > 0.0).then_some(fps)
+}
+
+/// ISO 639-2 code packed as three 5-bit letters offset from 0x60.
+fn unpack_language(packed: u16) -> Option<String> {
+    let code: String = [10, 5, 0].iter().map(|shift| ((packed >> shift & 0x1F) as u8 + 0x60) as char).collect();
+    (code.chars().all(|c| c.is_ascii_lowercase()) && code != "und").then_some(code)
+}
+
+// ── Chapters ──────────────────────────────────────────────────────────────────
+
+/// The chapter marks of the MP4 file at `path`: the QuickTime chapter track (a text
+/// track referenced by `tref/chap`, as iTunes audiobooks have), else the Nero `chpl`
+/// list in `moov/udta`. Empty when the file has neither.
+pub fn read_chapters(path: &Path) -> Vec<Chapter> {
+    let Some(moov) = read_moov(path) else { return Vec::new() };
+    let traks: Vec<&[u8]> = children(&moov).filter(|(kind, _)| kind == b"trak").map(|(_, trak)| trak).collect();
+    let chapter_track = traks
+        .iter()
+        .find_map(|trak| be32(child(child(trak, b"tref")?, b"chap")?, 0))
+        .and_then(|id| traks.iter().find(|trak| track_id(trak) == Some(id)));
+    let chapters = chapter_track.and_then(|trak| text_samples(path, trak)).unwrap_or_default();
+    if !chapters.is_empty() {
+        return chapters;
+    }
+    child(&moov, b"udta").and_then(|udta| child(udta, b"chpl")).and_then(nero_chapters).unwrap_or_default()
+}
+
+fn track_id(trak: &[u8]) -> Option<u32> {
+    let tkhd = child(trak, b"tkhd")?;
+    be32(tkhd, if tkhd.first() == Some(&1) { 20 } else { 12 })
+}
+
+/// One chapter per sample of a QuickTime text track: a 16-bit length, then the
+/// title in UTF-8 (or UTF-16 after a byte order mark).
+fn text_samples(path: &Path, trak: &[u8]) -> Option<Vec<Chapter>> {
+    let table = SampleTable::new(trak)?;
+    let mut file = File::open(path).ok()?;
+    let mut chapters = Vec::new();
+    for (sample, ticks) in table.start_times().take(MAX_CHAPTERS) {
+        let (offset, size) = table.locate(sample)?;
+        let mut data = vec![0u8; size.min(1024) as usize];
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut data).ok()?;
+        let len = be16(&data, 0).unwrap_or(0) as usize;
+        let text = data.get(2..2 + len).map(decode_text).unwrap_or_default();
+        chapters.push(Chapter {
+            start: ticks as f64 / table.timescale as f64,
+            title: (!text.trim().is_empty()).then(|| text.trim().to_string()),
+        });
+    }
+    Some(chapters)
+}
+
+fn decode_text(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFE, 0xFF, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Nero `chpl`: a count, then per chapter a start time in 100 ns units and a title
+/// prefixed by its length.
+fn nero_chapters(chpl: &[u8]) -> Option<Vec<Chapter>> {
+    // Version 1 has four reserved bytes after the flags.
+    let mut at = if chpl.first() == Some(&1) { 8 } else { 4 };
+    let count = *chpl.get(at)? as usize;
+    at += 1;
+    let mut chapters = Vec::with_capacity(count);
+    for _ in 0..count {
+        let start = be64(chpl, at)?;
+        let len = *chpl.get(at + 8)? as usize;
+        let title = String::from_utf8_lossy(chpl.get(at + 9..at + 9 + len)?).trim().to_string();
+        chapters.push(Chapter { start: start as f64 / 1e7, title: (!title.is_empty()).then_some(title) });
+        at += 9 + len;
+    }
+    Some(chapters)
+}
+
+/// The sample tables of one track: times (`stts`), chunks (`stsc`, `stco`/`co64`)
+/// and sizes (`stsz`).
+struct SampleTable<'a> {
+    timescale: u32,
+    stts: &'a [u8],
+    stsc: &'a [u8],
+    stsz: &'a [u8],
+    /// Chunk offset table and the width of its entries (4 for `stco`, 8 for `co64`).
+    chunks: (&'a [u8], usize),
+}
+
+impl<'a> SampleTable<'a> {
+    fn new(trak: &'a [u8]) -> Option<Self> {
+        let mdia = child(trak, b"mdia")?;
+        let mdhd = child(mdia, b"mdhd")?;
+        let timescale = if mdhd.first() == Some(&1) { be32(mdhd, 20)? } else { be32(mdhd, 12)? };
+        let stbl = child(child(mdia, b"minf")?, b"stbl")?;
+        let chunks = match child(stbl, b"stco") {
+            Some(stco) => (stco, 4),
+            None => (child(stbl, b"co64")?, 8),
+        };
+        Some(SampleTable {
+            timescale: timescale.max(1),
+            stts: child(stbl, b"stts")?,
+            stsc: child(stbl, b"stsc")?,
+            stsz: child(stbl, b"stsz")?,
+            chunks,
+        })
+    }
+
+    /// Each sample with its start time in timescale ticks. Ends early if the sample
+    /// numbers or times overflow.
+    fn start_times(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+        let runs = be32(self.stts, 4).unwrap_or(0) as usize;
+        let mut sample = 0u32;
+        let mut ticks = 0u64;
+        (0..runs)
+            .map_while(|i| Some((be32(self.stts, 8 + i * 8)?, be32(self.stts, 12 + i * 8)? as u64)))
+            .map_while(move |(count, delta)| {
+                let (first, start) = (sample, ticks);
+                sample = sample.checked_add(count)?;
+                ticks = ticks.checked_add(count as u64 * delta)?;
+                Some((first..sample).map(move |s| (s, start + (s - first) as u64 * delta)))
+            })
+            .flatten()
+    }
+
+    /// File offset and size of `sample`. None if the tables do not cover it or are
+    /// inconsistent (chunk numbers start at 1).
+    fn locate(&self, sample: u32) -> Option<(u64, u32)> {
+        let (offsets, width) = self.chunks;
+        let chunk_count = be32(offsets, 4)?;
+        let runs = be32(self.stsc, 4)? as usize;
+        let mut first = 0u32;
+        for i in 0..runs {
+            let first_chunk = be32(self.stsc, 8 + i * 12)?;
+            let per_chunk = be32(self.stsc, 12 + i * 12)?;
+            let next_chunk = if i + 1 < runs { be32(self.stsc, 8 + (i + 1) * 12)? } else { chunk_count + 1 };
+            let samples = next_chunk.checked_sub(first_chunk)?.checked_mul(per_chunk)?;
+            let within = sample.checked_sub(first)?;
+            if per_chunk > 0 && within < samples {
+                let chunk = first_chunk.checked_sub(1)?.checked_add(within / per_chunk)? as usize;
+                let mut offset = match width {
+                    4 => be32(offsets, 8 + chunk * 4)? as u64,
+                    _ => be64(offsets, 8 + chunk * 8)?,
+                };
+                for earlier in sample - within % per_chunk..sample {
+                    offset = offset.checked_add(self.size(earlier)? as u64)?;
+                }
+                return Some((offset, self.size(sample)?));
+            }
+            first = first.checked_add(samples)?;
+        }
+        None
+    }
+
+    fn size(&self, sample: u32) -> Option<u32> {
+        match be32(self.stsz, 4)? {
+            0 => be32(self.stsz, 12 + sample as usize * 4),
+            fixed => Some(fixed),
+        }
+    }
+}
+
+fn be16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn be32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn be64(data: &[u8], at: usize) -> Option<u64> {
+    data.get(at..at + 8).map(|b| u64::from_be_bytes(b.try_into().unwrap_or_default()))
+}
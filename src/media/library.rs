@@ -1,25 +1,116 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use crate::media::filter::SymlinkPolicy;
 use crate::media::mime::MediaKind;
+use crate::media::title::TitleNormalizer;
 
 /// Metadata extracted from file headers at scan time.
 /// All fields are Option — extraction may fail for any given file.
-#[derive(Debug, Clone, Default)]
+/// Serialized into the persistent metadata index (see `media::index`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MediaMeta {
     /// UPnP duration format: "HH:MM:SS.mmm" (INDX-02). None if extraction failed.
     pub duration: Option<String>,
     /// Pixel dimensions: "WxH" (INDX-03). None if not applicable or extraction failed.
     pub resolution: Option<String>,
-    /// Bitrate in bits per second (INDX-01). None if not available.
+    /// Average bitrate in bytes per second, the unit of the UPnP `res@bitrate`
+    /// attribute (INDX-01). Derived from file size and duration when the container
+    /// does not record it.
     pub bitrate: Option<u32>,
-    /// DLNA profile name e.g. "MP3", "AVC_MP4_MP_HD_720p_AAC" (INDX-04).
+    /// Audio sample rate in Hz.
+    pub sample_rate: Option<u32>,
+    /// Audio channel count.
+    pub channels: Option<u32>,
+    /// Bits per decoded sample; only known for PCM and lossless codecs.
+    pub bits_per_sample: Option<u32>,
+    /// DLNA profile name e.g. "MP3", "AVC_MP4_MP_HD_720p_AAC" (INDX-04), chosen by
+    /// `media::dlna` from the codec, container and picture size.
     /// None means omit DLNA.ORG_PN= from protocolInfo entirely — do NOT use wildcard.
-    pub dlna_profile: Option<&'static str>,
+    #[serde(default, deserialize_with = "crate::media::dlna::deserialize")]
+    pub dlna_profile: Option<crate::media::dlna::Profile>,
+    /// Tag title (ID3 TIT2, Vorbis TITLE, MP4 ©nam). Browse falls back to the file stem.
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    /// Track number within its disc; the "/total" part of "3/12" is dropped.
+    pub track: Option<u32>,
+    pub disc: Option<u32>,
+    pub genre: Option<String>,
+    /// Release year, taken from the leading digits of the date tag.
+    pub year: Option<i32>,
+    pub composer: Option<String>,
+    /// Cover picture: embedded in the file's tags, else a folder image next to it.
+    pub art: Option<AlbumArt>,
+    /// Photo capture time "YYYY-MM-DDTHH:MM:SS" (camera local time): EXIF
+    /// DateTimeOriginal, else a date in the file name.
+    pub taken: Option<String>,
+    /// EXIF orientation, 1-8.
+    pub orientation: Option<u8>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    /// Whether the photo carries GPS coordinates.
+    pub gps: bool,
+    /// Video codec as a short name ("h264", "hevc", "av1", ...). See `mkv::codec_name`.
+    pub video_codec: Option<String>,
+    /// Codec profile as ffmpeg names it ("High", "Main 10"). See `sps::profile_name`.
+    pub video_profile: Option<String>,
+    /// Codec level as a decimal ("4.1", "5.1").
+    pub video_level: Option<String>,
+    /// Luma bit depth of the video stream; 10 or more is what most TVs choke on.
+    pub video_bit_depth: Option<u32>,
+    /// Frames per second, e.g. 23.976.
+    pub frame_rate: Option<f64>,
+    /// Transfer characteristics by their ffmpeg name: "smpte2084" (PQ) for HDR10 and
+    /// Dolby Vision, "arib-std-b67" for HLG, "bt709" for SDR. See `sps::transfer_name`.
+    pub transfer: Option<String>,
+    /// Audio streams of a video file, in container order.
+    pub audio_tracks: Vec<AudioTrack>,
+    /// Kodi `.nfo` sidecar of a video. Its title, year and genres take precedence
+    /// over the file's own tags.
+    pub nfo: Option<crate::media::nfo::Nfo>,
+    /// Chapter marks of an MP4 (Nero or QuickTime chapters) or Matroska file, in
+    /// playback order.
+    pub chapters: Vec<Chapter>,
+    /// Sidecar subtitles of a video. Looked up on every scan, so never persisted.
+    #[serde(skip)]
+    pub subtitles: Vec<crate::media::subtitle::Subtitle>,
 }
 
+/// One audio stream inside a video container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AudioTrack {
+    /// Short codec name ("aac", "ac3", "opus", ...).
+    pub codec: String,
+    /// BCP 47 or ISO 639-2 language code; None when the stream is tagged "und".
+    pub language: Option<String>,
+    pub channels: Option<u32>,
+}
+
+/// A chapter mark: where it starts and what the file calls it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    /// Start time in seconds.
+    pub start: f64,
+    pub title: Option<String>,
+}
+
+/// A cover image served from `/art/{id}`, described at scan time so Browse can pick
+/// its DLNA profile without touching the disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlbumArt {
+    pub mime: String,
+    pub width: u32,
+    pub height: u32,
+    /// The image file for folder art (cover.jpg, ...); None when embedded in the media file.
+    pub path: Option<PathBuf>,
+}
 
 /// A single discovered media file with all metadata extracted at scan time.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MediaItem {
     /// Stable UUIDv5: uuid5(machine_namespace, canonical_path_bytes). Same file on same
     /// machine always produces the same ID across server restarts (CONTEXT.md locked).
@@ -28,6 +119,9 @@ pub struct MediaItem {
     pub path: PathBuf,
     /// File size in bytes, used in DIDL-Lite <res size="..."> (STRM-07).
     pub file_size: u64,
+    /// File modification time at scan. Together with `file_size`, decides whether a
+    /// persisted index entry is still fresh. None if the platform cannot report it.
+    pub modified: Option<SystemTime>,
     /// MIME type string from Phase 1 classify() — static str, e.g. "video/mp4".
     pub mime: &'static str,
     /// Media kind from Phase 1 classify() — Video, Audio, or Image (not Subtitle).
@@ -36,17 +130,163 @@ pub struct MediaItem {
     pub meta: MediaMeta,
 }
 
+/// A named library from the config file: its own top-level Browse container over
+/// `paths`, optionally limited to some media kinds (empty `kinds` = all).
+/// The scanner stores a copy with canonical paths in `MediaLibrary::libraries`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NamedLibrary {
+    pub name: String,
+    pub paths: Vec<PathBuf>,
+    #[serde(default)]
+    pub kinds: Vec<MediaKind>,
+}
+
+impl NamedLibrary {
+    /// Whether items of `kind` belong in this library.
+    pub fn accepts(&self, kind: MediaKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+}
+
+/// Item-level differences between two library snapshots (see `MediaLibrary::diff`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LibraryDiff {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+}
+
+impl LibraryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.changed == 0
+    }
+}
+
 /// Flat in-memory media library built synchronously at startup.
 /// Wrapped in Arc<RwLock<MediaLibrary>> in main.rs for thread-safe sharing.
-/// Library is write-once at startup, then read-only for the server lifetime.
-#[derive(Debug, Default)]
+/// Populated by the startup scan; the filesystem watcher applies incremental
+/// changes under the write lock and bumps `update_id` for each batch, and a
+/// SIGHUP rescan replaces the whole library in one swap.
+#[derive(Debug)]
 pub struct MediaLibrary {
     /// All discovered media items. No subtitle items — subtitles are filtered at scan time.
     pub items: Vec<MediaItem>,
+    /// Canonical scan roots, in configuration order. The Folders view is built
+    /// relative to these.
+    pub roots: Vec<PathBuf>,
+    /// Named libraries with canonical paths, each shown as its own top-level container.
+    pub libraries: Vec<NamedLibrary>,
+    /// Symlink policy of the scan that built this library, re-checked before a file
+    /// is streamed in case the tree changed since. A library not built by the
+    /// scanner has no roots to check against and uses `Always`.
+    pub symlinks: SymlinkPolicy,
+    /// Turns file names into titles for items without a tag or .nfo title.
+    pub titles: TitleNormalizer,
+    /// CDS SystemUpdateID. Starts at 1 and increments whenever the item set changes,
+    /// so control points know to refresh cached Browse results.
+    pub update_id: u32,
+    /// Position of each item in `items` by path, so `upsert` needs no search. Rebuilt
+    /// when its size no longer matches `items` or an entry points at another item,
+    /// as after `items` is assigned directly.
+    by_path: HashMap<PathBuf, usize>,
+}
+
+impl Default for MediaLibrary {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            roots: Vec::new(),
+            libraries: Vec::new(),
+            symlinks: SymlinkPolicy::Always,
+            titles: TitleNormalizer::default(),
+            update_id: 1,
+            by_path: HashMap::new(),
+        }
+    }
 }
 
 impl MediaLibrary {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Insert `item`, replacing any existing item with the same path (and so the same ID).
+    /// Returns true if the library changed (new item, or different size/metadata).
+    pub fn upsert(&mut self, item: MediaItem) -> bool {
+        match self.position(&item.path) {
+            Some(i) if self.items[i] == item => false,
+            Some(i) => {
+                self.items[i] = item;
+                true
+            }
+            None => {
+                self.by_path.insert(item.path.clone(), self.items.len());
+                self.items.push(item);
+                true
+            }
+        }
+    }
+
+    /// Append freshly scanned items, whose paths are not in the library yet.
+    pub fn extend(&mut self, items: impl IntoIterator<Item = MediaItem>) {
+        self.sync_paths();
+        for item in items {
+            self.by_path.insert(item.path.clone(), self.items.len());
+            self.items.push(item);
+        }
+    }
+
+    /// Remove every item whose path is `path` or lies underneath it (directory removal).
+    /// Returns the number of items removed.
+    pub fn remove_under(&mut self, path: &Path) -> usize {
+        let before = self.items.len();
+        self.items.retain(|it| !it.path.starts_with(path));
+        let removed = before - self.items.len();
+        if removed > 0 {
+            self.by_path.clear();
+        }
+        removed
+    }
+
+    /// Index of the item at `path`.
+    fn position(&mut self, path: &Path) -> Option<usize> {
+        self.sync_paths();
+        let i = *self.by_path.get(path)?;
+        if self.items[i].path == path {
+            return Some(i);
+        }
+        // `items` was rearranged directly; look again in a fresh index.
+        self.by_path.clear();
+        self.sync_paths();
+        self.by_path.get(path).copied()
+    }
+
+    /// Rebuild `by_path` if it no longer covers `items`.
+    fn sync_paths(&mut self) {
+        if self.by_path.len() != self.items.len() {
+            self.by_path = self.items.iter().enumerate().map(|(i, it)| (it.path.clone(), i)).collect();
+        }
+    }
+
+    /// Compare this library against a freshly scanned one, matching items by ID.
+    /// Used to summarise a SIGHUP rescan before the new library is swapped in.
+    pub fn diff(&self, newer: &MediaLibrary) -> LibraryDiff {
+        let old: HashMap<Uuid, &MediaItem> = self.items.iter().map(|it| (it.id, it)).collect();
+        let mut diff = LibraryDiff::default();
+        for item in &newer.items {
+            match old.get(&item.id) {
+                None => diff.added += 1,
+                Some(prev) if *prev != item => diff.changed += 1,
+                Some(_) => {}
+            }
+        }
+        let new_ids: HashSet<Uuid> = newer.items.iter().map(|it| it.id).collect();
+        diff.removed = self.items.iter().filter(|it| !new_ids.contains(&it.id)).count();
+        diff
+    }
+
+    /// Advance SystemUpdateID after a change. Wraps to 1 (not 0) per UPnP CDS:1 §2.5.20.
+    pub fn bump_update_id(&mut self) {
+        self.update_id = self.update_id.checked_add(1).unwrap_or(1);
+    }
 }
@@ -2,8 +2,11 @@ use std::io::BufReader;
 use std::path::Path;
 use uuid::Uuid;
 
-use crate::media::library::MediaMeta;
+use crate::media::library::{AudioTrack, MediaMeta};
 use crate::media::mime::MediaKind;
+use crate::media::dlna::{self, Container, Video};
+use crate::media::mkv::TrackKind;
+use crate::media::{art, asf, avi, isobmff, mkv, mp3, ps, sps, ts};
 
 /// Format a duration for UPnP/DLNA. `total_seconds` is the whole-second count;
 /// `frac` is the sub-second fraction in [0.0, 1.0).
@@ -16,17 +19,19 @@ pub fn format_upnp_duration(total_seconds: u64, frac: f64) -> String {
     format!("{:02}:{:02}:{:02}.{:03}", h, m, s, ms)
 }
 
-/// Return the static DLNA profile name for a given MIME type, or None when the
-/// type has no assigned profile (Phase 2 scope).
-/// video/mp4 returns None — DLNA profile deferred to Phase 5 per RESEARCH.md.
-pub fn dlna_profile_for(mime: &str) -> Option<&'static str> {
-    match mime {
-        "audio/mpeg" => Some("MP3"),
-        "audio/mp4" => Some("AAC_ISO_320"),
-        "image/jpeg" => Some("JPEG_LRG"),
-        "image/png" => Some("PNG_LRG"),
-        _ => None,
-    }
+/// Parse an "H+:MM:SS[.mmm]" UPnP duration back into seconds.
+pub fn parse_upnp_duration(duration: &str) -> Option<f64> {
+    let mut parts = duration.splitn(3, ':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    (minutes < 60 && (0.0..60.0).contains(&seconds)).then(|| (hours * 3600 + minutes * 60) as f64 + seconds)
+}
+
+/// `format_upnp_duration` for a length in (fractional) seconds, rounded to the millisecond.
+pub fn format_seconds(seconds: f64) -> String {
+    let ms = (seconds * 1000.0).round() as u64;
+    format_upnp_duration(ms / 1000, (ms % 1000) as f64 / 1000.0)
 }
 
 /// Derive a machine-specific UUID namespace by seeding UUIDv5 from the machine UID.
@@ -51,12 +56,42 @@ pub fn media_item_id(namespace: &Uuid, canonical_path: &Path) -> Uuid {
 /// Returns None if extraction fails entirely — partial data is never returned.
 /// Per LOCKED decision: skip files that fail extraction entirely (not include with null fields).
 pub fn extract_metadata(path: &Path, kind: MediaKind, mime: &'static str) -> Option<MediaMeta> {
-    match kind {
+    let mut meta = match kind {
+        MediaKind::Audio if mime == "audio/x-ms-wma" => extract_wma_meta(path, mime),
         MediaKind::Audio => extract_audio_meta(path, mime),
         MediaKind::Video => extract_video_meta(path, mime),
         MediaKind::Image => extract_image_meta(path, mime),
         MediaKind::Subtitle => None, // Subtitles are filtered before this is called
+    }?;
+    if meta.bitrate.is_none() && kind != MediaKind::Image {
+        meta.bitrate = average_bitrate(path, meta.duration.as_deref());
     }
+    Some(meta)
+}
+
+/// File size over duration, in bytes per second. Includes container overhead, which
+/// is what a renderer sizing its buffer wants anyway.
+fn average_bitrate(path: &Path, duration: Option<&str>) -> Option<u32> {
+    let seconds = parse_upnp_duration(duration?)?;
+    let size = std::fs::metadata(path).ok()?.len();
+    (seconds > 0.0).then(|| (size as f64 / seconds).round() as u32)
+}
+
+/// Short name of a symphonia audio codec, as in `AudioTrack::codec`.
+fn codec_name(codec: symphonia::core::codecs::CodecType) -> Option<&'static str> {
+    use symphonia::core::codecs::*;
+
+    Some(match codec {
+        CODEC_TYPE_MP3 => "mp3",
+        CODEC_TYPE_AAC => "aac",
+        CODEC_TYPE_FLAC => "flac",
+        CODEC_TYPE_ALAC => "alac",
+        CODEC_TYPE_VORBIS => "vorbis",
+        CODEC_TYPE_OPUS => "opus",
+        CODEC_TYPE_PCM_S16LE | CODEC_TYPE_PCM_S16BE | CODEC_TYPE_PCM_S24LE | CODEC_TYPE_PCM_S24BE
+        | CODEC_TYPE_PCM_S32LE | CODEC_TYPE_PCM_S32BE | CODEC_TYPE_PCM_F32LE | CODEC_TYPE_PCM_F32BE => "pcm",
+        _ => return None,
+    })
 }
 
 /// Extract metadata from an audio file using symphonia.
@@ -70,15 +105,16 @@ fn extract_audio_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
     let mut hint = Hint::new();
+    hint.mime_type(mime);
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
         hint.with_extension(ext);
     }
 
-    let probed = symphonia::default::get_probe()
+    let mut probed = symphonia::default::get_probe()
         .format(&hint, mss, &Default::default(), &Default::default())
         .ok()?;
 
-    let format = probed.format;
+    let mut format = probed.format;
 
     // Select first non-null track
     let track = format
@@ -87,8 +123,7 @@ fn extract_audio_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
         .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)?;
 
     // Duration via n_frames × time_base (works for FLAC, WAV, OGG, M4A with known frame count)
-    // For VBR MP3 without Xing header, n_frames may be None — log and leave duration as None
-    let duration = track
+    let mut duration = track
         .codec_params
         .time_base
         .and_then(|tb| {
@@ -98,37 +133,149 @@ fn extract_audio_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
             })
         });
 
+    let codec = codec_name(track.codec_params.codec);
+    let mut sample_rate = track.codec_params.sample_rate;
+    let mut channels = track.codec_params.channels.map(|c| c.count() as u32);
+    let bits_per_sample = track.codec_params.bits_per_sample;
+    // Symphonia reports no bitrate; it is derived from size and duration below.
+    let mut bitrate = None;
+
+    // MP3: symphonia only knows the frame count when there is a Xing header, and does
+    // not subtract the encoder delay. Read the frame headers ourselves.
+    if mime == "audio/mpeg" {
+        if let Some(info) = mp3::read_info(path) {
+            tracing::debug!("MP3 duration for {} from {:?}", path.display(), info.source);
+            duration = Some(format_seconds(info.duration));
+            bitrate = Some(info.bitrate / 8);
+            sample_rate = sample_rate.or(Some(info.sample_rate));
+            channels = channels.or(Some(info.channels));
+        }
+    }
+
     if duration.is_none() {
         tracing::debug!("No duration for {} (n_frames unavailable)", path.display());
     }
 
-    // Bitrate from bits_per_coded_sample if available
-    let bitrate = track
-        .codec_params
-        .bits_per_coded_sample;
-
-    Some(MediaMeta {
+    // The AAC profiles have bitrate limits, so the estimate is needed before picking one.
+    let bitrate = bitrate.or_else(|| average_bitrate(path, duration.as_deref()));
+    let mut meta = MediaMeta {
         duration,
         resolution: None,
         bitrate,
-        dlna_profile: dlna_profile_for(mime),
-    })
+        sample_rate,
+        channels,
+        bits_per_sample,
+        ..Default::default()
+    };
+    meta.dlna_profile = codec.and_then(|codec| dlna::audio_profile(mime, codec, &meta));
+    // Audiobooks (.m4b) and long podcasts carry chapter marks.
+    if mime == "audio/mp4" {
+        meta.chapters = isobmff::read_chapters(path);
+    }
+
+    // Tags found ahead of the container (ID3v2 on MP3) first, then the container's own
+    // (Vorbis comments, MP4 ilst, RIFF INFO), which win where both set a field.
+    if let Some(revision) = probed.metadata.get().as_mut().and_then(|m| m.skip_to_latest()) {
+        apply_tags(&mut meta, revision.tags());
+        meta.art = art::pick_cover(revision.visuals()).and_then(art::describe);
+    }
+    if let Some(revision) = format.metadata().skip_to_latest() {
+        apply_tags(&mut meta, revision.tags());
+        if let Some(cover) = art::pick_cover(revision.visuals()).and_then(art::describe) {
+            meta.art = Some(cover);
+        }
+    }
+
+    Some(meta)
+}
+
+/// Extract WMA metadata from the ASF header, which symphonia cannot demux: duration,
+/// the audio stream format, and title and author.
+fn extract_wma_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
+    let info = asf::read_info(path)?;
+    let audio = info.audio?;
+    let lossless = matches!(audio.codec.as_str(), "pcm" | "wmalossless");
+    let mut meta = MediaMeta {
+        duration: info.duration.map(format_seconds),
+        bitrate: Some(audio.bitrate).filter(|&b| b > 0),
+        sample_rate: Some(audio.sample_rate).filter(|&r| r > 0),
+        channels: Some(audio.channels).filter(|&c| c > 0),
+        bits_per_sample: Some(audio.bits_per_sample).filter(|&b| b > 0 && lossless),
+        title: info.title,
+        artist: info.author,
+        ..Default::default()
+    };
+    meta.dlna_profile = dlna::audio_profile(mime, &audio.codec, &meta);
+    Some(meta)
+}
+
+/// Copy the standard tags of one metadata revision into `meta`. Empty values are ignored.
+fn apply_tags(meta: &mut MediaMeta, tags: &[symphonia::core::meta::Tag]) {
+    use symphonia::core::meta::StandardTagKey;
+
+    for tag in tags {
+        let Some(key) = tag.std_key else { continue };
+        let value = tag.value.to_string();
+        let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+        if value.is_empty() {
+            continue;
+        }
+        let text = || Some(value.to_string());
+        match key {
+            StandardTagKey::TrackTitle => meta.title = text(),
+            StandardTagKey::Artist => meta.artist = text(),
+            StandardTagKey::Album => meta.album = text(),
+            StandardTagKey::AlbumArtist => meta.album_artist = text(),
+            StandardTagKey::Genre => meta.genre = text(),
+            StandardTagKey::Composer => meta.composer = text(),
+            StandardTagKey::TrackNumber => meta.track = leading_number(value).or(meta.track),
+            StandardTagKey::DiscNumber => meta.disc = leading_number(value).or(meta.disc),
+            StandardTagKey::Date => meta.year = parse_year(value).or(meta.year),
+            StandardTagKey::OriginalDate if meta.year.is_none() => meta.year = parse_year(value),
+            _ => {}
+        }
+    }
+}
+
+/// "3/12" → 3. Zero is treated as absent.
+fn leading_number(value: &str) -> Option<u32> {
+    value.split('/').next()?.trim().parse().ok().filter(|&n| n > 0)
+}
+
+/// "1997", "1997-05-12", "1997-05-12T10:00:00" → 1997.
+fn parse_year(value: &str) -> Option<i32> {
+    let digits = value.get(..4)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
 }
 
 /// Extract metadata from a video file.
 /// For MP4/M4V, uses the mp4 crate for width/height/duration.
-/// For other video formats (MKV, AVI, etc.), uses symphonia for audio track duration;
+/// For MKV/WebM, MPEG-TS, MPEG-PS and AVI, reads the container headers directly (see
+/// `media::mkv`, `media::ts`, `media::ps`, `media::avi`).
+/// For other video formats, uses symphonia for audio track duration;
 /// resolution is left as None since symphonia does not expose video frame dimensions.
 fn extract_video_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
     match mime {
-        "video/mp4" | "video/x-m4v" => extract_mp4_video_meta(path, mime),
+        "video/mp4" | "video/x-m4v" => extract_mp4_video_meta(path),
+        "video/x-matroska" | "video/webm" => {
+            extract_mkv_meta(path).or_else(|| extract_non_mp4_video_meta(path, mime))
+        }
+        "video/MP2T" => extract_ts_meta(path).or_else(|| extract_non_mp4_video_meta(path, mime)),
+        "video/mpeg" => extract_ps_meta(path).or_else(|| extract_non_mp4_video_meta(path, mime)),
+        "video/x-msvideo" => extract_avi_meta(path).or_else(|| extract_non_mp4_video_meta(path, mime)),
         _ => extract_non_mp4_video_meta(path, mime),
     }
 }
 
-/// Extract MP4/M4V video metadata using the mp4 crate.
-fn extract_mp4_video_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
-    use mp4::TrackType;
+/// Extract MP4/M4V video metadata: duration, size and bitrate of the first video
+/// track and cover art through the mp4 crate, codec details and audio tracks from the
+/// sample descriptions (see `media::isobmff`), which also cover hvc1, av01, AC-3 and
+/// the other sample entries the crate does not parse, and chapter marks.
+fn extract_mp4_video_meta(path: &Path) -> Option<MediaMeta> {
+    use mp4::{Metadata as _, TrackType};
 
     let file = std::fs::File::open(path).ok()?;
     let file_len = file.metadata().ok()?.len();
@@ -147,34 +294,59 @@ fn extract_mp4_video_meta(path: &Path, mime: &'static str) -> Option<MediaMeta>
         None
     };
 
-    // Find first video track for resolution and bitrate
-    let mut resolution = None;
-    let mut bitrate = None;
+    let first_video = mp4
+        .tracks()
+        .values()
+        .filter(|t| matches!(t.track_type(), Ok(TrackType::Video)))
+        .min_by_key(|t| t.track_id());
+    let (width, height) = first_video.map_or((0, 0), |t| (t.width() as u32, t.height() as u32));
+    let mut meta = MediaMeta {
+        duration,
+        resolution: (width > 0 && height > 0).then(|| format!("{}x{}", width, height)),
+        bitrate: first_video.map(|t| t.bitrate() / 8).filter(|&b| b > 0),
+        art: mp4.metadata().poster().and_then(art::describe),
+        ..Default::default()
+    };
 
-    for track in mp4.tracks().values() {
-        if matches!(track.track_type(), Ok(TrackType::Video)) {
-            let w = track.width();
-            let h = track.height();
-            if w > 0 && h > 0 {
-                resolution = Some(format!("{}x{}", w, h));
-            }
-            let bps = track.bitrate();
-            if bps > 0 {
-                bitrate = Some(bps);
-            }
-            break;
-        }
+    let tracks = isobmff::read_tracks(path).unwrap_or_default();
+    let video = tracks.iter().find(|t| t.kind == TrackKind::Video);
+    if let Some(video) = video {
+        set_video_format(&mut meta, &video.codec, video.config, video.transfer, video.frame_rate);
     }
+    meta.audio_tracks = tracks
+        .iter()
+        .filter(|t| t.kind == TrackKind::Audio)
+        .map(|t| AudioTrack { codec: t.codec.clone(), language: t.language.clone(), channels: t.channels })
+        .collect();
+    meta.dlna_profile = meta.video_codec.as_deref().and_then(|codec| {
+        let profile = video.and_then(|v| v.config).map_or(0, |c| c.profile);
+        dlna::video_profile(Container::Mp4, Video { codec, profile, width, height }, meta.audio_tracks.first())
+    });
+    meta.chapters = isobmff::read_chapters(path);
+    Some(meta)
+}
 
-    Some(MediaMeta {
-        duration,
-        resolution,
-        bitrate,
-        dlna_profile: dlna_profile_for(mime),
-    })
+/// Record the video stream's codec and, where known, its profile, level, bit depth,
+/// transfer characteristics and frame rate.
+fn set_video_format(
+    meta: &mut MediaMeta,
+    codec: &str,
+    config: Option<sps::Sps>,
+    transfer: Option<u8>,
+    frame_rate: Option<f64>,
+) {
+    meta.video_codec = Some(codec.to_string());
+    if let Some(config) = config {
+        meta.video_profile = sps::profile_name(codec, config.profile).map(str::to_string);
+        meta.video_level = sps::level_name(codec, config.level);
+        meta.video_bit_depth = Some(config.bit_depth as u32).filter(|&b| b > 0);
+    }
+    meta.transfer = transfer.and_then(sps::transfer_name).map(str::to_string);
+    meta.frame_rate = frame_rate.filter(|f| f.is_finite() && *f > 0.0).map(|f| (f * 1000.0).round() / 1000.0);
 }
 
-/// Extract non-MP4 video metadata (MKV, AVI, etc.) using symphonia for audio track duration.
+/// Extract video metadata for containers without a native reader, and for MKV/TS/AVI
+/// files their reader rejected, using symphonia for audio track duration.
 /// Resolution is not available — symphonia does not expose video frame dimensions (RESEARCH.md Pitfall 2).
 fn extract_non_mp4_video_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
     use symphonia::core::codecs::CODEC_TYPE_NULL;
@@ -185,6 +357,7 @@ fn extract_non_mp4_video_meta(path: &Path, mime: &'static str) -> Option<MediaMe
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
     let mut hint = Hint::new();
+    hint.mime_type(mime);
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
         hint.with_extension(ext);
     }
@@ -216,27 +389,226 @@ fn extract_non_mp4_video_meta(path: &Path, mime: &'static str) -> Option<MediaMe
         duration,
         resolution: None,
         bitrate: None,
-        dlna_profile: dlna_profile_for(mime),
+        ..Default::default()
     })
 }
 
-/// Extract image metadata (dimensions only) using imagesize.
-/// Uses header-only reads (~16 bytes) — never fully decodes the image.
-/// Returns None if imagesize fails — LOCKED: skip file on extraction failure.
-fn extract_image_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
-    match imagesize::size(path) {
-        Ok(dim) => {
-            let resolution = format!("{}x{}", dim.width, dim.height);
-            Some(MediaMeta {
-                duration: None,
-                resolution: Some(resolution),
-                bitrate: None,
-                dlna_profile: dlna_profile_for(mime),
+/// Extract Matroska/WebM metadata from the segment Info, Tracks and Chapters elements:
+/// duration, the first video track's resolution, codec details and colour, every
+/// audio track, and the chapter marks. None if the EBML headers cannot be read, so
+/// the caller can fall back to symphonia.
+fn extract_mkv_meta(path: &Path) -> Option<MediaMeta> {
+    let info = mkv::read_info(path)?;
+
+    let video = info.tracks.iter().find(|t| t.kind == TrackKind::Video);
+    let mut meta = MediaMeta {
+        duration: info.duration.map(format_seconds),
+        resolution: video.and_then(|v| match (v.width?, v.height?) {
+            (0, _) | (_, 0) => None,
+            (w, h) => Some(format!("{}x{}", w, h)),
+        }),
+        audio_tracks: info
+            .tracks
+            .iter()
+            .filter(|t| t.kind == TrackKind::Audio)
+            .map(|t| AudioTrack {
+                codec: mkv::codec_name(&t.codec_id).to_string(),
+                language: Some(t.language.clone()).filter(|l| !l.is_empty() && l != "und"),
+                channels: t.channels,
             })
+            .collect(),
+        art: mkv::cover_attachment(path).and_then(|a| art::describe(&a.data)),
+        chapters: info.chapters,
+        ..Default::default()
+    };
+    if let Some(video) = video {
+        let codec = mkv::codec_name(&video.codec_id);
+        let private = video.codec_private.as_deref().unwrap_or_default();
+        let config = match codec {
+            "h264" => sps::avc_config(private),
+            "hevc" => sps::hevc_config(private),
+            "av1" => sps::av1_config(private),
+            _ => None,
+        };
+        let frame_rate = video.default_duration.map(|ns| 1e9 / ns as f64);
+        set_video_format(&mut meta, codec, config, video.transfer, frame_rate);
+        if meta.video_bit_depth.is_none() {
+            meta.video_bit_depth = video.colour_bits;
         }
+    }
+    Some(meta)
+}
+
+/// Extract MPEG transport stream metadata: PCR duration, the video stream's codec,
+/// picture size and sequence header details, and the audio streams of the first
+/// program. None without a PAT/PMT.
+fn extract_ts_meta(path: &Path) -> Option<MediaMeta> {
+    let info = ts::read_info(path)?;
+    let container = Container::Ts { timestamped: info.timestamped };
+    let mut meta = MediaMeta {
+        duration: info.duration.map(format_seconds),
+        resolution: info.video.map(|v| format!("{}x{}", v.width, v.height)),
+        dlna_profile: info.video_codec.zip(info.video).and_then(|(codec, v)| {
+            let video = Video { codec, profile: v.profile, width: v.width, height: v.height };
+            dlna::video_profile(container, video, info.audio.first())
+        }),
+        audio_tracks: info.audio,
+        ..Default::default()
+    };
+    if let Some(codec) = info.video_codec {
+        set_video_format(&mut meta, codec, info.video, None, None);
+    }
+    Some(meta)
+}
+
+/// Extract MPEG program stream metadata: SCR duration, the video's MPEG flavour and
+/// picture size, and the audio streams. None without a pack header.
+fn extract_ps_meta(path: &Path) -> Option<MediaMeta> {
+    let info = ps::read_info(path)?;
+    let dlna_profile = info.video_codec.zip(info.size).and_then(|(codec, (width, height))| {
+        let video = Video { codec, profile: 0, width, height };
+        dlna::video_profile(Container::Ps, video, info.audio.first())
+    });
+    Some(MediaMeta {
+        duration: info.duration.map(format_seconds),
+        resolution: info.size.map(|(w, h)| format!("{}x{}", w, h)),
+        dlna_profile,
+        video_codec: info.video_codec.map(str::to_string),
+        audio_tracks: info.audio,
+        ..Default::default()
+    })
+}
+
+/// Extract AVI metadata from the `hdrl` headers. None if the file is not RIFF AVI.
+fn extract_avi_meta(path: &Path) -> Option<MediaMeta> {
+    let info = avi::read_info(path)?;
+    let resolution = match (info.width, info.height) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => Some(format!("{}x{}", w, h)),
+        _ => None,
+    };
+    Some(MediaMeta {
+        duration: info.duration.map(format_seconds),
+        resolution,
+        video_codec: info.video_codec,
+        audio_tracks: info.audio,
+        ..Default::default()
+    })
+}
+
+/// Extract image metadata: dimensions via imagesize (header-only reads, never decodes
+/// the image), then EXIF capture time, orientation, camera and GPS presence.
+/// Returns None if imagesize fails — LOCKED: skip file on extraction failure.
+/// Missing or unreadable EXIF is not a failure.
+fn extract_image_meta(path: &Path, mime: &'static str) -> Option<MediaMeta> {
+    let dim = match imagesize::size(path) {
+        Ok(dim) => dim,
         Err(e) => {
             tracing::warn!("Cannot read image dimensions for {}: {}", path.display(), e);
-            None
+            return None;
         }
+    };
+    let mut meta = MediaMeta {
+        duration: None,
+        resolution: Some(format!("{}x{}", dim.width, dim.height)),
+        bitrate: None,
+        dlna_profile: dlna::image_profile(mime, dim.width as u32, dim.height as u32),
+        ..Default::default()
+    };
+    apply_exif(&mut meta, path);
+    if meta.taken.is_none() {
+        meta.taken = path.file_name().and_then(|n| n.to_str()).and_then(date_from_file_name);
+    }
+    Some(meta)
+}
+
+/// Copy the EXIF fields we use into `meta`. Files without EXIF are left untouched.
+fn apply_exif(meta: &mut MediaMeta, path: &Path) {
+    use exif::{In, Tag, Value};
+
+    let Ok(file) = std::fs::File::open(path) else { return };
+    let Ok(exif) = exif::Reader::new().read_from_container(&mut BufReader::new(file)) else {
+        return;
+    };
+    let ascii = |tag: Tag| match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(parts) => {
+            let text = String::from_utf8_lossy(parts.first()?);
+            let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+            (!text.is_empty()).then(|| text.to_string())
+        }
+        _ => None,
+    };
+
+    meta.taken = [Tag::DateTimeOriginal, Tag::DateTimeDigitized, Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| {
+            let field = exif.get_field(tag, In::PRIMARY)?;
+            let Value::Ascii(parts) = &field.value else { return None };
+            let dt = exif::DateTime::from_ascii(parts.first()?).ok()?;
+            let (hour, minute, second) = (dt.hour.into(), dt.minute.into(), dt.second.into());
+            format_taken(dt.year.into(), dt.month.into(), dt.day.into(), hour, minute, second)
+        });
+    meta.orientation = exif
+        .get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|f| f.value.get_uint(0))
+        .and_then(|o| u8::try_from(o).ok())
+        .filter(|o| (1..=8).contains(o));
+    meta.camera_make = ascii(Tag::Make);
+    meta.camera_model = ascii(Tag::Model);
+    meta.gps = exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_some();
+}
+
+/// "YYYY-MM-DDTHH:MM:SS", or None when any part is out of range.
+fn format_taken(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> Option<String> {
+    chrono::NaiveDate::from_ymd_opt(year, month, day)?
+        .and_hms_opt(hour, minute, second)
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+/// Capture time from camera and phone file names, as "YYYY-MM-DDTHH:MM:SS".
+///
+/// Looks for a plausible date (1990-2100) starting at a digit-run boundary, in compact
+/// `YYYYMMDD` or dashed `YYYY-MM-DD` form, optionally followed by a time:
+/// - `IMG_20230101_142530.jpg`, `PXL_20230101_142530123.jpg`, `20230101-142530.jpg`
+/// - `Screenshot_2023-01-01-14-25-30.png`, `Photo 2023-01-01 14.25.30.jpg`
+/// - `IMG-20230101-WA0003.jpg` (no time: midnight)
+pub fn date_from_file_name(name: &str) -> Option<String> {
+    let bytes = name.as_bytes();
+    let number = |at: usize, len: usize| -> Option<u32> {
+        let run = bytes.get(at..at + len)?;
+        run.iter().all(u8::is_ascii_digit).then(|| std::str::from_utf8(run).ok()?.parse().ok())?
+    };
+    let is = |at: usize, set: &[u8]| bytes.get(at).is_some_and(|b| set.contains(b));
+
+    for start in (0..bytes.len()).filter(|&i| i == 0 || !bytes[i - 1].is_ascii_digit()) {
+        let Some(year) = number(start, 4) else { continue };
+        // (month, day, offset of the time part, separators allowed inside the time)
+        let (month, day, time_at, time_sep): (_, _, _, &[u8]) = if is(start + 4, b"-") && is(start + 7, b"-") {
+            (number(start + 5, 2), number(start + 8, 2), start + 10, b"-.:")
+        } else {
+            (number(start + 4, 2), number(start + 6, 2), start + 8, b"")
+        };
+        let (Some(month), Some(day)) = (month, day) else { continue };
+
+        let time_at = if is(time_at, b"_- ") { time_at + 1 } else { time_at };
+        let step = if time_sep.is_empty() { 2 } else { 3 };
+        let separated = time_sep.is_empty() || (is(time_at + 2, time_sep) && is(time_at + 5, time_sep));
+        let time = separated
+            .then(|| Some((number(time_at, 2)?, number(time_at + step, 2)?, number(time_at + 2 * step, 2)?)))
+            .flatten();
+        if let Some(taken) = plausible(year, month, day, time) {
+            return Some(taken);
+        }
+    }
+    None
+}
+
+fn plausible(year: u32, month: u32, day: u32, time: Option<(u32, u32, u32)>) -> Option<String> {
+    if !(1990..=2100).contains(&year) {
+        return None;
+    }
+    let date = format_taken(year as i32, month, day, 0, 0, 0)?;
+    match time {
+        Some((h, m, s)) => format_taken(year as i32, month, day, h, m, s).or(Some(date)),
+        None => Some(date),
     }
 }
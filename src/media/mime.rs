@@ -1,5 +1,7 @@
 use std::path::Path;
 
+use serde::Deserialize;
+
 /// All MIME types this server can serve. Used by CMS GetProtocolInfo.
 /// Only video, audio, and image types — subtitle types (text/srt, text/vtt)
 /// are intentionally excluded per DLNA ConnectionManager spec.
@@ -35,7 +37,9 @@ pub const SUPPORTED_MIMES: &[&str] = &[
 ];
 
 /// Media kind classification for discovered files.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Deserializes from lowercase names ("video", "audio", "image") in the config file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum MediaKind {
     Video,
     Audio,
@@ -76,6 +80,7 @@ pub fn classify(path: &Path) -> Option<(MediaKind, &'static str)> {
         "flac" => (MediaKind::Audio, "audio/flac"),
         "wav" => (MediaKind::Audio, "audio/wav"),
         "m4a" => (MediaKind::Audio, "audio/mp4"),
+        "m4b" => (MediaKind::Audio, "audio/mp4"),
         "aac" => (MediaKind::Audio, "audio/aac"),
         "ogg" => (MediaKind::Audio, "audio/ogg"),
         "oga" => (MediaKind::Audio, "audio/ogg"),
@@ -104,3 +109,20 @@ pub fn classify(path: &Path) -> Option<(MediaKind, &'static str)> {
 
     Some(result)
 }
+
+/// Media kind implied by a MIME type's top-level type.
+pub fn kind_for_mime(mime: &str) -> Option<MediaKind> {
+    match mime.split('/').next()? {
+        "video" => Some(MediaKind::Video),
+        "audio" => Some(MediaKind::Audio),
+        "image" => Some(MediaKind::Image),
+        "text" => Some(MediaKind::Subtitle),
+        _ => None,
+    }
+}
+
+/// The `SUPPORTED_MIMES` entry equal to `mime`, for turning a stored string back
+/// into the `&'static str` that `MediaItem` carries.
+pub fn supported_mime(mime: &str) -> Option<&'static str> {
+    SUPPORTED_MIMES.iter().copied().find(|m| *m == mime)
+}
@@ -0,0 +1,568 @@
+//! Minimal Matroska (EBML) reader for the parts symphonia does not expose: segment
+//! duration, track headers, chapters and attachments.
+
+use std::fs::File;
+use std::collections::HashSet;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::media::library::Chapter;
+
+const EBML_HEADER: u32 = 0x1A45_DFA3;
+const SEGMENT: u32 = 0x1853_8067;
+const SEEK_HEAD: u32 = 0x114D_9B74;
+const SEEK: u32 = 0x4DBB;
+const SEEK_ID: u32 = 0x53AB;
+const SEEK_POSITION: u32 = 0x53AC;
+const CLUSTER: u32 = 0x1F43_B675;
+
+const INFO: u32 = 0x1549_A966;
+const TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+const DURATION: u32 = 0x4489;
+
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_TYPE: u32 = 0x83;
+const CODEC_ID: u32 = 0x86;
+const CODEC_PRIVATE: u32 = 0x63A2;
+const DEFAULT_DURATION: u32 = 0x23_E383;
+const TRACK_NAME: u32 = 0x536E;
+const LANGUAGE: u32 = 0x22_B59C;
+const LANGUAGE_IETF: u32 = 0x22_B59D;
+const FLAG_DEFAULT: u32 = 0x88;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const COLOUR: u32 = 0x55B0;
+const BITS_PER_CHANNEL: u32 = 0x55B2;
+const TRANSFER_CHARACTERISTICS: u32 = 0x55BA;
+const AUDIO: u32 = 0xE1;
+const SAMPLING_FREQUENCY: u32 = 0xB5;
+const CHANNELS: u32 = 0x9F;
+const BIT_DEPTH: u32 = 0x6264;
+
+const CHAPTERS: u32 = 0x1043_A770;
+const EDITION_ENTRY: u32 = 0x45B9;
+const CHAPTER_ATOM: u32 = 0xB6;
+const CHAPTER_TIME_START: u32 = 0x91;
+const CHAPTER_FLAG_HIDDEN: u32 = 0x98;
+const CHAPTER_FLAG_ENABLED: u32 = 0x4598;
+const CHAPTER_DISPLAY: u32 = 0x80;
+const CHAP_STRING: u32 = 0x85;
+
+const ATTACHMENTS: u32 = 0x1941_A469;
+const ATTACHED_FILE: u32 = 0x61A7;
+const FILE_NAME: u32 = 0x466E;
+const FILE_MIME_TYPE: u32 = 0x4660;
+const FILE_DATA: u32 = 0x465C;
+
+/// TimestampScale when the Info element leaves it out: ticks are milliseconds.
+const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+/// Attachments larger than this are never read into memory.
+const MAX_ATTACHMENT: u64 = 16 * 1024 * 1024;
+/// CodecPrivate is only kept up to this size; decoder configuration records are small.
+const MAX_CODEC_PRIVATE: u64 = 64 * 1024;
+
+/// An element header: ID, payload size (None = unknown size) and payload offset.
+struct Header {
+    id: u32,
+    size: Option<u64>,
+    start: u64,
+}
+
+impl Header {
+    fn end(&self) -> Option<u64> {
+        self.size.and_then(|s| self.start.checked_add(s))
+    }
+}
+
+/// Read an element ID, keeping its length marker as Matroska IDs are written.
+fn read_id<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut first = [0u8; 1];
+    r.read_exact(&mut first)?;
+    let len = first[0].leading_zeros() as usize + 1;
+    if len > 4 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "bad EBML ID"));
+    }
+    let mut id = first[0] as u32;
+    for _ in 1..len {
+        r.read_exact(&mut first)?;
+        id = (id << 8) | first[0] as u32;
+    }
+    Ok(id)
+}
+
+/// Read an element size. All value bits set means "unknown size".
+fn read_size<R: Read>(r: &mut R) -> io::Result<Option<u64>> {
+    let mut byte = [0u8; 1];
+    r.read_exact(&mut byte)?;
+    let len = byte[0].leading_zeros() as usize + 1;
+    if len > 8 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "bad EBML size"));
+    }
+    let mut value = byte[0] as u64 & (0xFF >> len);
+    for _ in 1..len {
+        r.read_exact(&mut byte)?;
+        value = (value << 8) | byte[0] as u64;
+    }
+    let unknown = (1u64 << (7 * len)) - 1;
+    Ok((value != unknown).then_some(value))
+}
+
+fn read_header<R: Read + Seek>(r: &mut R) -> io::Result<Header> {
+    let id = read_id(r)?;
+    let size = read_size(r)?;
+    let start = r.stream_position()?;
+    Ok(Header { id, size, start })
+}
+
+fn read_uint<R: Read>(r: &mut R, size: u64) -> io::Result<u64> {
+    if size > 8 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "integer too long"));
+    }
+    let mut value = 0u64;
+    let mut byte = [0u8; 1];
+    for _ in 0..size {
+        r.read_exact(&mut byte)?;
+        value = (value << 8) | byte[0] as u64;
+    }
+    Ok(value)
+}
+
+fn read_float<R: Read>(r: &mut R, size: u64) -> io::Result<f64> {
+    match size {
+        4 => {
+            let mut buf = [0u8; 4];
+            r.read_exact(&mut buf)?;
+            Ok(f32::from_be_bytes(buf) as f64)
+        }
+        8 => {
+            let mut buf = [0u8; 8];
+            r.read_exact(&mut buf)?;
+            Ok(f64::from_be_bytes(buf))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "bad float size")),
+    }
+}
+
+fn read_string<R: Read>(r: &mut R, size: u64) -> io::Result<String> {
+    let mut buf = vec![0u8; size.min(4096) as usize];
+    r.read_exact(&mut buf)?;
+    let s = String::from_utf8_lossy(&buf);
+    Ok(s.trim_end_matches('\0').to_string())
+}
+
+/// Call `visit` for each child of `parent` with the reader at the child's payload.
+/// A child of unknown size ends the iteration.
+fn for_each_child<R: Read + Seek>(
+    r: &mut R,
+    parent: &Header,
+    mut visit: impl FnMut(&mut R, &Header) -> io::Result<()>,
+) -> io::Result<()> {
+    let Some(end) = parent.end() else { return Ok(()) };
+    let mut position = parent.start;
+    while position < end {
+        r.seek(SeekFrom::Start(position))?;
+        let child = read_header(r)?;
+        let Some(child_end) = child.end() else { break };
+        visit(r, &child)?;
+        position = child_end;
+    }
+    Ok(())
+}
+
+/// Call `visit` once for each top-level element of the first segment whose ID is in
+/// `wanted`.
+///
+/// Top-level elements are skipped by size. The SeekHead is used to jump past clusters
+/// to wanted elements stored after them; without one the walk stops at the first
+/// cluster rather than reading through the whole file. Each offset is read at most
+/// once, so SeekHead entries pointing back (or at themselves) cannot loop.
+fn walk_segment<R: Read + Seek>(
+    r: &mut R,
+    wanted: &[u32],
+    mut visit: impl FnMut(&mut R, &Header) -> io::Result<()>,
+) -> io::Result<()> {
+    let segment = read_segment_header(r)?;
+    let mut seen: Vec<u32> = Vec::new();
+    let mut seek_targets: Vec<u64> = Vec::new();
+    let mut visited: HashSet<u64> = HashSet::new();
+    let mut position = Some(segment.start);
+    while seen.len() < wanted.len() {
+        let in_segment = position.filter(|&p| segment.end().is_none_or(|end| p < end));
+        let next = match in_segment {
+            Some(p) => p,
+            None if !seek_targets.is_empty() => seek_targets.remove(0),
+            None => break,
+        };
+        if !visited.insert(next) {
+            position = None;
+            continue;
+        }
+        r.seek(SeekFrom::Start(next))?;
+        let Ok(child) = read_header(r) else {
+            position = None;
+            continue;
+        };
+        match child.id {
+            id if wanted.contains(&id) && !seen.contains(&id) => {
+                seen.push(id);
+                visit(r, &child)?;
+            }
+            SEEK_HEAD => {
+                let mut entries = Vec::new();
+                for_each_child(r, &child, |r, seek| {
+                    if seek.id == SEEK {
+                        entries.push(read_seek(r, seek)?);
+                    }
+                    Ok(())
+                })?;
+                seek_targets.extend(
+                    entries
+                        .into_iter()
+                        .filter(|(id, _)| wanted.contains(id))
+                        .filter_map(|(_, p)| segment.start.checked_add(p))
+                        .filter(|p| *p > next && !visited.contains(p)),
+                );
+            }
+            _ => {}
+        }
+        position = child.end().filter(|_| child.id != CLUSTER);
+    }
+    Ok(())
+}
+
+/// The header of the first segment, after the EBML header at the start of `r`.
+fn read_segment_header<R: Read + Seek>(r: &mut R) -> io::Result<Header> {
+    r.seek(SeekFrom::Start(0))?;
+    let ebml = read_header(r)?;
+    if ebml.id != EBML_HEADER {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not an EBML file"));
+    }
+    r.seek(SeekFrom::Start(ebml.end().unwrap_or(ebml.start)))?;
+    let segment = read_header(r)?;
+    if segment.id != SEGMENT {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "no Matroska segment"));
+    }
+    Ok(segment)
+}
+
+/// One SeekHead entry: the element ID and its segment-relative position.
+fn read_seek<R: Read + Seek>(r: &mut R, seek: &Header) -> io::Result<(u32, u64)> {
+    let (mut id, mut position) = (0, 0);
+    for_each_child(r, seek, |r, field| {
+        let size = field.size.unwrap_or(0);
+        match field.id {
+            SEEK_ID => id = read_uint(r, size)? as u32,
+            SEEK_POSITION => position = read_uint(r, size)?,
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok((id, position))
+}
+
+// ── Segment info and tracks ───────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackKind {
+    Video,
+    Audio,
+    Subtitle,
+    Other,
+}
+
+/// The parts of a TrackEntry udlna uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub number: u64,
+    pub kind: TrackKind,
+    /// Matroska codec ID, e.g. `V_MPEGH/ISO/HEVC`. See `codec_name`.
+    pub codec_id: String,
+    /// CodecPrivate: for H.264, HEVC and AV1 the decoder configuration record.
+    pub codec_private: Option<Vec<u8>>,
+    /// LanguageIETF (BCP 47) if present, else Language (ISO 639-2, default "eng").
+    pub language: String,
+    pub name: Option<String>,
+    pub default: bool,
+    /// DefaultDuration: nanoseconds per frame.
+    pub default_duration: Option<u64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Colour element: bits per colour channel and H.273 transfer characteristics.
+    pub colour_bits: Option<u32>,
+    pub transfer: Option<u8>,
+    pub channels: Option<u32>,
+    pub sample_rate: Option<f64>,
+    pub bit_depth: Option<u32>,
+}
+
+impl Default for Track {
+    fn default() -> Self {
+        Track {
+            number: 0,
+            kind: TrackKind::Other,
+            codec_id: String::new(),
+            codec_private: None,
+            language: "eng".to_string(),
+            name: None,
+            default: true,
+            default_duration: None,
+            width: None,
+            height: None,
+            colour_bits: None,
+            transfer: None,
+            channels: None,
+            sample_rate: None,
+            bit_depth: None,
+        }
+    }
+}
+
+/// Segment information and track headers of a Matroska or WebM file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MkvInfo {
+    /// Segment duration in seconds, if the muxer wrote one.
+    pub duration: Option<f64>,
+    pub tracks: Vec<Track>,
+    /// Chapters of the first edition, in file order.
+    pub chapters: Vec<Chapter>,
+}
+
+/// Read the segment Info, Tracks and Chapters of the file at `path`. None if it is
+/// not a Matroska file or declares no tracks.
+pub fn read_info(path: &Path) -> Option<MkvInfo> {
+    let mut r = BufReader::new(File::open(path).ok()?);
+    let mut info = MkvInfo::default();
+    walk_segment(&mut r, &[INFO, TRACKS, CHAPTERS], |r, element| match element.id {
+        INFO => {
+            info.duration = read_duration(r, element)?;
+            Ok(())
+        }
+        CHAPTERS => {
+            info.chapters = read_chapters(r, element)?;
+            Ok(())
+        }
+        _ => for_each_child(r, element, |r, entry| {
+            if entry.id == TRACK_ENTRY {
+                info.tracks.push(read_track(r, entry)?);
+            }
+            Ok(())
+        }),
+    })
+    .ok()?;
+    (!info.tracks.is_empty()).then_some(info)
+}
+
+/// Duration from the Info element, converted from TimestampScale ticks to seconds.
+fn read_duration<R: Read + Seek>(r: &mut R, info: &Header) -> io::Result<Option<f64>> {
+    let (mut scale, mut ticks) = (DEFAULT_TIMESTAMP_SCALE, None);
+    for_each_child(r, info, |r, field| {
+        let size = field.size.unwrap_or(0);
+        match field.id {
+            TIMESTAMP_SCALE => scale = read_uint(r, size)?,
+            DURATION => ticks = Some(read_float(r, size)?),
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(ticks
+        .map(|t| t * scale as f64 / 1e9)
+        .filter(|secs| secs.is_finite() && *secs > 0.0))
+}
+
+fn read_track<R: Read + Seek>(r: &mut R, entry: &Header) -> io::Result<Track> {
+    let mut track = Track::default();
+    let mut ietf = None;
+    for_each_child(r, entry, |r, field| {
+        let size = field.size.unwrap_or(0);
+        match field.id {
+            TRACK_NUMBER => track.number = read_uint(r, size)?,
+            TRACK_TYPE => {
+                track.kind = match read_uint(r, size)? {
+                    1 => TrackKind::Video,
+                    2 => TrackKind::Audio,
+                    17 => TrackKind::Subtitle,
+                    _ => TrackKind::Other,
+                }
+            }
+            CODEC_ID => track.codec_id = read_string(r, size)?,
+            CODEC_PRIVATE if size <= MAX_CODEC_PRIVATE => {
+                let mut data = vec![0u8; size as usize];
+                r.read_exact(&mut data)?;
+                track.codec_private = Some(data);
+            }
+            DEFAULT_DURATION => track.default_duration = Some(read_uint(r, size)?).filter(|&d| d > 0),
+            TRACK_NAME => track.name = Some(read_string(r, size)?).filter(|n| !n.is_empty()),
+            LANGUAGE => track.language = read_string(r, size)?,
+            LANGUAGE_IETF => ietf = Some(read_string(r, size)?).filter(|l| !l.is_empty()),
+            FLAG_DEFAULT => track.default = read_uint(r, size)? != 0,
+            VIDEO => for_each_child(r, field, |r, video| {
+                let size = video.size.unwrap_or(0);
+                match video.id {
+                    PIXEL_WIDTH => track.width = Some(read_uint(r, size)? as u32),
+                    PIXEL_HEIGHT => track.height = Some(read_uint(r, size)? as u32),
+                    COLOUR => for_each_child(r, video, |r, colour| {
+                        let size = colour.size.unwrap_or(0);
+                        match colour.id {
+                            BITS_PER_CHANNEL => track.colour_bits = Some(read_uint(r, size)? as u32).filter(|&b| b > 0),
+                            TRANSFER_CHARACTERISTICS => track.transfer = u8::try_from(read_uint(r, size)?).ok(),
+                            _ => {}
+                        }
+                        Ok(())
+                    })?,
+                    _ => {}
+                }
+                Ok(())
+            })?,
+            AUDIO => for_each_child(r, field, |r, audio| {
+                let size = audio.size.unwrap_or(0);
+                match audio.id {
+                    SAMPLING_FREQUENCY => track.sample_rate = Some(read_float(r, size)?),
+                    CHANNELS => track.channels = Some(read_uint(r, size)? as u32),
+                    BIT_DEPTH => track.bit_depth = Some(read_uint(r, size)? as u32),
+                    _ => {}
+                }
+                Ok(())
+            })?,
+            _ => {}
+        }
+        Ok(())
+    })?;
+    if let Some(language) = ietf {
+        track.language = language;
+    }
+    Ok(track)
+}
+
+/// Short codec name for a Matroska codec ID (`h264`, `hevc`, `aac`, ...). Unknown IDs
+/// are returned as they are.
+pub fn codec_name(codec_id: &str) -> &str {
+    match codec_id {
+        "V_MPEG4/ISO/AVC" => "h264",
+        "V_MPEGH/ISO/HEVC" => "hevc",
+        "V_AV1" => "av1",
+        "V_VP9" => "vp9",
+        "V_VP8" => "vp8",
+        "V_MPEG1" | "V_MPEG2" => "mpeg2video",
+        "V_THEORA" => "theora",
+        "A_AC3" => "ac3",
+        "A_EAC3" => "eac3",
+        "A_TRUEHD" => "truehd",
+        "A_OPUS" => "opus",
+        "A_VORBIS" => "vorbis",
+        "A_FLAC" => "flac",
+        "A_MPEG/L3" => "mp3",
+        "A_MPEG/L2" => "mp2",
+        id if id.starts_with("V_MPEG4/ISO/") => "mpeg4",
+        id if id.starts_with("A_AAC") => "aac",
+        id if id.starts_with("A_DTS") => "dts",
+        id if id.starts_with("A_PCM/") => "pcm",
+        id => id,
+    }
+}
+
+// ── Chapters ──────────────────────────────────────────────────────────────────
+
+/// The visible top-level chapters of the first edition. Nested chapters and further
+/// editions (alternative cuts) are left out.
+fn read_chapters<R: Read + Seek>(r: &mut R, chapters: &Header) -> io::Result<Vec<Chapter>> {
+    let mut list = Vec::new();
+    let mut editions = 0;
+    for_each_child(r, chapters, |r, edition| {
+        if edition.id != EDITION_ENTRY || editions > 0 {
+            return Ok(());
+        }
+        editions += 1;
+        for_each_child(r, edition, |r, atom| {
+            if atom.id != CHAPTER_ATOM {
+                return Ok(());
+            }
+            let (mut start, mut title, mut visible) = (None, None, true);
+            for_each_child(r, atom, |r, field| {
+                let size = field.size.unwrap_or(0);
+                match field.id {
+                    CHAPTER_TIME_START => start = Some(read_uint(r, size)?),
+                    CHAPTER_FLAG_HIDDEN => visible &= read_uint(r, size)? == 0,
+                    CHAPTER_FLAG_ENABLED => visible &= read_uint(r, size)? != 0,
+                    // The first display string; later ones are translations.
+                    CHAPTER_DISPLAY if title.is_none() => for_each_child(r, field, |r, display| {
+                        if display.id == CHAP_STRING && title.is_none() {
+                            title = Some(read_string(r, display.size.unwrap_or(0))?).filter(|t| !t.is_empty());
+                        }
+                        Ok(())
+                    })?,
+                    _ => {}
+                }
+                Ok(())
+            })?;
+            if let Some(start) = start.filter(|_| visible) {
+                list.push(Chapter { start: start as f64 / 1e9, title });
+            }
+            Ok(())
+        })
+    })?;
+    Ok(list)
+}
+
+// ── Attachments ───────────────────────────────────────────────────────────────
+
+/// A file attached to a Matroska segment (fonts, cover images, ...).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub name: String,
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+/// Find the segment's cover image. Follows the Matroska attachment convention:
+/// a file named `cover.*` wins, then any other JPEG or PNG attachment.
+pub fn cover_attachment(path: &Path) -> Option<Attachment> {
+    let mut r = BufReader::new(File::open(path).ok()?);
+    let mut images = Vec::new();
+    walk_segment(&mut r, &[ATTACHMENTS], |r, attachments| {
+        images = read_attached_files(r, attachments, |mime| mime == "image/jpeg" || mime == "image/png")?;
+        Ok(())
+    })
+    .ok()?;
+    let cover = images
+        .iter()
+        .position(|a| a.name.to_ascii_lowercase().starts_with("cover."))
+        .unwrap_or(0);
+    images.into_iter().nth(cover)
+}
+
+/// Read the attachments whose MIME type `want` accepts.
+fn read_attached_files<R: Read + Seek>(
+    r: &mut R,
+    attachments: &Header,
+    want: impl Fn(&str) -> bool,
+) -> io::Result<Vec<Attachment>> {
+    let mut files = Vec::new();
+    for_each_child(r, attachments, |r, file| {
+        if file.id != ATTACHED_FILE {
+            return Ok(());
+        }
+        let (mut name, mut mime, mut data_at) = (String::new(), String::new(), None);
+        for_each_child(r, file, |r, field| {
+            let size = field.size.unwrap_or(0);
+            match field.id {
+                FILE_NAME => name = read_string(r, size)?,
+                FILE_MIME_TYPE => mime = read_string(r, size)?,
+                FILE_DATA => data_at = Some((field.start, size)),
+                _ => {}
+            }
+            Ok(())
+        })?;
+        if let Some((start, size)) = data_at.filter(|&(_, size)| size <= MAX_ATTACHMENT && want(&mime)) {
+            r.seek(SeekFrom::Start(start))?;
+            let mut data = vec![0u8; size as usize];
+            r.read_exact(&mut data)?;
+            files.push(Attachment { name, mime, data });
+        }
+        Ok(())
+    })?;
+    Ok(files)
+}
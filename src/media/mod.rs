@@ -1,4 +1,23 @@
+pub mod art;
+pub mod asf;
+pub mod avi;
+pub mod dlna;
+pub mod episode;
+pub mod filter;
+pub mod index;
+pub mod isobmff;
 pub mod library;
 pub mod metadata;
 pub mod mime;
+pub mod mkv;
+pub mod mp3;
+pub mod nfo;
+pub mod ps;
 pub mod scanner;
+pub mod seek;
+pub mod sniff;
+pub mod sps;
+pub mod subtitle;
+pub mod title;
+pub mod ts;
+pub mod watcher;
@@ -0,0 +1,253 @@
+//! MPEG audio duration and average bitrate: from the Xing/Info or VBRI header of the
+//! first frame, else by walking frame headers.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes of audio walked frame by frame when there is no Xing/VBRI header. Longer
+/// files are extrapolated from the frames seen.
+const SCAN_LIMIT: u64 = 8 * 1024 * 1024;
+/// Bytes searched for the first frame after any ID3v2 tag.
+const SYNC_SEARCH: usize = 64 * 1024;
+/// Bytes read to find the first frame and its Xing/VBRI header: the sync search plus
+/// the largest frame (layer II at 384 kbit/s and 32 kHz, 1.7 KiB) and its successor.
+const HEAD_LEN: u64 = SYNC_SEARCH as u64 + 4096;
+const ID3V1_LEN: u64 = 128;
+
+/// Duration and bitrate of an MPEG audio stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mp3Info {
+    pub duration: f64,
+    /// Average bitrate in bits per second.
+    pub bitrate: u32,
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// How the duration was found.
+    pub source: DurationSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationSource {
+    /// Frame count from a Xing/Info or VBRI header.
+    Header,
+    /// Every frame header was read.
+    Scan,
+    /// The first `SCAN_LIMIT` bytes were read and the rest extrapolated.
+    Estimate,
+}
+
+/// A parsed 4-byte frame header.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    mpeg1: bool,
+    sample_rate: u32,
+    samples: u32,
+    len: usize,
+    mono: bool,
+}
+
+const BITRATES_V1: [[u32; 16]; 3] = [
+    [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448, 0],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384, 0],
+    [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0],
+];
+const BITRATES_V2: [[u32; 16]; 3] = [
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256, 0],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0],
+];
+
+fn parse_frame(h: &[u8]) -> Option<Frame> {
+    if h.len() < 4 || h[0] != 0xFF || h[1] & 0xE0 != 0xE0 {
+        return None;
+    }
+    let version = (h[1] >> 3) & 0x03; // 0 = MPEG 2.5, 2 = MPEG 2, 3 = MPEG 1
+    let layer = match (h[1] >> 1) & 0x03 {
+        3 => 1,
+        2 => 2,
+        1 => 3,
+        _ => return None,
+    };
+    let bitrate_index = (h[2] >> 4) as usize;
+    let rate_index = ((h[2] >> 2) & 0x03) as usize;
+    if version == 1 || rate_index == 3 {
+        return None;
+    }
+    let mpeg1 = version == 3;
+    let table = if mpeg1 { &BITRATES_V1 } else { &BITRATES_V2 };
+    let bitrate = table[layer - 1][bitrate_index] * 1000;
+    if bitrate == 0 {
+        return None; // free format or invalid
+    }
+    let sample_rate = match version {
+        3 => [44100, 48000, 32000][rate_index],
+        2 => [22050, 24000, 16000][rate_index],
+        _ => [11025, 12000, 8000][rate_index],
+    };
+    let padding = ((h[2] >> 1) & 0x01) as u32;
+    let (samples, len) = match layer {
+        1 => (384, (12 * bitrate / sample_rate + padding) * 4),
+        3 if !mpeg1 => (576, 72 * bitrate / sample_rate + padding),
+        _ => (1152, 144 * bitrate / sample_rate + padding),
+    };
+    Some(Frame { mpeg1, sample_rate, samples, len: len as usize, mono: h[3] >> 6 == 3 })
+}
+
+/// Read the duration and average bitrate of the MPEG audio file at `path`.
+pub fn read_info(path: &Path) -> Option<Mp3Info> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut start = skip_id3v2(&mut file)?;
+    let mut end = len;
+    if len >= ID3V1_LEN {
+        let mut tag = [0u8; 3];
+        file.seek(SeekFrom::Start(len - ID3V1_LEN)).ok()?;
+        if file.read_exact(&mut tag).is_ok() && &tag == b"TAG" {
+            end = len - ID3V1_LEN;
+        }
+    }
+
+    let mut head = Vec::new();
+    file.seek(SeekFrom::Start(start)).ok()?;
+    (&mut file).take(HEAD_LEN.min(end.saturating_sub(start))).read_to_end(&mut head).ok()?;
+    let first_at = find_sync(&head[..head.len().min(SYNC_SEARCH)])?;
+    start += first_at as u64;
+    let head = &head[first_at..];
+    let first = parse_frame(head)?;
+    let channels = if first.mono { 1 } else { 2 };
+    let audio_len = end.saturating_sub(start);
+
+    if let Some((frames, bytes)) = vbr_header(head, &first) {
+        let samples = frames as u64 * first.samples as u64;
+        let samples = samples.saturating_sub(lame_gapless(head, &first).unwrap_or(0));
+        let duration = samples as f64 / first.sample_rate as f64;
+        // Some encoders leave the byte count out; the file size is close enough then.
+        let bytes = bytes.filter(|&b| b > 0).map_or(audio_len, u64::from);
+        return (duration > 0.0).then(|| Mp3Info {
+            duration,
+            bitrate: (bytes as f64 * 8.0 / duration).round() as u32,
+            sample_rate: first.sample_rate,
+            channels,
+            source: DurationSource::Header,
+        });
+    }
+
+    // No header: walk the frames of the first `SCAN_LIMIT` bytes.
+    let mut buf = Vec::new();
+    file.seek(SeekFrom::Start(start)).ok()?;
+    (&mut file).take(SCAN_LIMIT.min(audio_len)).read_to_end(&mut buf).ok()?;
+    let buf = &buf[..];
+    let (samples, bytes) = scan_frames(buf, first.sample_rate);
+    if samples == 0 || bytes == 0 {
+        return None;
+    }
+    let scanned = samples as f64 / first.sample_rate as f64;
+    let bitrate = bytes as f64 * 8.0 / scanned;
+    let complete = (buf.len() as u64) >= audio_len;
+    let duration = if complete { scanned } else { audio_len as f64 * 8.0 / bitrate };
+    Some(Mp3Info {
+        duration,
+        bitrate: bitrate.round() as u32,
+        sample_rate: first.sample_rate,
+        channels,
+        source: if complete { DurationSource::Scan } else { DurationSource::Estimate },
+    })
+}
+
+/// Position after any ID3v2 tags at the start of the file.
+fn skip_id3v2(file: &mut File) -> Option<u64> {
+    let mut position = 0;
+    loop {
+        let mut header = [0u8; 10];
+        file.seek(SeekFrom::Start(position)).ok()?;
+        if file.read_exact(&mut header).is_err() || &header[..3] != b"ID3" {
+            return Some(position);
+        }
+        let size = header[6..10].iter().fold(0u64, |size, &b| size << 7 | (b & 0x7F) as u64);
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        position += 10 + size + footer;
+    }
+}
+
+/// Offset of the first frame header in `buf` that is followed by another valid one
+/// (or by the end of the buffer), to avoid false syncs in junk data.
+fn find_sync(buf: &[u8]) -> Option<usize> {
+    (0..buf.len().saturating_sub(3)).find(|&i| {
+        let Some(frame) = parse_frame(&buf[i..]) else { return false };
+        match buf.get(i + frame.len..) {
+            Some(next) if next.len() >= 4 => parse_frame(next).is_some_and(|n| n.sample_rate == frame.sample_rate),
+            _ => true,
+        }
+    })
+}
+
+/// Offset of the Xing/Info/VBRI tag inside the first frame, after the side information.
+fn side_info_end(frame: &Frame) -> usize {
+    4 + match (frame.mpeg1, frame.mono) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    }
+}
+
+/// Frame count and (if recorded) byte count from a Xing/Info or VBRI header.
+fn vbr_header(buf: &[u8], first: &Frame) -> Option<(u32, Option<u32>)> {
+    let be32 = |at: usize| buf.get(at..at + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let xing = side_info_end(first);
+    if matches!(buf.get(xing..xing + 4), Some(b"Xing" | b"Info")) {
+        let flags = be32(xing + 4)?;
+        if flags & 0x01 == 0 {
+            return None;
+        }
+        let frames = be32(xing + 8)?;
+        let bytes = if flags & 0x02 != 0 { be32(xing + 12) } else { None };
+        return (frames > 0).then_some((frames, bytes));
+    }
+    // VBRI sits at a fixed offset: header plus 32 bytes.
+    if buf.get(36..40) == Some(b"VBRI") {
+        let (bytes, frames) = (be32(36 + 10)?, be32(36 + 14)?);
+        return (frames > 0).then_some((frames, Some(bytes)));
+    }
+    None
+}
+
+/// Encoder delay plus padding in samples, from the LAME extension of a Xing header
+/// written with all four optional fields.
+fn lame_gapless(buf: &[u8], first: &Frame) -> Option<u64> {
+    let xing = side_info_end(first);
+    let flags = u32::from_be_bytes(buf.get(xing + 4..xing + 8)?.try_into().ok()?);
+    if flags & 0x0F != 0x0F {
+        return None;
+    }
+    let lame = xing + 8 + 4 + 4 + 100 + 4;
+    if buf.get(lame..lame + 4) != Some(b"LAME") {
+        return None;
+    }
+    let b = buf.get(lame + 21..lame + 24)?;
+    let delay = (b[0] as u64) << 4 | (b[1] as u64) >> 4;
+    let padding = (b[1] as u64 & 0x0F) << 8 | b[2] as u64;
+    Some(delay + padding)
+}
+
+/// Walk the frames of `buf`, resynchronizing over junk. Returns the samples and bytes
+/// of the frames that were read in full.
+fn scan_frames(buf: &[u8], sample_rate: u32) -> (u64, u64) {
+    let (mut samples, mut bytes) = (0u64, 0u64);
+    let mut position = 0;
+    while position + 4 <= buf.len() {
+        match parse_frame(&buf[position..]).filter(|f| f.sample_rate == sample_rate) {
+            Some(frame) if position + frame.len <= buf.len() => {
+                samples += frame.samples as u64;
+                bytes += frame.len as u64;
+                position += frame.len;
+            }
+            Some(_) => break,
+            None => match find_sync(&buf[position + 1..]) {
+                Some(skip) => position += 1 + skip,
+                None => break,
+            },
+        }
+    }
+    (samples, bytes)
+}
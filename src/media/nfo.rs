@@ -0,0 +1,164 @@
+//! Kodi `.nfo` sidecars: the XML movie and episode descriptions media managers
+//! (Kodi, Radarr, Sonarr, tinyMediaManager) write next to video files.
+
+use std::path::{Path, PathBuf};
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+
+use crate::media::mime::{classify, MediaKind};
+
+/// Sidecars larger than this are not descriptions.
+const MAX_NFO_LEN: u64 = 1024 * 1024;
+
+/// Root elements of the descriptions udlna reads.
+const ROOTS: &[&[u8]] = &[b"movie", b"episodedetails", b"musicvideo"];
+
+/// What a `.nfo` sidecar says about a video. Kept apart from the fields read from the
+/// file itself, so a sidecar that is edited or deleted never leaves stale values.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Nfo {
+    pub title: Option<String>,
+    /// One-line summary: `<outline>`, else `<tagline>`.
+    pub outline: Option<String>,
+    pub plot: Option<String>,
+    /// `<year>`, else the year of `<premiered>` or `<aired>`.
+    pub year: Option<i32>,
+    pub genres: Vec<String>,
+    /// Content rating (certification) from `<mpaa>`, e.g. "PG-13". This is what
+    /// `upnp:rating` carries, not the review score of `<rating>`.
+    pub rating: Option<String>,
+    pub directors: Vec<String>,
+    /// Cast names in billing order.
+    pub actors: Vec<String>,
+}
+
+/// Whether `path` is named like a sidecar (`.nfo`, any case).
+pub fn is_sidecar(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("nfo"))
+}
+
+/// The sidecar describing `media`: `<basename>.nfo`, else the `movie.nfo` of a
+/// one-movie folder. Trailers and extras next to the movie do not get its `movie.nfo`:
+/// it only applies to a video named like the folder or alone in it.
+pub fn sidecar_path(media: &Path) -> Option<PathBuf> {
+    let own = media.with_extension("nfo");
+    if own.is_file() {
+        return Some(own);
+    }
+    let dir = media.parent()?;
+    let folder = dir.join("movie.nfo");
+    (folder.is_file() && is_folder_movie(media, dir)).then_some(folder)
+}
+
+fn is_folder_movie(media: &Path, dir: &Path) -> bool {
+    let named_like_folder = match (media.file_stem(), dir.file_name()) {
+        (Some(stem), Some(name)) => stem.eq_ignore_ascii_case(name),
+        _ => false,
+    };
+    named_like_folder
+        || std::fs::read_dir(dir).is_ok_and(|entries| {
+            let videos = entries.flatten().filter(|e| matches!(classify(&e.path()), Some((MediaKind::Video, _))));
+            videos.take(2).count() == 1
+        })
+}
+
+/// Read and parse the sidecar of `media`, if it has one udlna understands.
+pub fn read_sidecar(media: &Path) -> Option<Nfo> {
+    let path = sidecar_path(media)?;
+    if std::fs::metadata(&path).ok()?.len() > MAX_NFO_LEN {
+        return None;
+    }
+    let bytes = std::fs::read(&path).ok()?;
+    let nfo = parse(&String::from_utf8_lossy(&bytes));
+    if nfo.is_none() {
+        tracing::debug!("Ignoring {}: no movie or episode description", path.display());
+    }
+    nfo
+}
+
+/// Parse a Kodi description. None if the text has no `<movie>`, `<episodedetails>`
+/// or `<musicvideo>` root (Kodi also accepts sidecars holding just a scraper URL).
+pub fn parse(text: &str) -> Option<Nfo> {
+    let mut reader = Reader::from_str(text);
+    let mut nfo = Nfo::default();
+    let mut found = false;
+    // Element names from the root down, and the text of the innermost element.
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    let mut value = String::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = e.local_name().as_ref().to_ascii_lowercase();
+                if path.is_empty() && !ROOTS.contains(&name.as_slice()) {
+                    return None;
+                }
+                found = true;
+                path.push(name);
+                value.clear();
+            }
+            Ok(Event::Text(t)) => value.push_str(&t.xml_content().ok()?),
+            Ok(Event::CData(t)) => value.push_str(&t.decode().ok()?),
+            Ok(Event::GeneralRef(r)) => {
+                if let Ok(Some(c)) = r.resolve_char_ref() {
+                    value.push(c);
+                } else if let Some(s) = quick_xml::escape::resolve_predefined_entity(&r.decode().ok()?) {
+                    value.push_str(s);
+                }
+            }
+            Ok(Event::End(_)) => {
+                let text = value.trim();
+                if !text.is_empty() {
+                    let names: Vec<&[u8]> = path.iter().map(Vec::as_slice).collect();
+                    apply(&mut nfo, &names[1..], text);
+                }
+                value.clear();
+                path.pop();
+                if path.is_empty() && found {
+                    break;
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => return None,
+            _ => {}
+        }
+    }
+    found.then_some(nfo)
+}
+
+/// Store the text of the element at `path` (below the root).
+fn apply(nfo: &mut Nfo, path: &[&[u8]], text: &str) {
+    let text = text.to_string();
+    match path {
+        [b"title"] => nfo.title = Some(text),
+        [b"outline"] => nfo.outline = Some(text),
+        [b"tagline"] => nfo.outline = nfo.outline.take().or(Some(text)),
+        [b"plot"] => nfo.plot = Some(text),
+        [b"year"] => nfo.year = text.parse().ok().or(nfo.year),
+        [b"premiered" | b"aired"] => nfo.year = nfo.year.or(text.get(..4).and_then(|y| y.parse().ok())),
+        [b"genre"] => push_distinct(&mut nfo.genres, text),
+        [b"mpaa"] => nfo.rating = certification(&text),
+        [b"director"] => push_distinct(&mut nfo.directors, text),
+        [b"actor", b"name"] => push_distinct(&mut nfo.actors, text),
+        _ => {}
+    }
+}
+
+/// Add `value` unless present; Kodi repeats genres and people across scrapers.
+fn push_distinct(list: &mut Vec<String>, value: String) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+/// "Rated PG-13" and "US:PG-13" → "PG-13".
+fn certification(mpaa: &str) -> Option<String> {
+    let rating = mpaa.strip_prefix("Rated ").unwrap_or(mpaa);
+    let rating = match rating.split_once(':') {
+        Some((country, rating)) if country.len() <= 3 => rating,
+        _ => rating,
+    };
+    let rating = rating.trim();
+    (!rating.is_empty()).then(|| rating.to_string())
+}
@@ -0,0 +1,160 @@
+//! MPEG program stream reader (.mpg, .mpeg): SCR-based duration, the picture size
+//! and MPEG-1/MPEG-2 flavour of the video, and the audio streams.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::media::library::AudioTrack;
+use crate::media::sps;
+
+const PACK_START: u8 = 0xBA;
+const SYSTEM_HEADER: u8 = 0xBB;
+const END_CODE: u8 = 0xB9;
+const PRIVATE_STREAM_1: u8 = 0xBD;
+/// Sequence extension start code; only MPEG-2 video has one.
+const EXTENSION_START: u8 = 0xB5;
+const SEQUENCE_EXTENSION_ID: u8 = 1;
+
+/// Bytes read from each end of the file. Packs (each with an SCR) are at most a few
+/// KiB apart, and the sequence header repeats at every GOP.
+const HEAD_LEN: u64 = 1024 * 1024;
+const TAIL_LEN: u64 = 256 * 1024;
+/// Bytes searched for the first pack header; some files start with junk.
+const SYNC_SEARCH: usize = 64 * 1024;
+
+/// SCR ticks per second, and the span of its 33-bit counter.
+const SCR_HZ: f64 = 90_000.0;
+const SCR_WRAP: u64 = 1 << 33;
+
+/// What udlna reads from a program stream.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PsInfo {
+    /// Seconds between the first and the last pack's SCR.
+    pub duration: Option<f64>,
+    /// "mpeg1video" or "mpeg2video".
+    pub video_codec: Option<&'static str>,
+    /// Picture size from the first sequence header.
+    pub size: Option<(u32, u32)>,
+    pub audio: Vec<AudioTrack>,
+}
+
+/// Read the duration, video format and audio streams of the program stream at
+/// `path`. None if no pack header is found near the start of the file.
+pub fn read_info(path: &Path) -> Option<PsInfo> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut head = Vec::new();
+    (&mut file).take(HEAD_LEN).read_to_end(&mut head).ok()?;
+    let mut tail = Vec::new();
+    if len > HEAD_LEN {
+        file.seek(SeekFrom::Start(len.saturating_sub(TAIL_LEN).max(HEAD_LEN))).ok()?;
+        file.take(TAIL_LEN).read_to_end(&mut tail).ok()?;
+    }
+
+    let start = start_codes(&head[..head.len().min(SYNC_SEARCH)]).find(|&at| scr(&head[at..]).is_some())?;
+    let first = scr(&head[start..])?;
+    let end = if tail.is_empty() { &head } else { &tail };
+    let last = start_codes(end).filter_map(|at| scr(&end[at..])).last().unwrap_or(first);
+
+    let ticks = (last + SCR_WRAP - first) % SCR_WRAP;
+    let mut info = PsInfo {
+        duration: (ticks > 0).then(|| ticks as f64 / SCR_HZ),
+        audio: audio_streams(&head[start..]),
+        ..Default::default()
+    };
+    // The sequence header is a dozen bytes and rarely straddles two packets, so it is
+    // searched for in the multiplexed bytes directly.
+    info.size = sps::find_mpeg2_size(&head);
+    if info.size.is_some() {
+        let extension = head
+            .windows(5)
+            .any(|w| w[..4] == [0, 0, 1, EXTENSION_START] && w[4] >> 4 == SEQUENCE_EXTENSION_ID);
+        info.video_codec = Some(if extension { "mpeg2video" } else { "mpeg1video" });
+    }
+    Some(info)
+}
+
+/// Offsets of the pack start codes (`00 00 01 BA`) in `data`.
+fn start_codes(data: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    data.windows(4).enumerate().filter(|(_, w)| *w == [0, 0, 1, PACK_START]).map(|(at, _)| at)
+}
+
+/// SCR base (90 kHz) of the pack header at the start of `pack`, for MPEG-2 and MPEG-1
+/// layouts. None when the marker bits do not check out.
+fn scr(pack: &[u8]) -> Option<u64> {
+    let b = pack.get(4..9)?;
+    let b: [u64; 5] = [b[0] as u64, b[1] as u64, b[2] as u64, b[3] as u64, b[4] as u64];
+    if b[0] >> 6 == 0b01 && b[0] & b[2] & b[4] & 0x04 != 0 {
+        Some((b[0] >> 3 & 0x07) << 30 | (b[0] & 0x03) << 28 | b[1] << 20 | (b[2] >> 3) << 15 | (b[2] & 0x03) << 13
+            | b[3] << 5 | b[4] >> 3)
+    } else if b[0] >> 4 == 0b0010 && b[0] & b[2] & b[4] & 0x01 != 0 {
+        Some((b[0] >> 1 & 0x07) << 30 | b[1] << 22 | (b[2] >> 1) << 15 | b[3] << 7 | b[4] >> 1)
+    } else {
+        None
+    }
+}
+
+/// Length of the pack header at the start of `pack`: MPEG-2 headers end in stuffing.
+fn pack_len(pack: &[u8]) -> Option<usize> {
+    let mpeg2 = pack.get(4)? >> 6 == 0b01;
+    Some(if mpeg2 { 14 + (*pack.get(13)? & 0x07) as usize } else { 12 })
+}
+
+/// The audio streams of the packets in `data`, in order of first appearance. MPEG
+/// audio has its own stream IDs; AC-3, DTS and LPCM are told apart by the sub-stream
+/// ID that leads the payload of private stream 1.
+fn audio_streams(data: &[u8]) -> Vec<AudioTrack> {
+    let mut seen = Vec::new();
+    let mut audio = Vec::new();
+    let mut at = 0;
+    while at + 6 <= data.len() {
+        if data[at..at + 3] != [0, 0, 1] {
+            // Lost sync: skip to the next pack.
+            match start_codes(&data[at + 1..]).next() {
+                Some(next) => at += 1 + next,
+                None => break,
+            }
+            continue;
+        }
+        let id = data[at + 3];
+        let len = match id {
+            PACK_START => match pack_len(&data[at..]) {
+                Some(len) => len,
+                None => break,
+            },
+            END_CODE => 4,
+            SYSTEM_HEADER.. => 6 + u16::from_be_bytes([data[at + 4], data[at + 5]]) as usize,
+            _ => 1,
+        };
+        let key = match id {
+            0xC0..=0xDF => Some((id, 0)),
+            PRIVATE_STREAM_1 => private_substream(&data[at..]).map(|sub| (id, sub)),
+            _ => None,
+        };
+        if let Some(key) = key.filter(|k| !seen.contains(k)) {
+            let codec = match key {
+                (PRIVATE_STREAM_1, 0x80..=0x87) => Some("ac3"),
+                (PRIVATE_STREAM_1, 0x88..=0x8F) => Some("dts"),
+                (PRIVATE_STREAM_1, 0xA0..=0xA7) => Some("pcm"),
+                (PRIVATE_STREAM_1, _) => None,
+                _ => Some("mp2"),
+            };
+            seen.push(key);
+            if let Some(codec) = codec {
+                audio.push(AudioTrack { codec: codec.to_string(), language: None, channels: None });
+            }
+        }
+        at += len;
+    }
+    audio
+}
+
+/// Sub-stream ID of a private stream 1 packet: the first payload byte after the
+/// MPEG-2 PES header.
+fn private_substream(packet: &[u8]) -> Option<u8> {
+    if packet.get(6)? >> 6 != 0b10 {
+        return None;
+    }
+    packet.get(9 + *packet.get(8)? as usize).copied()
+}
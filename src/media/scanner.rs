@@ -1,95 +1,593 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
 use std::time::Instant;
 use uuid::Uuid;
 use walkdir::WalkDir;
 
-use crate::media::library::{MediaItem, MediaLibrary};
+use crate::media::{art, nfo};
+use crate::media::filter::{Exclusion, IgnoreStack, ScanFilter, SymlinkPolicy};
+use crate::media::index::MetadataIndex;
+use crate::media::library::{MediaItem, MediaLibrary, NamedLibrary};
 use crate::media::metadata::{extract_metadata, media_item_id, MACHINE_NAMESPACE};
-use crate::media::mime::{classify, MediaKind};
+use crate::media::mime::{classify, kind_for_mime, MediaKind};
+use crate::media::sniff::classify_file;
+use crate::media::subtitle::SidecarIndex;
+use crate::media::title::TitleNormalizer;
 
-/// Statistics collected during scanning for the startup summary line.
-/// Reserved for Phase 3+ callers that need scan statistics.
+/// Statistics collected during scanning for the summary line.
+#[derive(Debug, Clone, Default)]
 pub struct ScanStats {
     pub total: usize,
     pub video: usize,
     pub audio: usize,
     pub image: usize,
+    /// Files and directories skipped by the `ScanFilter` (a pruned directory counts once).
+    pub excluded: usize,
     pub elapsed_secs: f64,
 }
 
+/// Files handed to the worker pool at a time. Also the publication granularity of
+/// `scan_into`: small enough that a background scan shows progress quickly, large
+/// enough that the write lock and UpdateID bumps stay infrequent.
+const BATCH_SIZE: usize = 256;
+
+/// Options for a full scan, built from `Config` in main.rs.
+/// `Default` is a plain scan with no persistent index.
+#[derive(Debug, Clone, Default)]
+pub struct ScanConfig {
+    /// Where the persistent metadata index lives. None disables the index.
+    pub index_path: Option<PathBuf>,
+    /// Ignore the existing index (probe every file) and write a fresh one.
+    pub rebuild_index: bool,
+    /// Metadata extraction workers per root. None picks a default from the
+    /// root's storage type (see `default_threads`).
+    pub threads: Option<usize>,
+    /// Include/exclude globs, hidden-file skipping and marker files.
+    pub filter: ScanFilter,
+    /// Which symlinks are followed (and served).
+    pub symlinks: SymlinkPolicy,
+    /// Do not descend into directories on a different filesystem than their root.
+    pub same_filesystem: bool,
+    /// Maximum directory depth below a root; 1 = only files directly in the root.
+    pub max_depth: Option<usize>,
+    /// Named libraries as configured. Their paths must also be among the scanned paths.
+    pub libraries: Vec<NamedLibrary>,
+    /// Canonical roots limited to some media kinds (see `kind_limits`).
+    /// Roots not listed accept every kind.
+    pub kind_limits: Vec<(PathBuf, Vec<MediaKind>)>,
+    /// How file names become titles; handed on to the library for Browse.
+    pub titles: TitleNormalizer,
+}
+
+impl ScanConfig {
+    /// The kinds allowed under the canonical root `root`, or None for no limit.
+    pub fn kind_limit(&self, root: &Path) -> Option<&[MediaKind]> {
+        self.kind_limits
+            .iter()
+            .find(|(r, _)| r == root)
+            .map(|(_, kinds)| kinds.as_slice())
+    }
+}
+
+/// The innermost of the canonical `roots` containing `path`. A library nested inside
+/// another scan root owns its files: they get its kind limit, not the outer root's.
+pub fn innermost_root<'a>(roots: &'a [PathBuf], path: &Path) -> Option<&'a PathBuf> {
+    roots
+        .iter()
+        .filter(|r| path.starts_with(r))
+        .max_by_key(|r| r.components().count())
+}
+
+/// Work out which roots are limited to some media kinds. A root is limited only if it
+/// is not one of the unrestricted `default_paths` and every named library containing
+/// it restricts kinds; it then accepts the union of those libraries' kinds.
+pub fn kind_limits(default_paths: &[PathBuf], libraries: &[NamedLibrary]) -> Vec<(PathBuf, Vec<MediaKind>)> {
+    let defaults = canonical_roots(default_paths);
+    let mut limits: Vec<(PathBuf, Vec<MediaKind>)> = Vec::new();
+    let mut unlimited: Vec<PathBuf> = Vec::new();
+    for library in libraries {
+        for root in canonical_roots(&library.paths) {
+            if defaults.contains(&root) || unlimited.contains(&root) {
+                continue;
+            }
+            if library.kinds.is_empty() {
+                limits.retain(|(r, _)| *r != root);
+                unlimited.push(root);
+                continue;
+            }
+            match limits.iter_mut().find(|(r, _)| *r == root) {
+                Some((_, kinds)) => {
+                    for kind in &library.kinds {
+                        if !kinds.contains(kind) {
+                            kinds.push(*kind);
+                        }
+                    }
+                }
+                None => limits.push((root, library.kinds.clone())),
+            }
+        }
+    }
+    limits
+}
+
 /// Scan all provided paths and return a MediaLibrary with all discovered media items.
-/// Symlinks are followed. Missing/unreadable paths log warn and continue.
+/// Symlinks are followed within the roots. Missing/unreadable paths log warn and continue.
 /// Per LOCKED decision: MediaKind::Subtitle items are excluded from library.items.
 pub fn scan(paths: &[PathBuf]) -> MediaLibrary {
+    scan_with(paths, &ScanConfig::default())
+}
+
+/// Scan with explicit options. When an index path is configured, unchanged files
+/// reuse their cached metadata and the index is rewritten from the result.
+pub fn scan_with(paths: &[PathBuf], config: &ScanConfig) -> MediaLibrary {
+    scan_with_stats(paths, config).0
+}
+
+/// `scan_with`, also returning the scan statistics.
+pub fn scan_with_stats(paths: &[PathBuf], config: &ScanConfig) -> (MediaLibrary, ScanStats) {
+    let mut library = MediaLibrary::new();
+    library.roots = canonical_roots(paths);
+    library.libraries = canonical_libraries(&config.libraries);
+    library.symlinks = config.symlinks;
+    library.titles = config.titles.clone();
+    let index = load_index(config);
+    let stats = scan_batches(paths, config, index.as_ref(), |batch| library.extend(batch));
+    save_index(config, index.as_ref(), &library.items, &library.roots);
+    (library, stats)
+}
+
+/// Scan into a library that is already being served (background scan mode).
+/// Items are published in batches of `BATCH_SIZE`, each batch bumping SystemUpdateID
+/// so control points re-browse and see the library grow while the scan runs.
+/// The library must not be modified by anyone else until this returns.
+pub fn scan_into(paths: &[PathBuf], config: &ScanConfig, library: &RwLock<MediaLibrary>) {
+    {
+        let mut lib = library.write().expect("library lock poisoned");
+        lib.roots = canonical_roots(paths);
+        lib.libraries = canonical_libraries(&config.libraries);
+        lib.symlinks = config.symlinks;
+        lib.titles = config.titles.clone();
+    }
+    let index = load_index(config);
+    scan_batches(paths, config, index.as_ref(), |batch| {
+        let mut lib = library.write().expect("library lock poisoned");
+        lib.extend(batch);
+        lib.bump_update_id();
+        tracing::debug!("Background scan: {} items published", lib.items.len());
+    });
+    let lib = library.read().expect("library lock poisoned");
+    save_index(config, index.as_ref(), &lib.items, &lib.roots);
+}
+
+/// Canonicalize the scan roots (skipping missing ones) so they prefix-match the
+/// canonical item paths.
+fn canonical_roots(paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter_map(|p| std::fs::canonicalize(p).ok())
+        .collect()
+}
+
+fn canonical_libraries(libraries: &[NamedLibrary]) -> Vec<NamedLibrary> {
+    libraries
+        .iter()
+        .map(|l| NamedLibrary {
+            paths: canonical_roots(&l.paths),
+            ..l.clone()
+        })
+        .collect()
+}
+
+fn load_index(config: &ScanConfig) -> Option<MetadataIndex> {
+    match &config.index_path {
+        Some(path) if !config.rebuild_index => Some(MetadataIndex::load(path)),
+        Some(_) => {
+            tracing::info!("Rebuilding metadata index from scratch");
+            Some(MetadataIndex::default())
+        }
+        None => None,
+    }
+}
+
+/// Rewrite the index from `items`, scanned from the canonical `roots`.
+fn save_index(config: &ScanConfig, index: Option<&MetadataIndex>, items: &[MediaItem], roots: &[PathBuf]) {
+    if let (Some(index), Some(path)) = (index, &config.index_path) {
+        tracing::info!(
+            "Metadata index: {} reused, {} probed",
+            index.hits(),
+            items.len().saturating_sub(index.hits())
+        );
+        match index.save_merged(items, roots, path) {
+            Ok(()) => tracing::debug!("Wrote metadata index {}", path.display()),
+            Err(e) => tracing::warn!("Cannot write metadata index {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// The files directly in `dir` that pass the filter and walk policy of `root`, as a
+/// walk would find them. The watcher looks up sidecars among these.
+pub fn dir_files(roots: &[PathBuf], root: &Path, dir: &Path, config: &ScanConfig) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            check_walk_policy(roots, root, path, config)
+                .or_else(|| config.filter.check_path(root, path, false))
+                .is_none()
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Scan the directory `dir` below the canonical scan root `root` (the innermost one
+/// containing it, see `innermost_root`), applying the filter
+/// rules of `root` and of every directory in between. `roots` are all canonical scan
+/// roots, for the symlink policy. Used by the watcher when a whole directory appears;
+/// files `known` holds at the same size and mtime are not probed again.
+pub fn scan_subtree(
+    roots: &[PathBuf],
+    root: &Path,
+    dir: &Path,
+    config: &ScanConfig,
+    known: Option<&MetadataIndex>,
+) -> Vec<MediaItem> {
+    let mut ignores = IgnoreStack::default();
+    let excluded = check_walk_policy(roots, root, dir, config)
+        .or_else(|| config.filter.check_ancestors(root, dir, &mut ignores));
+    if let Some(reason) = excluded {
+        tracing::debug!("Excluded {} ({})", dir.display(), reason);
+        return Vec::new();
+    }
+    let mut excluded = 0usize;
+    let mut files = walk_files(roots, root, dir, config, &mut ignores, &mut excluded);
+    let kinds = config.kind_limit(root);
+    if let Some(kinds) = kinds {
+        files.retain(|f| keep_kind(f, classify(f).map(|(k, _)| k), kinds, &mut excluded));
+    }
+    let threads = config.threads.unwrap_or_else(|| default_threads(root));
+    let sidecars = SidecarIndex::new(&files);
+    let mut items = extract_parallel(&files, threads, &MACHINE_NAMESPACE, known, &sidecars);
+    if let Some(kinds) = kinds {
+        items.retain(|it| keep_kind(&it.path, Some(it.kind), kinds, &mut excluded));
+    }
+    items
+}
+
+/// Walk every root and hand extracted items to `on_batch` in deterministic order,
+/// at most `BATCH_SIZE` files' worth at a time.
+fn scan_batches(
+    paths: &[PathBuf],
+    config: &ScanConfig,
+    index: Option<&MetadataIndex>,
+    mut on_batch: impl FnMut(Vec<MediaItem>),
+) -> ScanStats {
     let start = Instant::now();
     let machine_ns = *MACHINE_NAMESPACE;
-    let mut library = MediaLibrary::new();
-    let mut video_count = 0usize;
-    let mut audio_count = 0usize;
-    let mut image_count = 0usize;
+    let mut stats = ScanStats::default();
+    let roots = canonical_roots(paths);
 
+    let mut walked: Vec<PathBuf> = Vec::new();
     for root in paths {
         // LOCKED: warn and continue if directory is missing — do not abort startup
-        if !root.exists() {
+        let Ok(canonical) = std::fs::canonicalize(root) else {
             tracing::warn!("Scan path does not exist, skipping: {}", root.display());
             continue;
+        };
+        if walked.contains(&canonical) {
+            tracing::debug!("Scan path {} already walked under another name", root.display());
+            continue;
         }
-        for entry in WalkDir::new(root).follow_links(true) {
-            match entry {
-                Err(e) => {
-                    // LOCKED: log warn for unreadable files / broken symlinks, continue
-                    tracing::warn!("Cannot access entry: {}", e);
-                }
-                Ok(entry) if entry.file_type().is_file() => {
-                    process_file(
-                        entry.path(),
-                        &machine_ns,
-                        &mut library,
-                        &mut video_count,
-                        &mut audio_count,
-                        &mut image_count,
-                    );
+        let mut ignores = IgnoreStack::default();
+        // Roots nested below this one are left out of its walk, so `kinds` is the
+        // innermost limit for every file found.
+        let mut files = walk_files(&roots, root, root, config, &mut ignores, &mut stats.excluded);
+        let kinds = config.kind_limit(&canonical);
+        walked.push(canonical);
+        if let Some(kinds) = kinds {
+            // Cheap pre-filter by extension; sniffed kinds are checked again after extraction.
+            files.retain(|f| keep_kind(f, classify(f).map(|(k, _)| k), kinds, &mut stats.excluded));
+        }
+        let threads = config.threads.unwrap_or_else(|| default_threads(root));
+        let sidecars = SidecarIndex::new(&files);
+        tracing::debug!(
+            "Extracting metadata for {} files under {} with {} worker(s)",
+            files.len(),
+            root.display(),
+            threads
+        );
+        // Batches cover `BATCH_SIZE` files each, published as the workers finish them.
+        let mut batch = Vec::new();
+        let mut done = 0;
+        extract_ordered(&files, threads, &machine_ns, index, &sidecars, |item| {
+            done += 1;
+            let excluded = &mut stats.excluded;
+            let item = item.filter(|it| kinds.is_none_or(|k| keep_kind(&it.path, Some(it.kind), k, excluded)));
+            if let Some(item) = item {
+                match item.kind {
+                    MediaKind::Video => stats.video += 1,
+                    MediaKind::Audio => stats.audio += 1,
+                    MediaKind::Image => stats.image += 1,
+                    MediaKind::Subtitle => unreachable!("filtered in scan_file"),
                 }
-                Ok(_) => {} // directory entries — walkdir handles recursion
+                batch.push(item);
             }
+            if done % BATCH_SIZE == 0 && !batch.is_empty() {
+                on_batch(std::mem::take(&mut batch));
+            }
+        });
+        if !batch.is_empty() {
+            on_batch(batch);
         }
     }
 
-    let elapsed = start.elapsed().as_secs_f64();
-    let total = library.items.len();
+    stats.total = stats.video + stats.audio + stats.image;
+    stats.elapsed_secs = start.elapsed().as_secs_f64();
     tracing::info!(
-        "Scanned {} files ({} video, {} audio, {} image) in {:.1}s",
-        total,
-        video_count,
-        audio_count,
-        image_count,
-        elapsed
+        "Scanned {} files ({} video, {} audio, {} image, {} excluded) in {:.1}s",
+        stats.total,
+        stats.video,
+        stats.audio,
+        stats.image,
+        stats.excluded,
+        stats.elapsed_secs
     );
+    stats
+}
+
+/// Walk `start` (the scan root or a directory below it) and return every regular file
+/// that passes the filter and walk policy, sorted by path within each directory so the
+/// resulting library order is the same on every run. Excluded directories and other
+/// scan roots nested below `root` are not descended into.
+fn walk_files(
+    roots: &[PathBuf],
+    root: &Path,
+    start: &Path,
+    config: &ScanConfig,
+    ignores: &mut IgnoreStack,
+    excluded: &mut usize,
+) -> Vec<PathBuf> {
+    let mut walk = WalkDir::new(start)
+        .follow_links(config.symlinks != SymlinkPolicy::Never)
+        .same_file_system(config.same_filesystem)
+        .sort_by_file_name();
+    if let Some(max_depth) = config.max_depth {
+        let start_depth = start.strip_prefix(root).map_or(0, |p| p.components().count());
+        walk = walk.max_depth(max_depth.saturating_sub(start_depth));
+    }
+
+    // Other roots below `root` are walked on their own, with their own kind limit.
+    let nested: Vec<PathBuf> = std::fs::canonicalize(root)
+        .map(|canonical| {
+            roots
+                .iter()
+                .filter_map(|r| r.strip_prefix(&canonical).ok())
+                .filter(|rel| !rel.as_os_str().is_empty())
+                .map(|rel| root.join(rel))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut files = Vec::new();
+    let mut entries = walk.into_iter();
+    while let Some(entry) = entries.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                // LOCKED: log warn for unreadable files / broken symlinks, continue
+                tracing::warn!("Cannot access entry: {}", e);
+                continue;
+            }
+        };
+        let is_dir = entry.file_type().is_dir();
+        if is_dir && entry.depth() > 0 && nested.iter().any(|n| n == entry.path()) {
+            tracing::debug!("Leaving {} to its own scan root", entry.path().display());
+            entries.skip_current_dir();
+            continue;
+        }
+        let reason = if entry.path_is_symlink() && !follows(entry.path(), roots, config.symlinks) {
+            Some(Exclusion::Symlink)
+        } else {
+            config.filter.check(root, entry.path(), is_dir, ignores)
+        };
+        if let Some(reason) = reason {
+            tracing::debug!("Excluded {} ({})", entry.path().display(), reason);
+            *excluded += 1;
+            if is_dir {
+                entries.skip_current_dir();
+            }
+            continue;
+        }
+        if entry.file_type().is_file() {
+            files.push(entry.into_path());
+        }
+    }
+    files
+}
 
-    library
+/// Whether a file of `kind` (None = not known yet) may be indexed under a root limited
+/// to `kinds`. Counts and logs the file as excluded otherwise.
+fn keep_kind(path: &Path, kind: Option<MediaKind>, kinds: &[MediaKind], excluded: &mut usize) -> bool {
+    match kind {
+        Some(kind) if kind != MediaKind::Subtitle && !kinds.contains(&kind) => {
+            tracing::debug!("Excluded {} ({})", path.display(), Exclusion::LibraryKind);
+            *excluded += 1;
+            false
+        }
+        _ => true,
+    }
 }
 
-fn process_file(
-    path: &Path,
+/// Whether the walk may go through the symlink at `link`.
+fn follows(link: &Path, roots: &[PathBuf], policy: SymlinkPolicy) -> bool {
+    match policy {
+        SymlinkPolicy::Always => true,
+        SymlinkPolicy::Never => false,
+        SymlinkPolicy::WithinRoots => std::fs::canonicalize(link)
+            .is_ok_and(|target| roots.iter().any(|r| target.starts_with(r))),
+    }
+}
+
+/// Apply the walk-time limits (symlinks, depth, filesystem boundary) to a single path
+/// below the canonical root `root`, as the watcher sees it outside of a walk.
+pub fn check_walk_policy(roots: &[PathBuf], root: &Path, path: &Path, config: &ScanConfig) -> Option<Exclusion> {
+    let depth = path.strip_prefix(root).map_or(0, |p| p.components().count());
+    if config.max_depth.is_some_and(|max| depth > max) {
+        return Some(Exclusion::TooDeep);
+    }
+    let resolved = std::fs::canonicalize(path).ok()?;
+    if !config.symlinks.allows(path, &resolved, roots) {
+        return Some(Exclusion::Symlink);
+    }
+    if config.same_filesystem && !same_device(root, path) {
+        return Some(Exclusion::OtherFilesystem);
+    }
+    None
+}
+
+#[cfg(unix)]
+fn same_device(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => true,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_device(_a: &Path, _b: &Path) -> bool {
+    true
+}
+
+/// Run `scan_file` over `files` and return the items in the order of `files`.
+fn extract_parallel(
+    files: &[PathBuf],
+    threads: usize,
     machine_ns: &Uuid,
-    library: &mut MediaLibrary,
-    video_count: &mut usize,
-    audio_count: &mut usize,
-    image_count: &mut usize,
+    index: Option<&MetadataIndex>,
+    sidecars: &SidecarIndex,
+) -> Vec<MediaItem> {
+    let mut items = Vec::with_capacity(files.len());
+    extract_ordered(files, threads, machine_ns, index, sidecars, |item| items.extend(item));
+    items
+}
+
+/// Run `scan_file` over `files` on `threads` workers pulling from a shared cursor, and
+/// hand each file's result to `on_result` in the order of `files` as soon as it and
+/// everything before it are done. One pool serves the whole list; workers send their
+/// results over a channel and the calling thread puts them back in order.
+fn extract_ordered(
+    files: &[PathBuf],
+    threads: usize,
+    machine_ns: &Uuid,
+    index: Option<&MetadataIndex>,
+    sidecars: &SidecarIndex,
+    mut on_result: impl FnMut(Option<MediaItem>),
 ) {
-    // classify() returns None for non-media files (silently skipped) and for unrecognized extensions
-    let Some((kind, mime)) = classify(path) else {
+    let threads = threads.clamp(1, files.len().max(1));
+    if threads == 1 {
+        files.iter().for_each(|path| on_result(scan_file(path, machine_ns, index, sidecars)));
         return;
-    };
+    }
+
+    let next = AtomicUsize::new(0);
+    // Bounded, so workers running ahead of a slow file wait instead of piling up items.
+    let (tx, rx) = std::sync::mpsc::sync_channel(BATCH_SIZE);
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(i) else { break };
+                if tx.send((i, scan_file(path, machine_ns, index, sidecars))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut waiting: BTreeMap<usize, Option<MediaItem>> = BTreeMap::new();
+        let mut expected = 0;
+        for (i, item) in rx {
+            waiting.insert(i, item);
+            while let Some(item) = waiting.remove(&expected) {
+                on_result(item);
+                expected += 1;
+            }
+        }
+        assert_eq!(expected, files.len(), "scan worker panicked");
+    });
+}
+
+/// Default worker count for extracting metadata under `root`.
+/// Spinning disks get 2 workers — more just makes the heads thrash between files.
+/// SSDs, network shares and anything unknown are latency-bound, so use the CPU
+/// count (capped at 8) to keep several opens/seeks in flight.
+pub fn default_threads(root: &Path) -> usize {
+    if is_rotational(root) == Some(true) {
+        return 2;
+    }
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(8)
+}
+
+/// Whether the block device holding `path` is a spinning disk, via
+/// /sys/dev/block/MAJOR:MINOR/queue/rotational. None if it cannot be determined
+/// (non-Linux, network or virtual filesystems).
+#[cfg(target_os = "linux")]
+fn is_rotational(path: &Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = std::fs::metadata(path).ok()?.dev();
+    // glibc gnu_dev_major/gnu_dev_minor encoding
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    let sys = PathBuf::from(format!("/sys/dev/block/{}:{}", major, minor));
+    // Whole disks have queue/ directly; partitions inherit it from the parent disk.
+    let flag = std::fs::read_to_string(sys.join("queue/rotational"))
+        .or_else(|_| std::fs::read_to_string(sys.join("../queue/rotational")))
+        .ok()?;
+    Some(flag.trim() == "1")
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_rotational(_path: &Path) -> Option<bool> {
+    None
+}
+
+/// Build a MediaItem for a single file, or None if the file is not servable media.
+/// Shared by the full scan and the filesystem watcher so both apply the same LOCKED rules.
+/// With an index, a fresh cached entry replaces header probing. A video's subtitles
+/// are looked up in `sidecars`, built from the same walk.
+pub fn scan_file(
+    path: &Path,
+    machine_ns: &Uuid,
+    index: Option<&MetadataIndex>,
+    sidecars: &SidecarIndex,
+) -> Option<MediaItem> {
+    // classify() returns None for non-media files (silently skipped) and for unrecognized
+    // extensions. Extensionless files are left to content sniffing below.
+    let guess = classify(path);
+    if guess.is_none() && path.extension().is_some() {
+        return None;
+    }
 
     // LOCKED: Subtitle files must NOT appear as media items in the library.
-    // They are recognized by classify() but excluded here (handled in Phase 3/5).
-    if kind == MediaKind::Subtitle {
+    // They are recognized by classify() and attached to their video instead (see below).
+    if let Some((MediaKind::Subtitle, _)) = guess {
         tracing::debug!(
             "Subtitle file recognized but excluded from library: {}",
             path.display()
         );
-        return;
+        return None;
     }
 
     // LOCKED: canonicalize path for UUID stability (Pitfall 5 in RESEARCH.md)
@@ -97,44 +595,64 @@ fn process_file(
         Ok(p) => p,
         Err(e) => {
             tracing::warn!("Cannot canonicalize {}: {}", path.display(), e);
-            return;
+            return None;
         }
     };
 
-    let file_size = match std::fs::metadata(&canonical) {
-        Ok(m) => m.len(),
+    let (file_size, modified) = match std::fs::metadata(&canonical) {
+        Ok(m) => (m.len(), m.modified().ok()),
         Err(e) => {
             tracing::warn!("Cannot stat {}: {}", canonical.display(), e);
-            return;
+            return None;
         }
     };
 
-    // LOCKED: skip file entirely on metadata extraction failure (not include with null fields)
-    let Some(meta) = extract_metadata(&canonical, kind, mime) else {
-        tracing::warn!(
-            "Skipping {} — metadata extraction failed",
-            canonical.display()
-        );
-        return;
-    };
+    // A fresh index entry already records the sniffed MIME type, so the file is not opened.
+    let cached = index
+        .zip(modified)
+        .and_then(|(index, modified)| index.lookup(&canonical, file_size, modified))
+        .and_then(|(mime, meta)| Some((kind_for_mime(mime)?, mime, meta)));
 
-    let id = media_item_id(machine_ns, &canonical);
+    let (kind, mime, mut meta) = match cached {
+        Some(hit) => hit,
+        None => {
+            // Confirm (or override) the extension guess from the file's first bytes.
+            let (kind, mime) = classify_file(path)?;
+            // LOCKED: skip file entirely on metadata extraction failure (not include with null fields)
+            let Some(meta) = extract_metadata(&canonical, kind, mime) else {
+                tracing::warn!(
+                    "Skipping {} — metadata extraction failed",
+                    canonical.display()
+                );
+                return None;
+            };
+            (kind, mime, meta)
+        }
+    };
 
-    match kind {
-        MediaKind::Video => *video_count += 1,
-        MediaKind::Audio => *audio_count += 1,
-        MediaKind::Image => *image_count += 1,
-        MediaKind::Subtitle => unreachable!("filtered above"),
+    // Folder art is looked up on every scan, so a cover.jpg added later is picked up
+    // even for files whose metadata came from the index. Embedded art wins.
+    let wants_art = matches!(kind, MediaKind::Audio | MediaKind::Video);
+    if wants_art && meta.art.as_ref().is_none_or(|a| a.path.is_some()) {
+        meta.art = canonical.parent().and_then(art::folder_art);
     }
+    // Sidecars are re-read for the same reason; a deleted one clears its fields.
+    if kind == MediaKind::Video {
+        meta.nfo = nfo::read_sidecar(&canonical);
+        meta.subtitles = sidecars.sidecars(&canonical, machine_ns);
+    }
+
+    let id = media_item_id(machine_ns, &canonical);
 
     let item = MediaItem {
         id,
         path: canonical,
         file_size,
+        modified,
         mime,
         kind,
         meta,
     };
     tracing::debug!("indexed {} -> {}", item.id, item.path.display());
-    library.items.push(item);
+    Some(item)
 }
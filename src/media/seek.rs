@@ -0,0 +1,65 @@
+//! DLNA time-based seeking: a renderer sending `TimeSeekRange.dlna.org: npt=600-`
+//! wants the stream from ten minutes in, which is how it jumps between chapters.
+//! Times map to byte offsets in proportion to the duration, so only streams whose
+//! decoders resynchronize on their own can be time-seeked.
+
+use std::path::Path;
+
+use crate::media::library::MediaItem;
+use crate::media::metadata::parse_upnp_duration;
+
+/// A requested `npt=start-[end]` range, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NptRange {
+    pub start: f64,
+    pub end: Option<f64>,
+}
+
+/// Whether a stream of `mime` can start at a mapped byte offset: MPEG transport and
+/// program streams, MP3 and ADTS AAC, which a decoder picks up at the next sync word.
+/// MP4, Matroska, Ogg and FLAC need their headers, so renderers byte-seek those.
+pub fn supports(mime: &str) -> bool {
+    matches!(mime, "video/MP2T" | "video/mpeg" | "audio/mpeg" | "audio/aac")
+}
+
+/// The duration in seconds of `item` if it can be time-seeked: it has a known
+/// duration and a format `supports` accepts.
+pub fn seekable_duration(item: &MediaItem) -> Option<f64> {
+    let duration = item.meta.duration.as_deref().and_then(parse_upnp_duration)?;
+    (duration > 0.0 && supports(item.mime)).then_some(duration)
+}
+
+/// Parse a `TimeSeekRange.dlna.org` value: `npt=` and a start time, optionally an
+/// end time, each as seconds ("90.5") or "H:MM:SS[.fff]". None if malformed or the
+/// end is before the start.
+pub fn parse_npt(value: &str) -> Option<NptRange> {
+    let range = value.trim().strip_prefix("npt=")?;
+    let (start, end) = range.split_once('-')?;
+    let start = parse_time(start)?;
+    let end = match end.trim() {
+        "" => None,
+        end => Some(parse_time(end)?),
+    };
+    end.is_none_or(|end| end > start).then_some(NptRange { start, end })
+}
+
+fn parse_time(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let seconds = if text.contains(':') { parse_upnp_duration(text)? } else { text.parse().ok()? };
+    (seconds >= 0.0 && seconds.is_finite()).then_some(seconds)
+}
+
+/// Byte offset to stream the file at `path` from to play it from `seconds`.
+pub fn byte_offset(path: &Path, mime: &str, file_size: u64, duration: f64, seconds: f64) -> u64 {
+    if seconds <= 0.0 {
+        return 0;
+    }
+    let offset = (file_size as f64 * (seconds / duration).min(1.0)) as u64;
+    // Whole transport stream packets, 192 bytes in M2TS.
+    let packet = match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        _ if mime != "video/MP2T" => 1,
+        Some("m2ts" | "mts") => 192,
+        _ => 188,
+    };
+    (offset - offset % packet).min(file_size)
+}
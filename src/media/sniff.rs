@@ -0,0 +1,160 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::media::mime::{classify, kind_for_mime, MediaKind};
+
+/// Bytes read from the start of a file for sniffing. Enough for three 192-byte
+/// M2TS packets and the first Ogg page's codec header.
+pub const SNIFF_LEN: usize = 1024;
+
+/// MIME types that share a container and cannot be told apart from the first bytes
+/// alone (MP4 brands are not reliable about audio vs video; ASF is ASF). Within a
+/// family the extension decides.
+const FAMILIES: &[&[&str]] = &[
+    &["video/mp4", "audio/mp4", "video/quicktime", "video/3gpp"],
+    &["video/x-ms-wmv", "audio/x-ms-wma"],
+];
+
+/// Classify `path` by extension, confirmed or overridden by its content.
+///
+/// - Subtitles are never sniffed.
+/// - A recognized signature wins over a conflicting extension (a `.mp4` that is really
+///   MPEG-TS becomes `video/MP2T`), except within a container family (see `FAMILIES`).
+/// - An unrecognized signature keeps the extension guess: headerless formats such as
+///   raw MP3 with leading junk are still served.
+/// - Files without an extension are classified by content alone; files with an
+///   unknown extension are skipped without being opened.
+pub fn classify_file(path: &Path) -> Option<(MediaKind, &'static str)> {
+    let guess = classify(path);
+    if path.extension().is_some() && guess.is_none() {
+        return None;
+    }
+    if matches!(guess, Some((MediaKind::Subtitle, _))) {
+        return guess;
+    }
+    let sniffed = read_header(path).and_then(|header| sniff(&header));
+    match (guess, sniffed) {
+        (Some((kind, mime)), Some(found)) if mime == found || same_family(mime, found) => Some((kind, mime)),
+        (Some((_, mime)), Some(found)) => {
+            tracing::debug!(
+                "{}: content is {}, not {} as the extension suggests",
+                path.display(),
+                found,
+                mime
+            );
+            kind_for_mime(found).map(|kind| (kind, found))
+        }
+        (guess, None) => guess,
+        (None, Some(found)) => {
+            tracing::debug!("{}: no extension, content is {}", path.display(), found);
+            kind_for_mime(found).map(|kind| (kind, found))
+        }
+    }
+}
+
+fn same_family(a: &str, b: &str) -> bool {
+    FAMILIES.iter().any(|f| f.contains(&a) && f.contains(&b))
+}
+
+/// Read the first `SNIFF_LEN` bytes of `path`. A leading ID3v2 tag too long to fit
+/// (embedded cover art) is skipped: the bytes after it are returned instead.
+pub fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    (&mut file).take(SNIFF_LEN as u64).read_to_end(&mut header).ok()?;
+    if let Some(len) = id3_len(&header).filter(|len| *len >= header.len() as u64) {
+        file.seek(SeekFrom::Start(len)).ok()?;
+        header.clear();
+        file.take(SNIFF_LEN as u64).read_to_end(&mut header).ok()?;
+    }
+    Some(header)
+}
+
+/// Total length of the ID3v2 tag `header` starts with: the 10-byte header, the
+/// syncsafe body size and the footer if the flags announce one. None without a tag.
+pub fn id3_len(header: &[u8]) -> Option<u64> {
+    if header.get(..3)? != b"ID3" {
+        return None;
+    }
+    let size = header.get(6..10)?.iter().fold(0u64, |n, b| n << 7 | u64::from(b & 0x7F));
+    let footer = if header.get(5)? & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + size + footer)
+}
+
+/// Identify a media container from its first bytes. Returns one of `SUPPORTED_MIMES`,
+/// or None when no known signature matches.
+pub fn sniff(header: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+
+    if at(4, b"ftyp") {
+        return Some(match header.get(8..12)? {
+            b"M4A " | b"M4B " | b"M4P " => "audio/mp4",
+            b"qt  " => "video/quicktime",
+            brand if brand.starts_with(b"3g") => "video/3gpp",
+            // HEIF/AVIF stills share the ISO base media format but are not served.
+            b"heic" | b"heix" | b"mif1" | b"msf1" | b"avif" => return None,
+            _ => "video/mp4",
+        });
+    }
+    if at(0, &[0x1A, 0x45, 0xDF, 0xA3]) {
+        // EBML header: the DocType element (0x42 0x82) names the flavour.
+        let webm = header.windows(7).any(|w| w == b"\x42\x82\x84webm");
+        return Some(if webm { "video/webm" } else { "video/x-matroska" });
+    }
+    if at(0, b"RIFF") {
+        return match header.get(8..12)? {
+            b"WAVE" => Some("audio/wav"),
+            b"AVI " => Some("video/x-msvideo"),
+            b"WEBP" => Some("image/webp"),
+            _ => None,
+        };
+    }
+    if at(0, b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) {
+        return Some("audio/aiff");
+    }
+    if at(0, b"fLaC") {
+        return Some("audio/flac");
+    }
+    if at(0, b"OggS") {
+        // The first page carries the first stream's codec identification header.
+        let theora = header.windows(7).any(|w| w == b"\x80theora");
+        return Some(if theora { "video/ogg" } else { "audio/ogg" });
+    }
+    if at(0, &[0xFF, 0xD8, 0xFF]) {
+        return Some("image/jpeg");
+    }
+    if at(0, b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png");
+    }
+    if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        return Some("image/gif");
+    }
+    if at(0, b"II*\0") || at(0, b"MM\0*") {
+        return Some("image/tiff");
+    }
+    if at(0, b"FLV\x01") {
+        return Some("video/x-flv");
+    }
+    if at(0, &[0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11]) {
+        return Some("video/x-ms-wmv");
+    }
+    if at(0, &[0x00, 0x00, 0x01, 0xBA]) {
+        return Some("video/mpeg");
+    }
+    // MPEG-TS: 0x47 sync byte every 188 bytes; M2TS adds a 4-byte timestamp per packet.
+    if (0..3).all(|i| header.get(i * 188) == Some(&0x47))
+        || (0..3).all(|i| header.get(4 + i * 192) == Some(&0x47))
+    {
+        return Some("video/MP2T");
+    }
+    // ID3v2 tags precede MP3, but also AAC and FLAC streams: identify what follows. A
+    // tag longer than the header leaves the extension to decide.
+    if let Some(len) = id3_len(header) {
+        return header.get(usize::try_from(len).ok()?..).and_then(sniff);
+    }
+    // Frame sync: 11 set bits. Layer bits 00 mean ADTS (AAC); anything else is MPEG audio.
+    if header.len() >= 2 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0 {
+        return Some(if header[1] & 0x06 == 0 { "audio/aac" } else { "audio/mpeg" });
+    }
+    None
+}
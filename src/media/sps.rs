@@ -0,0 +1,393 @@
+//! Video sequence headers: picture size, profile, level and bit depth from H.264 and
+//! HEVC sequence parameter sets, MPEG-1/2 sequence headers and the decoder
+//! configuration records MP4 and Matroska store them in (avcC, hvcC, av1C, vpcC).
+
+/// The fields of a sequence parameter set udlna uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sps {
+    /// `profile_idc` (H.264: 66 Baseline, 77 Main, 100 High, ...; HEVC: 1 Main, 2 Main 10).
+    pub profile: u8,
+    /// `level_idc` (H.264: level × 10; HEVC: level × 30).
+    pub level: u8,
+    /// Luma bit depth.
+    pub bit_depth: u8,
+    /// Displayed size, after the cropping window.
+    pub width: u32,
+    pub height: u32,
+}
+
+/// H.264 profiles whose SPS carries chroma format, bit depth and scaling lists.
+const H264_HIGH_PROFILES: &[u8] = &[100, 110, 122, 244, 44, 83, 86, 118, 128, 138, 139, 134, 135];
+
+const H264_NAL_SPS: u8 = 7;
+const HEVC_NAL_SPS: u8 = 33;
+const MPEG_SEQUENCE_HEADER: u8 = 0xB3;
+
+/// The first H.264 SPS in an Annex B byte stream.
+pub fn find_h264_sps(stream: &[u8]) -> Option<Sps> {
+    nal_units(stream)
+        .filter(|nal| nal.first().is_some_and(|b| b & 0x1F == H264_NAL_SPS))
+        .find_map(h264_sps)
+}
+
+/// The first HEVC SPS in an Annex B byte stream.
+pub fn find_hevc_sps(stream: &[u8]) -> Option<Sps> {
+    nal_units(stream)
+        .filter(|nal| nal.first().is_some_and(|b| (b >> 1) & 0x3F == HEVC_NAL_SPS))
+        .find_map(hevc_sps)
+}
+
+/// Picture size from the first MPEG-1/MPEG-2 video sequence header in `stream`.
+pub fn find_mpeg2_size(stream: &[u8]) -> Option<(u32, u32)> {
+    let at = stream
+        .windows(4)
+        .position(|w| w == [0, 0, 1, MPEG_SEQUENCE_HEADER])?;
+    let b = stream.get(at + 4..at + 7)?;
+    let width = (b[0] as u32) << 4 | (b[1] as u32) >> 4;
+    let height = (b[1] as u32 & 0x0F) << 8 | b[2] as u32;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Split an Annex B byte stream on `00 00 01` start codes.
+fn nal_units(stream: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut starts = Vec::new();
+    let mut i = 0;
+    while i + 3 <= stream.len() {
+        if stream[i..i + 3] == [0, 0, 1] {
+            starts.push(i + 3);
+            i += 3;
+        } else {
+            i += 1;
+        }
+    }
+    let ends: Vec<usize> = starts.iter().skip(1).map(|s| s - 3).chain([stream.len()]).collect();
+    starts.into_iter().zip(ends).map(move |(start, end)| &stream[start..end])
+}
+
+/// Strip emulation prevention bytes (`00 00 03` → `00 00`).
+fn unescape(nal: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(nal.len());
+    let mut zeros = 0;
+    for &b in nal {
+        if zeros >= 2 && b == 3 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if b == 0 { zeros + 1 } else { 0 };
+        out.push(b);
+    }
+    out
+}
+
+/// Parse an H.264 SPS NAL unit, header byte included.
+pub fn h264_sps(nal: &[u8]) -> Option<Sps> {
+    let rbsp = unescape(nal.get(1..)?);
+    let mut r = Bits::new(&rbsp);
+    let profile = r.bits(8)? as u8;
+    r.skip(8)?; // constraint flags
+    let level = r.bits(8)? as u8;
+    r.ue()?; // seq_parameter_set_id
+    let (mut chroma_format, mut bit_depth) = (1, 8);
+    if H264_HIGH_PROFILES.contains(&profile) {
+        chroma_format = r.ue()?;
+        if chroma_format == 3 {
+            r.skip(1)?; // separate_colour_plane_flag
+        }
+        bit_depth = r.ue()?.checked_add(8)?;
+        r.ue()?; // bit_depth_chroma_minus8
+        r.skip(1)?; // qpprime_y_zero_transform_bypass_flag
+        if r.bit()? {
+            let lists = if chroma_format == 3 { 12 } else { 8 };
+            for i in 0..lists {
+                if r.bit()? {
+                    skip_scaling_list(&mut r, if i < 6 { 16 } else { 64 })?;
+                }
+            }
+        }
+    }
+    r.ue()?; // log2_max_frame_num_minus4
+    match r.ue()? {
+        0 => {
+            r.ue()?; // log2_max_pic_order_cnt_lsb_minus4
+        }
+        1 => {
+            r.skip(1)?; // delta_pic_order_always_zero_flag
+            r.se()?;
+            r.se()?;
+            for _ in 0..r.ue()? {
+                r.se()?;
+            }
+        }
+        _ => {}
+    }
+    r.ue()?; // max_num_ref_frames
+    r.skip(1)?; // gaps_in_frame_num_value_allowed_flag
+    let width_mbs = r.ue()? + 1;
+    let height_units = r.ue()? + 1;
+    let frame_mbs_only = r.bit()? as u32;
+    if frame_mbs_only == 0 {
+        r.skip(1)?; // mb_adaptive_frame_field_flag
+    }
+    r.skip(1)?; // direct_8x8_inference_flag
+    let (mut left, mut right, mut top, mut bottom) = (0, 0, 0, 0);
+    if r.bit()? {
+        (left, right, top, bottom) = (r.ue()?, r.ue()?, r.ue()?, r.ue()?);
+    }
+
+    let (crop_x, crop_y) = match chroma_format {
+        1 => (2, 2 * (2 - frame_mbs_only)),
+        2 => (2, 2 - frame_mbs_only),
+        _ => (1, 2 - frame_mbs_only),
+    };
+    let width = width_mbs.checked_mul(16)?.checked_sub(left.checked_add(right)?.checked_mul(crop_x)?)?;
+    let height = height_units
+        .checked_mul(16 * (2 - frame_mbs_only))?
+        .checked_sub(top.checked_add(bottom)?.checked_mul(crop_y)?)?;
+    Some(Sps { profile, level, bit_depth: bit_depth as u8, width, height })
+}
+
+fn skip_scaling_list(r: &mut Bits, size: usize) -> Option<()> {
+    let (mut last, mut next) = (8i32, 8i32);
+    for _ in 0..size {
+        if next != 0 {
+            // delta_scale is -128..=127; anything else is a corrupt SPS.
+            let delta = r.se()?;
+            if !(-128..=127).contains(&delta) {
+                return None;
+            }
+            next = (last + delta + 256) % 256;
+        }
+        if next != 0 {
+            last = next;
+        }
+    }
+    Some(())
+}
+
+/// Parse an HEVC SPS NAL unit, two-byte header included.
+pub fn hevc_sps(nal: &[u8]) -> Option<Sps> {
+    let rbsp = unescape(nal.get(2..)?);
+    let mut r = Bits::new(&rbsp);
+    r.skip(4)?; // sps_video_parameter_set_id
+    let sub_layers = r.bits(3)? as usize;
+    r.skip(1)?; // sps_temporal_id_nesting_flag
+
+    // profile_tier_level: general_profile_space, general_tier_flag, then the profile.
+    r.skip(3)?;
+    let profile = r.bits(5)? as u8;
+    r.skip(32 + 4 + 43 + 1)?; // compatibility flags, source flags, reserved bits
+    let level = r.bits(8)? as u8;
+    let mut present = Vec::with_capacity(sub_layers);
+    for _ in 0..sub_layers {
+        present.push((r.bit()?, r.bit()?));
+    }
+    if sub_layers > 0 {
+        r.skip(2 * (8 - sub_layers))?;
+    }
+    for (profile_present, level_present) in present {
+        if profile_present {
+            r.skip(88)?;
+        }
+        if level_present {
+            r.skip(8)?;
+        }
+    }
+
+    r.ue()?; // sps_seq_parameter_set_id
+    let chroma_format = r.ue()?;
+    if chroma_format == 3 {
+        r.skip(1)?; // separate_colour_plane_flag
+    }
+    let mut width = r.ue()?;
+    let mut height = r.ue()?;
+    if r.bit()? {
+        let (sub_width, sub_height) = match chroma_format {
+            1 => (2, 2),
+            2 => (2, 1),
+            _ => (1, 1),
+        };
+        let (left, right, top, bottom) = (r.ue()?, r.ue()?, r.ue()?, r.ue()?);
+        width = width.checked_sub(left.checked_add(right)?.checked_mul(sub_width)?)?;
+        height = height.checked_sub(top.checked_add(bottom)?.checked_mul(sub_height)?)?;
+    }
+    let bit_depth = r.ue()?.checked_add(8)?;
+    Some(Sps { profile, level, bit_depth: bit_depth as u8, width, height })
+}
+
+// ── Configuration records ─────────────────────────────────────────────────────
+
+/// The first SPS of an AVCDecoderConfigurationRecord (MP4 `avcC`, Matroska
+/// `V_MPEG4/ISO/AVC` CodecPrivate).
+pub fn avc_config(record: &[u8]) -> Option<Sps> {
+    if record.first() != Some(&1) || record.get(5)? & 0x1F == 0 {
+        return None;
+    }
+    let len = u16::from_be_bytes([*record.get(6)?, *record.get(7)?]) as usize;
+    h264_sps(record.get(8..8 + len)?)
+}
+
+/// An HEVCDecoderConfigurationRecord (MP4 `hvcC`, Matroska `V_MPEGH/ISO/HEVC`
+/// CodecPrivate): its first SPS, else the profile, level and bit depth of the record
+/// header with no picture size.
+pub fn hevc_config(record: &[u8]) -> Option<Sps> {
+    if record.len() < 23 || record[0] != 1 {
+        return None;
+    }
+    let header = Sps {
+        profile: record[1] & 0x1F,
+        level: record[12],
+        bit_depth: (record[17] & 0x07) + 8,
+        width: 0,
+        height: 0,
+    };
+    let mut at = 23;
+    for _ in 0..record[22] {
+        let nal_type = record.get(at)? & 0x3F;
+        let count = u16::from_be_bytes([*record.get(at + 1)?, *record.get(at + 2)?]);
+        at += 3;
+        for _ in 0..count {
+            let len = u16::from_be_bytes([*record.get(at)?, *record.get(at + 1)?]) as usize;
+            let nal = record.get(at + 2..at + 2 + len)?;
+            if nal_type == HEVC_NAL_SPS {
+                return Some(hevc_sps(nal).unwrap_or(header));
+            }
+            at += 2 + len;
+        }
+    }
+    Some(header)
+}
+
+/// Profile, level and bit depth from an AV1CodecConfigurationRecord (MP4 `av1C`,
+/// Matroska `V_AV1` CodecPrivate). The picture size is left 0.
+pub fn av1_config(record: &[u8]) -> Option<Sps> {
+    let b = record.get(..3)?;
+    if b[0] != 0x81 {
+        return None;
+    }
+    let bit_depth = match (b[2] & 0x40 != 0, b[2] & 0x20 != 0) {
+        (true, true) => 12,
+        (true, false) => 10,
+        _ => 8,
+    };
+    Some(Sps { profile: b[1] >> 5, level: b[1] & 0x1F, bit_depth, width: 0, height: 0 })
+}
+
+/// Profile, level and bit depth from a VPCodecConfigurationRecord (MP4 `vpcC`,
+/// version and flags included). The picture size is left 0.
+pub fn vp9_config(record: &[u8]) -> Option<Sps> {
+    let b = record.get(..7)?;
+    if b[0] != 1 {
+        return None;
+    }
+    Some(Sps { profile: b[4], level: b[5], bit_depth: b[6] >> 4, width: 0, height: 0 })
+}
+
+/// Profile name for `codec` (a short name as in `MediaMeta::video_codec`), as
+/// ffmpeg and MediaInfo print it.
+pub fn profile_name(codec: &str, profile: u8) -> Option<&'static str> {
+    let name = match (codec, profile) {
+        ("h264", 66) => "Baseline",
+        ("h264", 77) => "Main",
+        ("h264", 88) => "Extended",
+        ("h264", 100) => "High",
+        ("h264", 110) => "High 10",
+        ("h264", 122) => "High 4:2:2",
+        ("h264", 244) => "High 4:4:4 Predictive",
+        ("hevc", 1) => "Main",
+        ("hevc", 2) => "Main 10",
+        ("hevc", 3) => "Main Still Picture",
+        ("hevc", 4) => "Rext",
+        ("av1", 0) => "Main",
+        ("av1", 1) => "High",
+        ("av1", 2) => "Professional",
+        ("vp9", 0) => "Profile 0",
+        ("vp9", 1) => "Profile 1",
+        ("vp9", 2) => "Profile 2",
+        ("vp9", 3) => "Profile 3",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Level as a decimal ("4", "4.1", "5.1") from the codec's encoded level.
+pub fn level_name(codec: &str, level: u8) -> Option<String> {
+    let (major, minor) = match codec {
+        "h264" if level == 9 => return Some("1b".to_string()),
+        "h264" | "vp9" => (level / 10, level % 10),
+        "hevc" => (level / 30, level % 30 / 3),
+        // seq_level_idx 31 places no level constraint.
+        "av1" if level < 31 => (2 + (level >> 2), level & 3),
+        _ => return None,
+    };
+    if major == 0 {
+        return None;
+    }
+    Some(if minor == 0 { major.to_string() } else { format!("{major}.{minor}") })
+}
+
+/// Name of an ITU-T H.273 transfer characteristics code, as ffmpeg prints it.
+/// `smpte2084` (PQ) marks HDR10 and Dolby Vision, `arib-std-b67` HLG.
+pub fn transfer_name(code: u8) -> Option<&'static str> {
+    let name = match code {
+        1 => "bt709",
+        4 => "gamma22",
+        5 => "gamma28",
+        6 => "smpte170m",
+        7 => "smpte240m",
+        8 => "linear",
+        13 => "iec61966-2-1",
+        14 => "bt2020-10",
+        15 => "bt2020-12",
+        16 => "smpte2084",
+        18 => "arib-std-b67",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// MSB-first bit reader with Exp-Golomb codes.
+struct Bits<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Bits<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Bits { data, position: 0 }
+    }
+
+    fn bit(&mut self) -> Option<bool> {
+        let byte = self.data.get(self.position / 8)?;
+        let bit = byte >> (7 - self.position % 8) & 1;
+        self.position += 1;
+        Some(bit == 1)
+    }
+
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        (0..n).try_fold(0u32, |value, _| Some(value << 1 | self.bit()? as u32))
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        self.position += n;
+        (self.position <= self.data.len() * 8).then_some(())
+    }
+
+    /// Unsigned Exp-Golomb.
+    fn ue(&mut self) -> Option<u32> {
+        let mut zeros = 0;
+        while !self.bit()? {
+            zeros += 1;
+            if zeros > 31 {
+                return None;
+            }
+        }
+        Some((1u32 << zeros) - 1 + self.bits(zeros)?)
+    }
+
+    /// Signed Exp-Golomb.
+    fn se(&mut self) -> Option<i32> {
+        let code = self.ue()? as i64;
+        let value = if code % 2 == 1 { (code + 1) / 2 } else { -(code / 2) };
+        Some(value as i32)
+    }
+}
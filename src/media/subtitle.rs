@@ -0,0 +1,125 @@
+//! Sidecar subtitles: `movie.srt`, `movie.en.srt` or `movie.pt-BR.forced.vtt` next to
+//! `movie.mkv`. They are never items of their own; each video lists its sidecars.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+use crate::media::metadata::media_item_id;
+use crate::media::mime::{classify, MediaKind};
+
+/// Name tags that mark a kind of subtitle rather than its language.
+const FLAGS: &[&str] = &["forced", "sdh", "cc", "hi", "default"];
+
+/// A subtitle file belonging to a video.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subtitle {
+    /// UUIDv5 of the subtitle's path, as media items are identified; `/subtitle/{id}`.
+    pub id: Uuid,
+    pub path: PathBuf,
+    /// "text/srt" or "text/vtt".
+    pub mime: &'static str,
+    /// Language tag from the file name: "en" in `movie.en.srt`.
+    pub language: Option<String>,
+}
+
+impl Subtitle {
+    /// The format name Samsung's `sec:CaptionInfoEx sec:type` expects: "srt" or "vtt".
+    pub fn format(&self) -> &'static str {
+        self.mime.trim_start_matches("text/")
+    }
+}
+
+/// Whether `path` is named like a subtitle file udlna serves.
+pub fn is_sidecar(path: &Path) -> bool {
+    matches!(classify(path), Some((MediaKind::Subtitle, _)))
+}
+
+/// The subtitle files and video names of every directory among a list of files, so
+/// each video's sidecars are found without listing its directory again. Built from
+/// the files a walk found, so the include/exclude rules, hidden files and the symlink
+/// policy apply to subtitles as to media.
+#[derive(Debug, Default)]
+pub struct SidecarIndex {
+    /// By canonical directory.
+    dirs: HashMap<PathBuf, DirEntries>,
+}
+
+#[derive(Debug, Default)]
+struct DirEntries {
+    /// (stem, file name, MIME) of each subtitle, sorted by stem.
+    subtitles: Vec<(String, String, &'static str)>,
+    video_stems: Vec<String>,
+}
+
+impl SidecarIndex {
+    pub fn new<'a>(files: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        let mut canonical: HashMap<&Path, Option<PathBuf>> = HashMap::new();
+        let mut dirs: HashMap<PathBuf, DirEntries> = HashMap::new();
+        for file in files {
+            let (Some(parent), Some(name)) = (file.parent(), file.file_name().and_then(|n| n.to_str())) else {
+                continue;
+            };
+            let (kind, mime) = match classify(file) {
+                Some((kind @ (MediaKind::Video | MediaKind::Subtitle), mime)) => (kind, mime),
+                _ => continue,
+            };
+            let Some(dir) = canonical.entry(parent).or_insert_with(|| std::fs::canonicalize(parent).ok()) else {
+                continue;
+            };
+            let Some(stem) = Path::new(name).file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let entries = dirs.entry(dir.clone()).or_default();
+            match kind {
+                MediaKind::Video => entries.video_stems.push(stem.to_string()),
+                _ => entries.subtitles.push((stem.to_string(), name.to_string(), mime)),
+            }
+        }
+        for entries in dirs.values_mut() {
+            entries.subtitles.sort();
+        }
+        SidecarIndex { dirs }
+    }
+
+    /// The subtitles next to the video at canonical path `media`: files named
+    /// `<basename>.srt` or `<basename>.<tags>.srt` (and `.vtt`), untagged first, then by
+    /// name. A file is left to a sibling video whose own name it matches more closely
+    /// (`movie.part2.srt` belongs to `movie.part2.mkv`).
+    pub fn sidecars(&self, media: &Path, namespace: &Uuid) -> Vec<Subtitle> {
+        let (Some(dir), Some(stem)) = (media.parent(), media.file_stem().and_then(|s| s.to_str())) else {
+            return Vec::new();
+        };
+        let Some(entries) = self.dirs.get(dir) else {
+            return Vec::new();
+        };
+        let longer: Vec<&str> =
+            entries.video_stems.iter().map(String::as_str).filter(|other| other.len() > stem.len()).collect();
+
+        // Candidates share the prefix `stem`, so they sit together in the sorted list.
+        let first = entries.subtitles.partition_point(|(base, _, _)| base.as_str() < stem);
+        let mut found: Vec<(bool, &str, Subtitle)> = entries.subtitles[first..]
+            .iter()
+            .take_while(|(base, _, _)| base.starts_with(stem))
+            .filter_map(|(base, name, mime)| {
+                let tags = if base == stem { "" } else { base.strip_prefix(stem)?.strip_prefix('.')? };
+                if longer.iter().any(|other| base == other || base.starts_with(&format!("{other}."))) {
+                    return None;
+                }
+                let language =
+                    tags.split('.').find(|tag| !tag.is_empty() && !FLAGS.contains(&tag.to_lowercase().as_str()));
+                let path = dir.join(name);
+                let subtitle = Subtitle {
+                    id: media_item_id(namespace, &path),
+                    path,
+                    mime,
+                    language: language.map(str::to_string),
+                };
+                Some((!tags.is_empty(), name.as_str(), subtitle))
+            })
+            .collect();
+        found.sort_by_key(|(tagged, name, _)| (*tagged, *name));
+        found.into_iter().map(|(_, _, subtitle)| subtitle).collect()
+    }
+}
@@ -0,0 +1,140 @@
+//! Display titles from file names: `The.Movie.2019.1080p.BluRay.x264-GRP` is shown
+//! as "The Movie" dated 2019. The raw name stays in the item's path (and the index).
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::media::mime::MediaKind;
+
+/// Words that start the release details of a video name: resolution, source, codec
+/// and edition tags. Everything from the first of them on is dropped.
+const RELEASE_TOKENS: &[&str] = &[
+    "480p", "576p", "720p", "1080p", "1080i", "2160p", "4k", "uhd", "hdtv", "pdtv", "sdtv", "web", "webrip", "webdl",
+    "bluray", "blu-ray", "brrip", "bdrip", "dvdrip", "hdrip", "amzn", "dsnp", "hmax", "atvp", "x264", "x265", "h264",
+    "h265", "hevc", "xvid", "divx", "10bit", "hdr", "hdr10", "aac", "ac3", "eac3", "dts", "dd5", "ddp5", "repack",
+    "remux", "extended", "unrated", "remastered",
+];
+
+/// A `[[title_rewrite]]` table: a regex replaced in the file name before cleanup.
+/// `replace` may refer to capture groups as `$1` or `${name}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RewriteRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub replace: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid title_rewrite pattern `{pattern}`: {source}")]
+pub struct TitleError {
+    pattern: String,
+    source: regex::Error,
+}
+
+/// Title and year read from a file name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanTitle {
+    pub title: String,
+    pub year: Option<i32>,
+}
+
+/// Turns file names into display titles. Rewrite rules apply to every file name;
+/// the cleanup (separators, release tokens, year) to video names only, since music
+/// and photo names rarely carry release details and "Prince - 1999" is not dated.
+#[derive(Debug, Clone)]
+pub struct TitleNormalizer {
+    enabled: bool,
+    rules: Vec<RewriteRule>,
+    compiled: Vec<Regex>,
+}
+
+impl Default for TitleNormalizer {
+    fn default() -> Self {
+        Self { enabled: true, rules: Vec::new(), compiled: Vec::new() }
+    }
+}
+
+/// Equal when configured the same; a compiled regex has no equality of its own.
+impl PartialEq for TitleNormalizer {
+    fn eq(&self, other: &Self) -> bool {
+        self.enabled == other.enabled && self.rules == other.rules
+    }
+}
+
+impl TitleNormalizer {
+    /// `enabled` = false shows video names as they are, apart from the rewrite rules.
+    pub fn new(enabled: bool, rules: &[RewriteRule]) -> Result<Self, TitleError> {
+        let compiled = rules
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern).map_err(|source| TitleError { pattern: rule.pattern.clone(), source })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { enabled, rules: rules.to_vec(), compiled })
+    }
+
+    /// The display title for a file named `stem` (no extension). Falls back to the
+    /// rewritten name when cleanup would leave nothing.
+    pub fn normalize(&self, stem: &str, kind: MediaKind) -> CleanTitle {
+        let mut name = stem.to_string();
+        for (regex, rule) in self.compiled.iter().zip(&self.rules) {
+            name = regex.replace_all(&name, rule.replace.as_str()).into_owned();
+        }
+        if !self.enabled || kind != MediaKind::Video {
+            return CleanTitle { title: name.trim().to_string(), year: None };
+        }
+        let cleaned = clean_video_name(&name);
+        if cleaned.title.is_empty() {
+            return CleanTitle { title: name.trim().to_string(), year: None };
+        }
+        cleaned
+    }
+}
+
+/// Split a video name into words, cut at the first release token, and take the
+/// last year after the first word as the release year: the title ends there.
+fn clean_video_name(name: &str) -> CleanTitle {
+    let kept = words_before_release(name);
+    let year_at = kept.iter().enumerate().skip(1).rev().find_map(|(i, word)| Some((i, year(word)?)));
+    let (title, year) = match year_at {
+        Some((i, year)) => (&kept[..i], Some(year)),
+        None => (&kept[..], None),
+    };
+    CleanTitle { title: trim(&title.join(" ")), year }
+}
+
+/// `word` as a plausible release year, also in brackets: "2019", "(2019)", "[2019]".
+fn year(word: &str) -> Option<i32> {
+    let digits = word.trim_matches(['(', ')', '[', ']']);
+    let year: i32 = digits.parse().ok().filter(|_| digits.len() == 4)?;
+    (1900..=2099).contains(&year).then_some(year)
+}
+
+/// The words of `text`. Dots separate words only in names without spaces ("Mr. Robot"
+/// keeps its dot); underscores always do.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    let separators: &[char] = if text.contains(' ') { &[' ', '_'] } else { &['.', '_', ' '] };
+    text.split(separators).filter(|word| !word.is_empty())
+}
+
+/// The words of `text` up to the first release token.
+pub fn words_before_release(text: &str) -> Vec<&str> {
+    words(text).take_while(|word| !is_release_token(word)).collect()
+}
+
+/// Whether `word` starts the release details: "1080p", "[WEB-DL]", "x264-GROUP".
+fn is_release_token(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    let token = lower.trim_matches(['[', ']', '(', ')']);
+    let head = token.split_once('-').filter(|(head, _)| !head.is_empty()).map_or(token, |(head, _)| head);
+    RELEASE_TOKENS.contains(&token) || RELEASE_TOKENS.contains(&head)
+}
+
+/// `text` as single-spaced words, without the dashes and brackets left at either end.
+pub fn clean(text: &str) -> String {
+    trim(&words(text).collect::<Vec<_>>().join(" "))
+}
+
+fn trim(text: &str) -> String {
+    text.trim_start_matches(['-', ']', ')', ' ']).trim_end_matches(['-', '[', '(', ' ']).to_string()
+}
@@ -0,0 +1,295 @@
+//! MPEG transport stream reader (.ts, .m2ts, .mts): program tables, PCR-based
+//! duration and the picture size from the video stream's sequence header.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::media::library::AudioTrack;
+use crate::media::sps;
+
+const SYNC: u8 = 0x47;
+const PACKET_LEN: usize = 188;
+/// M2TS (Blu-ray, AVCHD) prefixes every packet with a 4-byte timestamp.
+const PACKET_SIZES: &[usize] = &[188, 192];
+
+const PAT_PID: u16 = 0x0000;
+const NULL_PID: u16 = 0x1FFF;
+const TABLE_PAT: u8 = 0x00;
+const TABLE_PMT: u8 = 0x02;
+
+const DESCRIPTOR_LANGUAGE: u8 = 0x0A;
+const DESCRIPTOR_AC3: u8 = 0x6A;
+const DESCRIPTOR_EAC3: u8 = 0x7A;
+const DESCRIPTOR_DTS: u8 = 0x7B;
+
+/// Bytes read from each end of the file. PCRs repeat at least every 100 ms and
+/// encoders repeat the sequence header at every keyframe.
+const HEAD_LEN: u64 = 4 * 1024 * 1024;
+const TAIL_LEN: u64 = 1024 * 1024;
+/// Video elementary stream bytes searched for a sequence header.
+const MAX_VIDEO_ES: usize = 1024 * 1024;
+
+/// PCR ticks per second.
+const PCR_HZ: f64 = 27_000_000.0;
+/// The 33-bit PCR base wraps about every 26.5 hours.
+const PCR_WRAP: u64 = (1 << 33) * 300;
+
+/// What udlna reads from a transport stream. Only the first program is considered.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TsInfo {
+    /// Seconds between the first and the last PCR.
+    pub duration: Option<f64>,
+    pub video_codec: Option<&'static str>,
+    /// Picture size from the first SPS / sequence header of the video stream.
+    pub video: Option<sps::Sps>,
+    pub audio: Vec<AudioTrack>,
+    /// 192-byte M2TS packets, each carrying an arrival timestamp.
+    pub timestamped: bool,
+}
+
+/// Read the program tables, duration and picture size of the transport stream at
+/// `path`. None if no PAT/PMT is found near the start of the file.
+pub fn read_info(path: &Path) -> Option<TsInfo> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut head = Vec::new();
+    (&mut file).take(HEAD_LEN).read_to_end(&mut head).ok()?;
+    let mut tail = Vec::new();
+    if len > HEAD_LEN {
+        file.seek(SeekFrom::Start(len.saturating_sub(TAIL_LEN).max(HEAD_LEN))).ok()?;
+        file.take(TAIL_LEN).read_to_end(&mut tail).ok()?;
+    }
+
+    let program = read_program(&head)?;
+    let mut info = TsInfo { timestamped: layout(&head).is_some_and(|(_, size)| size == 192), ..Default::default() };
+    let mut video_pid = None;
+    for stream in &program.streams {
+        match stream_codec(stream) {
+            Some((Kind::Video, codec)) if video_pid.is_none() => {
+                video_pid = Some(stream.pid);
+                info.video_codec = Some(codec);
+            }
+            Some((Kind::Audio, codec)) => info.audio.push(AudioTrack {
+                codec: codec.to_string(),
+                language: stream.language.clone(),
+                channels: None,
+            }),
+            _ => {}
+        }
+    }
+
+    if program.pcr_pid != NULL_PID {
+        let first = packets(&head).find_map(|p| p.pcr.filter(|_| p.pid == program.pcr_pid));
+        let last = packets(if tail.is_empty() { &head } else { &tail })
+            .filter(|p| p.pid == program.pcr_pid)
+            .filter_map(|p| p.pcr)
+            .last();
+        if let (Some(first), Some(last)) = (first, last) {
+            let ticks = (last + PCR_WRAP - first) % PCR_WRAP;
+            info.duration = (ticks > 0).then(|| ticks as f64 / PCR_HZ);
+        }
+    }
+
+    if let (Some(pid), Some(codec)) = (video_pid, info.video_codec) {
+        let es = elementary_stream(&head, pid);
+        info.video = match codec {
+            "h264" => sps::find_h264_sps(&es),
+            "hevc" => sps::find_hevc_sps(&es),
+            "mpeg1video" | "mpeg2video" => sps::find_mpeg2_size(&es).map(|(width, height)| sps::Sps {
+                profile: 0,
+                level: 0,
+                bit_depth: 8,
+                width,
+                height,
+            }),
+            _ => None,
+        };
+    }
+    Some(info)
+}
+
+struct Packet<'a> {
+    pid: u16,
+    unit_start: bool,
+    /// Program clock reference in 27 MHz ticks.
+    pcr: Option<u64>,
+    payload: &'a [u8],
+}
+
+/// Offset of the first packet in `data` and the packet size (188, or 192 for M2TS),
+/// from a run of sync bytes.
+fn layout(data: &[u8]) -> Option<(usize, usize)> {
+    PACKET_SIZES.iter().find_map(|&size| {
+        (0..size)
+            .find(|&first| (0..4).all(|k| data.get(first + k * size) == Some(&SYNC)))
+            .map(|first| (first, size))
+    })
+}
+
+/// The 188-byte packets of `data`, which may start mid-packet and may use 192-byte
+/// M2TS framing. Empty if no run of sync bytes is found.
+fn packets(data: &[u8]) -> impl Iterator<Item = Packet<'_>> {
+    let (first, size) = layout(data).unwrap_or((data.len(), PACKET_LEN));
+    data[first..]
+        .chunks(size)
+        .filter_map(|chunk| chunk.get(..PACKET_LEN))
+        .filter_map(parse_packet)
+}
+
+fn parse_packet(p: &[u8]) -> Option<Packet<'_>> {
+    if p[0] != SYNC {
+        return None;
+    }
+    let pid = (p[1] as u16 & 0x1F) << 8 | p[2] as u16;
+    let unit_start = p[1] & 0x40 != 0;
+    let control = (p[3] >> 4) & 0x03;
+    let mut payload_at = 4;
+    let mut pcr = None;
+    if control & 0x02 != 0 {
+        let len = p[4] as usize;
+        if len >= 7 && p[5] & 0x10 != 0 {
+            let b = &p[6..12];
+            let base = (b[0] as u64) << 25 | (b[1] as u64) << 17 | (b[2] as u64) << 9 | (b[3] as u64) << 1
+                | (b[4] as u64) >> 7;
+            let extension = (b[4] as u64 & 0x01) << 8 | b[5] as u64;
+            pcr = Some(base * 300 + extension);
+        }
+        payload_at = 5 + len;
+    }
+    let payload = match p.get(payload_at..) {
+        Some(payload) if control & 0x01 != 0 => payload,
+        _ => &[],
+    };
+    Some(Packet { pid, unit_start, pcr, payload })
+}
+
+/// A PSI section starting in `payload`, CRC included. None if it continues in the
+/// next packet; PAT and PMT sections nearly always fit in one.
+fn section(payload: &[u8]) -> Option<&[u8]> {
+    let pointer = *payload.first()? as usize;
+    let section = payload.get(1 + pointer..)?;
+    let len = (*section.get(1)? as usize & 0x0F) << 8 | *section.get(2)? as usize;
+    section.get(..3 + len).filter(|s| s.len() >= 12)
+}
+
+struct Stream {
+    pid: u16,
+    stream_type: u8,
+    language: Option<String>,
+    /// Descriptor tags, to tell private streams (type 0x06) apart.
+    descriptors: Vec<u8>,
+}
+
+struct Program {
+    pcr_pid: u16,
+    streams: Vec<Stream>,
+}
+
+fn read_program(data: &[u8]) -> Option<Program> {
+    let pmt_pid = packets(data)
+        .filter(|p| p.pid == PAT_PID && p.unit_start)
+        .filter_map(|p| section(p.payload))
+        .filter(|s| s[0] == TABLE_PAT)
+        .find_map(|s| {
+            s[8..s.len() - 4]
+                .chunks_exact(4)
+                .find(|entry| u16::from_be_bytes([entry[0], entry[1]]) != 0)
+                .map(|entry| (entry[2] as u16 & 0x1F) << 8 | entry[3] as u16)
+        })?;
+    packets(data)
+        .filter(|p| p.pid == pmt_pid && p.unit_start)
+        .filter_map(|p| section(p.payload))
+        .find(|s| s[0] == TABLE_PMT)
+        .map(parse_pmt)
+}
+
+fn parse_pmt(s: &[u8]) -> Program {
+    let pcr_pid = (s[8] as u16 & 0x1F) << 8 | s[9] as u16;
+    let info_len = (s[10] as usize & 0x0F) << 8 | s[11] as usize;
+    let end = s.len() - 4;
+    let mut streams = Vec::new();
+    let mut i = 12 + info_len;
+    while i + 5 <= end {
+        let stream_type = s[i];
+        let pid = (s[i + 1] as u16 & 0x1F) << 8 | s[i + 2] as u16;
+        let es_info_len = (s[i + 3] as usize & 0x0F) << 8 | s[i + 4] as usize;
+        let descriptors = s.get(i + 5..(i + 5 + es_info_len).min(end)).unwrap_or_default();
+        let mut stream = Stream { pid, stream_type, language: None, descriptors: Vec::new() };
+        let mut d = 0;
+        while d + 2 <= descriptors.len() {
+            let (tag, len) = (descriptors[d], descriptors[d + 1] as usize);
+            let body = descriptors.get(d + 2..d + 2 + len).unwrap_or_default();
+            if tag == DESCRIPTOR_LANGUAGE && body.len() >= 3 {
+                let code = String::from_utf8_lossy(&body[..3]).to_ascii_lowercase();
+                stream.language = Some(code).filter(|c| c.chars().all(|c| c.is_ascii_lowercase()) && c != "und");
+            }
+            stream.descriptors.push(tag);
+            d += 2 + len;
+        }
+        streams.push(stream);
+        i += 5 + es_info_len;
+    }
+    Program { pcr_pid, streams }
+}
+
+enum Kind {
+    Video,
+    Audio,
+}
+
+/// Codec of a PMT stream, from its stream type and, for private data streams, its
+/// DVB descriptors. Types 0x80-0x86 follow the Blu-ray (HDMV) assignments.
+fn stream_codec(stream: &Stream) -> Option<(Kind, &'static str)> {
+    Some(match stream.stream_type {
+        0x01 => (Kind::Video, "mpeg1video"),
+        0x02 => (Kind::Video, "mpeg2video"),
+        0x10 => (Kind::Video, "mpeg4"),
+        0x1B => (Kind::Video, "h264"),
+        0x24 => (Kind::Video, "hevc"),
+        0xEA => (Kind::Video, "vc1"),
+        0x03 | 0x04 => (Kind::Audio, "mp2"),
+        0x0F | 0x11 => (Kind::Audio, "aac"),
+        0x80 => (Kind::Audio, "pcm"),
+        0x81 => (Kind::Audio, "ac3"),
+        0x82 | 0x85 | 0x86 => (Kind::Audio, "dts"),
+        0x83 => (Kind::Audio, "truehd"),
+        0x84 | 0x87 => (Kind::Audio, "eac3"),
+        0x06 => {
+            let has = |tag| stream.descriptors.contains(&tag);
+            if has(DESCRIPTOR_AC3) {
+                (Kind::Audio, "ac3")
+            } else if has(DESCRIPTOR_EAC3) {
+                (Kind::Audio, "eac3")
+            } else if has(DESCRIPTOR_DTS) {
+                (Kind::Audio, "dts")
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// The start of the elementary stream carried on `pid`, PES headers removed,
+/// beginning at the first PES packet.
+fn elementary_stream(data: &[u8], pid: u16) -> Vec<u8> {
+    let mut es = Vec::new();
+    let mut started = false;
+    for packet in packets(data).filter(|p| p.pid == pid) {
+        let mut payload = packet.payload;
+        if packet.unit_start {
+            started = true;
+            if payload.starts_with(&[0, 0, 1]) && payload.len() >= 9 {
+                payload = payload.get(9 + payload[8] as usize..).unwrap_or_default();
+            }
+        }
+        if started {
+            es.extend_from_slice(payload);
+            if es.len() >= MAX_VIDEO_ES {
+                break;
+            }
+        }
+    }
+    es
+}
@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::{mpsc, watch};
+
+use crate::media::{art, nfo, subtitle};
+use crate::media::filter::{IGNORE_FILE, NOMEDIA_FILE};
+use crate::media::index::MetadataIndex;
+use crate::media::library::{MediaItem, MediaLibrary};
+use crate::media::metadata::MACHINE_NAMESPACE;
+use crate::media::mime::{classify, MediaKind};
+use crate::media::scanner::{self, ScanConfig};
+use crate::media::subtitle::SidecarIndex;
+
+/// Quiet period after the last event for a path before it is re-indexed.
+/// Large copies emit a steady stream of modify events; waiting for them to stop
+/// avoids probing half-written files.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Quiet period after the last applied batch before the metadata index is rewritten,
+/// so a burst of changes costs one write.
+const INDEX_SAVE_DELAY: Duration = Duration::from_secs(30);
+
+/// How often pending paths are checked against the debounce deadline.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The roots to watch and the scan options that apply to them.
+/// Replaced as a whole when a SIGHUP reload changes either.
+#[derive(Debug, Clone)]
+pub struct WatchScope {
+    /// Media roots to watch recursively (same paths given to the scanner).
+    pub roots: Vec<PathBuf>,
+    /// Scan options, so changed files pass the same filter as in a full scan.
+    pub config: ScanConfig,
+}
+
+/// Configuration passed from main.rs to the watcher task.
+pub struct WatchConfig {
+    /// A new value (e.g. after a SIGHUP config reload) re-registers the watches.
+    pub scope: watch::Receiver<WatchScope>,
+    /// Quiet period before a changed path is processed.
+    pub debounce: Duration,
+    /// True once the initial scan has finished. Events seen before then stay pending:
+    /// a background scan owns the library until it is done.
+    pub scan_done: watch::Receiver<bool>,
+    /// Records applied changes while a SIGHUP rescan runs.
+    pub log: ChangeLog,
+}
+
+/// A change computed off the async runtime, applied under the library write lock.
+#[derive(Clone)]
+enum Change {
+    Upsert(Box<MediaItem>),
+    Remove(PathBuf),
+}
+
+/// The changes the watcher applied while a SIGHUP rescan was running. The rescan may
+/// have read a file before the watcher saw it change, so the record is replayed onto
+/// the fresh library before it replaces the live one.
+#[derive(Clone, Default)]
+pub struct ChangeLog(Arc<Mutex<Option<Vec<Change>>>>);
+
+impl ChangeLog {
+    /// Start recording, dropping anything recorded before.
+    pub fn start(&self) {
+        *self.0.lock().expect("change log poisoned") = Some(Vec::new());
+    }
+
+    /// Stop recording and drop the record.
+    pub fn stop(&self) {
+        *self.0.lock().expect("change log poisoned") = None;
+    }
+
+    /// Stop recording and apply the record to `fresh`, returning how many items it
+    /// changed. Call with the live library's write lock held so no batch lands between
+    /// the replay and the swap.
+    pub fn replay_into(&self, fresh: &mut MediaLibrary) -> usize {
+        let changes = self.0.lock().expect("change log poisoned").take().unwrap_or_default();
+        changes
+            .into_iter()
+            .map(|change| match change {
+                Change::Upsert(item) => usize::from(fresh.upsert(*item)),
+                Change::Remove(path) => fresh.remove_under(&path),
+            })
+            .sum()
+    }
+}
+
+/// Filesystem watcher async task.
+///
+/// Lifecycle:
+/// 1. Register an inotify (platform-recommended) watch on every root, and again
+///    whenever the root list changes.
+/// 2. Collect changed paths, resetting each path's deadline on every event. The
+///    watch starts before a background scan, so changes during the scan are caught.
+/// 3. Once a path has been quiet for `debounce` and the initial scan is done,
+///    re-index it on the blocking pool:
+///    files are re-extracted, directories are walked (re-extracting only new or
+///    changed files), missing or excluded paths are removed, and a changed marker
+///    file re-indexes its whole directory.
+/// 4. Apply the batch under the write lock and bump SystemUpdateID once per batch.
+/// 5. Once no batch has changed the library for `INDEX_SAVE_DELAY`, rewrite the
+///    metadata index (when one is configured).
+///
+/// Runs until the process exits. Watch failures are non-fatal: the server keeps
+/// serving the startup scan.
+pub async fn run(mut config: WatchConfig, library: Arc<RwLock<MediaLibrary>>) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+
+    let mut watcher = match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        match res {
+            Ok(event) => {
+                if is_relevant(&event.kind) {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Watcher error: {}", e),
+        }
+    }) {
+        Ok(w) => w,
+        Err(e) => {
+            tracing::warn!("Cannot start filesystem watcher — live updates disabled: {}", e);
+            return;
+        }
+    };
+
+    let mut scope = config.scope.borrow_and_update().clone();
+    let mut watched = watch_roots(&mut watcher, &[], &scope.roots);
+
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+    let mut scope_open = true;
+    // When the last batch that changed the library was applied, until the index is saved.
+    let mut unsaved: Option<Instant> = None;
+
+    loop {
+        tokio::select! {
+            path = rx.recv() => {
+                match path {
+                    Some(path) => { pending.insert(path, Instant::now()); }
+                    None => break, // watcher dropped
+                }
+            }
+            changed = config.scope.changed(), if scope_open => {
+                if changed.is_err() {
+                    // Sender dropped: the scope can no longer change; keep watching the current set.
+                    scope_open = false;
+                    continue;
+                }
+                scope = config.scope.borrow_and_update().clone();
+                watched = watch_roots(&mut watcher, &watched, &scope.roots);
+            }
+            _ = ticker.tick() => {
+                if !*config.scan_done.borrow() {
+                    continue;
+                }
+                if let (Some(since), Some(path)) = (unsaved, &scope.config.index_path) {
+                    if since.elapsed() >= INDEX_SAVE_DELAY {
+                        unsaved = None;
+                        let (library, path) = (library.clone(), path.clone());
+                        if let Err(e) = tokio::task::spawn_blocking(move || save_index(&library, &path)).await {
+                            tracing::error!("Watcher index save failed: {}", e);
+                        }
+                    }
+                }
+                let now = Instant::now();
+                let due: Vec<PathBuf> = pending
+                    .iter()
+                    .filter(|(_, last)| now.duration_since(**last) >= config.debounce)
+                    .map(|(p, _)| p.clone())
+                    .collect();
+                if due.is_empty() {
+                    continue;
+                }
+                for path in &due {
+                    pending.remove(path);
+                }
+                let roots = watched.clone();
+                let scan_config = scope.config.clone();
+                let known = library.clone();
+                let changes = match tokio::task::spawn_blocking(move || {
+                    collect_changes(due, &roots, &scan_config, &known)
+                })
+                .await
+                {
+                    Ok(c) => c,
+                    Err(e) => {
+                        tracing::error!("Watcher re-index task failed: {}", e);
+                        continue;
+                    }
+                };
+                if apply_changes(&library, changes, &config.log) {
+                    unsaved = Some(Instant::now());
+                }
+            }
+        }
+    }
+}
+
+/// Replace the watched set `old` with `roots`, returning the canonical roots now watched.
+/// Watches the canonical root so event paths line up with canonical MediaItem paths.
+fn watch_roots(
+    watcher: &mut notify::RecommendedWatcher,
+    old: &[PathBuf],
+    roots: &[PathBuf],
+) -> Vec<PathBuf> {
+    let roots: Vec<PathBuf> = roots
+        .iter()
+        .map(|r| std::fs::canonicalize(r).unwrap_or_else(|_| r.clone()))
+        .collect();
+    for root in old.iter().filter(|r| !roots.contains(r)) {
+        if let Err(e) = watcher.unwatch(root) {
+            tracing::debug!("Cannot unwatch {}: {}", root.display(), e);
+        }
+    }
+    for root in roots.iter().filter(|r| !old.contains(r)) {
+        match watcher.watch(root, RecursiveMode::Recursive) {
+            Ok(()) => tracing::info!("Watching {} for changes", root.display()),
+            Err(e) => tracing::warn!("Cannot watch {}: {}", root.display(), e),
+        }
+    }
+    roots
+}
+
+/// Only events that can change the item set are interesting; reads and opens are noise.
+fn is_relevant(kind: &EventKind) -> bool {
+    use notify::event::{AccessKind, AccessMode};
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Modify(_)
+            | EventKind::Remove(_)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    )
+}
+
+/// Turn debounced paths into library changes. Runs on the blocking pool because
+/// metadata extraction does synchronous file I/O. Walked directories only re-read
+/// files that `library` does not already hold at the same size and mtime.
+fn collect_changes(
+    paths: Vec<PathBuf>,
+    roots: &[PathBuf],
+    config: &ScanConfig,
+    library: &RwLock<MediaLibrary>,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for path in paths {
+        let Some(root) = scanner::innermost_root(roots, &path) else {
+            continue;
+        };
+        // A marker file changes what is visible in its whole directory: drop the
+        // directory's items and re-add whatever the new rules allow.
+        let is_marker = path
+            .file_name()
+            .is_some_and(|n| n == NOMEDIA_FILE || n == IGNORE_FILE);
+        if is_marker {
+            if let Some(dir) = path.parent() {
+                changes.push(Change::Remove(dir.to_path_buf()));
+                if dir.is_dir() {
+                    let items = scanner::scan_subtree(roots, root, dir, config, Some(&known_under(library, dir)));
+                    changes.extend(items.into_iter().map(|item| Change::Upsert(Box::new(item))));
+                }
+            }
+            continue;
+        }
+        // A .nfo or subtitle sidecar belongs to the video next to it (movie.nfo to the
+        // whole folder) and is never an item itself: re-read the videos beside it so
+        // they pick up the change.
+        if nfo::is_sidecar(&path) || subtitle::is_sidecar(&path) {
+            if let Some(dir) = path.parent() {
+                changes.extend(rescan_siblings(dir, &[MediaKind::Video], roots, root, config, library));
+            }
+            continue;
+        }
+        // A folder image is album art for its siblings: re-read the tracks and videos
+        // beside it so they pick up (or drop) the cover, then handle the image itself.
+        if art::is_folder_art(&path) {
+            if let Some(dir) = path.parent() {
+                let kinds = [MediaKind::Audio, MediaKind::Video];
+                changes.extend(rescan_siblings(dir, &kinds, roots, root, config, library));
+            }
+        }
+        let metadata = std::fs::metadata(&path);
+        // Files are checked against the filter in `file_change`.
+        if let Some(m) = metadata.as_ref().ok().filter(|m| !m.is_file()) {
+            let excluded = scanner::check_walk_policy(roots, root, &path, config)
+                .or_else(|| config.filter.check_path(root, &path, m.is_dir()));
+            if let Some(reason) = excluded {
+                tracing::debug!("Excluded {} ({})", path.display(), reason);
+                changes.push(Change::Remove(path));
+                continue;
+            }
+        }
+        match metadata {
+            Ok(m) if m.is_file() => {
+                let sidecars = dir_sidecars(&path, roots, root, config);
+                changes.push(file_change(path, roots, root, config, None, &sidecars));
+            }
+            Ok(m) if m.is_dir() => {
+                // A directory moved into the tree produces a single event; walk it.
+                let items = scanner::scan_subtree(roots, root, &path, config, Some(&known_under(library, &path)));
+                changes.extend(items.into_iter().map(|item| Change::Upsert(Box::new(item))));
+            }
+            Ok(_) => {}
+            Err(_) => changes.push(Change::Remove(path)),
+        }
+    }
+    changes
+}
+
+/// The change for the file at `path`: its fresh item, or its removal when it is
+/// excluded, of a kind its root does not serve, or no longer servable.
+fn file_change(
+    path: PathBuf,
+    roots: &[PathBuf],
+    root: &Path,
+    config: &ScanConfig,
+    known: Option<&MetadataIndex>,
+    sidecars: &SidecarIndex,
+) -> Change {
+    let excluded = scanner::check_walk_policy(roots, root, &path, config)
+        .or_else(|| config.filter.check_path(root, &path, false));
+    if let Some(reason) = excluded {
+        tracing::debug!("Excluded {} ({})", path.display(), reason);
+        return Change::Remove(path);
+    }
+    match scanner::scan_file(&path, &MACHINE_NAMESPACE, known, sidecars) {
+        Some(item) if config.kind_limit(root).is_some_and(|k| !k.contains(&item.kind)) => Change::Remove(path),
+        Some(item) => Change::Upsert(Box::new(item)),
+        // No longer servable (e.g. truncated or renamed to a non-media extension)
+        None => Change::Remove(path),
+    }
+}
+
+/// Re-read the files of `kinds` directly in `dir`, for a sidecar or folder image
+/// that changed beside them. Subdirectories are left alone, and unchanged files
+/// reuse their metadata: only the sidecars and folder art are looked up again.
+fn rescan_siblings(
+    dir: &Path,
+    kinds: &[MediaKind],
+    roots: &[PathBuf],
+    root: &Path,
+    config: &ScanConfig,
+    library: &RwLock<MediaLibrary>,
+) -> Vec<Change> {
+    let files = scanner::dir_files(roots, root, dir, config);
+    let sidecars = SidecarIndex::new(&files);
+    let known = known_under(library, dir);
+    files
+        .into_iter()
+        .filter(|path| classify(path).is_some_and(|(kind, _)| kinds.contains(&kind)))
+        .map(|path| file_change(path, roots, root, config, Some(&known), &sidecars))
+        .collect()
+}
+
+/// The sidecars beside the file at `path`, among the files a walk would find there.
+fn dir_sidecars(path: &Path, roots: &[PathBuf], root: &Path, config: &ScanConfig) -> SidecarIndex {
+    match path.parent() {
+        Some(dir) if matches!(classify(path), Some((MediaKind::Video, _))) => {
+            SidecarIndex::new(&scanner::dir_files(roots, root, dir, config))
+        }
+        _ => SidecarIndex::default(),
+    }
+}
+
+/// What the library holds below `dir`, as an index for rescanning it.
+fn known_under(library: &RwLock<MediaLibrary>, dir: &Path) -> MetadataIndex {
+    let lib = library.read().expect("library lock poisoned");
+    MetadataIndex::from_items(lib.items.iter().filter(|it| it.path.starts_with(dir)))
+}
+
+/// Apply a batch of changes under the write lock, bumping SystemUpdateID once if
+/// anything actually changed. The batch is added to `log` while it records.
+/// Returns whether the library changed.
+fn apply_changes(library: &RwLock<MediaLibrary>, changes: Vec<Change>, log: &ChangeLog) -> bool {
+    if changes.is_empty() {
+        return false;
+    }
+    let mut lib = library.write().expect("library lock poisoned");
+    if let Some(record) = log.0.lock().expect("change log poisoned").as_mut() {
+        record.extend(changes.iter().cloned());
+    }
+    let mut changed = 0usize;
+    for change in changes {
+        match change {
+            Change::Upsert(item) => {
+                let path = item.path.clone();
+                if lib.upsert(*item) {
+                    tracing::info!("Library updated: {}", path.display());
+                    changed += 1;
+                }
+            }
+            Change::Remove(path) => {
+                let removed = lib.remove_under(&path);
+                if removed > 0 {
+                    tracing::info!("Library removed {} item(s) under {}", removed, path.display());
+                    changed += removed;
+                }
+            }
+        }
+    }
+    if changed > 0 {
+        lib.bump_update_id();
+        tracing::debug!("SystemUpdateID now {}", lib.update_id);
+    }
+    changed > 0
+}
+
+/// Rewrite the metadata index at `path` from the library, keeping the entries it
+/// holds for roots outside the library. The items are copied so the read lock is not
+/// held while writing.
+fn save_index(library: &RwLock<MediaLibrary>, path: &Path) {
+    let (items, roots) = {
+        let lib = library.read().expect("library lock poisoned");
+        (lib.items.clone(), lib.roots.clone())
+    };
+    match MetadataIndex::load(path).save_merged(&items, &roots, path) {
+        Ok(()) => tracing::debug!("Wrote metadata index {}", path.display()),
+        Err(e) => tracing::warn!("Cannot write metadata index {}: {}", path.display(), e),
+    }
+}
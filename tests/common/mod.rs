@@ -0,0 +1,41 @@
+//! Fixtures shared by the integration tests.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A fixture file that is deleted when dropped.
+pub struct TempFile(PathBuf);
+
+impl Deref for TempFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+        // Only succeeds once the last fixture of this test binary is gone.
+        if let Some(dir) = self.0.parent() {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Write `contents` to `name` in a directory private to this test binary and process.
+/// Names must be unique within the binary, since its tests run in parallel.
+pub fn temp_file(name: &str, contents: &[u8]) -> TempFile {
+    let dir = std::env::temp_dir().join(format!("udlna-{}-{}", env!("CARGO_CRATE_NAME"), std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    TempFile(path)
+}
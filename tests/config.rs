@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 use udlna::cli::Args;
 use udlna::config::{Config, FileConfig};
+use udlna::media::filter::SymlinkPolicy;
+use udlna::media::mime::MediaKind;
 
 fn make_args(port: Option<u16>, name: Option<String>, paths: Vec<PathBuf>) -> Args {
     Args {
@@ -9,6 +11,10 @@ fn make_args(port: Option<u16>, name: Option<String>, paths: Vec<PathBuf>) -> Ar
         paths,
         config: None,
         localhost: false,
+        watch: false,
+        rebuild_index: false,
+        scan_threads: None,
+        background_scan: false,
     }
 }
 
@@ -33,7 +39,7 @@ fn test_cli_flag_overrides_default() {
 
 #[test]
 fn test_toml_overrides_default() {
-    let file = FileConfig { port: Some(7777), name: None, localhost: None };
+    let file = FileConfig { port: Some(7777), ..Default::default() };
     let args = make_args(None, None, vec![PathBuf::from("/tmp")]);
     let config = Config::resolve(Some(file), &args);
     assert_eq!(config.port, 7777);
@@ -41,7 +47,7 @@ fn test_toml_overrides_default() {
 
 #[test]
 fn test_cli_overrides_toml() {
-    let file = FileConfig { port: Some(7777), name: None, localhost: None };
+    let file = FileConfig { port: Some(7777), ..Default::default() };
     let args = make_args(Some(9000), None, vec![PathBuf::from("/tmp")]);
     let config = Config::resolve(Some(file), &args);
     assert_eq!(config.port, 9000); // CLI wins
@@ -69,3 +75,127 @@ fn test_localhost_default_false() {
     let config = Config::resolve(None, &args);
     assert!(!config.localhost, "localhost should default to false when neither CLI nor TOML sets it");
 }
+
+#[test]
+fn test_watch_from_toml() {
+    let file = FileConfig { watch: Some(true), ..Default::default() };
+    let args = make_args(None, None, vec![PathBuf::from("/tmp")]);
+    let config = Config::resolve(Some(file), &args);
+    assert!(config.watch, "watch = true in TOML should enable the watcher");
+}
+
+#[test]
+fn test_toml_paths_used_when_cli_has_none() {
+    let file = FileConfig { paths: Some(vec![PathBuf::from("/srv/media")]), ..Default::default() };
+    let args = make_args(None, None, vec![]);
+    let config = Config::resolve(Some(file), &args);
+    assert_eq!(config.paths, vec![PathBuf::from("/srv/media")]);
+}
+
+#[test]
+fn test_cli_paths_replace_toml_paths() {
+    let file = FileConfig { paths: Some(vec![PathBuf::from("/srv/media")]), ..Default::default() };
+    let args = make_args(None, None, vec![PathBuf::from("/tmp")]);
+    let config = Config::resolve(Some(file), &args);
+    assert_eq!(config.paths, vec![PathBuf::from("/tmp")]);
+}
+
+#[test]
+fn test_restart_required_changes_lists_port_and_name() {
+    let args = make_args(None, Some("A".to_string()), vec![PathBuf::from("/tmp")]);
+    let old = Config::resolve(None, &args);
+    let mut new = old.clone();
+    new.port = 9999;
+    new.paths = vec![PathBuf::from("/srv")];
+    assert_eq!(old.restart_required_changes(&new), vec!["port"]);
+}
+
+#[test]
+fn test_toml_scan_filters() {
+    let toml_str = "exclude = [\"**/Samples/**\", \"*.partial.mkv\"]\nskip_hidden = true\n";
+    let file: FileConfig = toml::from_str(toml_str).expect("should parse");
+    let args = make_args(None, None, vec![PathBuf::from("/tmp")]);
+    let config = Config::resolve(Some(file), &args);
+    assert_eq!(config.exclude, vec!["**/Samples/**", "*.partial.mkv"]);
+    assert!(config.include.is_empty());
+    assert!(config.skip_hidden);
+}
+
+#[test]
+fn test_skip_hidden_default_false() {
+    let args = make_args(None, None, vec![PathBuf::from("/tmp")]);
+    assert!(!Config::resolve(None, &args).skip_hidden);
+}
+
+#[test]
+fn test_toml_walk_policy() {
+    let toml_str = "follow_symlinks = \"never\"\nsame_filesystem = true\nmax_depth = 3\n";
+    let file: FileConfig = toml::from_str(toml_str).expect("should parse");
+    let args = make_args(None, None, vec![PathBuf::from("/tmp")]);
+    let config = Config::resolve(Some(file), &args);
+    assert_eq!(config.follow_symlinks, SymlinkPolicy::Never);
+    assert!(config.same_filesystem);
+    assert_eq!(config.max_depth, Some(3));
+    let defaults = Config::resolve(None, &args);
+    assert_eq!(defaults.follow_symlinks, SymlinkPolicy::WithinRoots);
+}
+
+#[test]
+fn test_toml_named_libraries() {
+    let toml_str = r#"
+paths = ["/srv/media"]
+
+[[library]]
+name = "Kids Movies"
+paths = ["/srv/kids"]
+kinds = ["video"]
+
+[[library]]
+name = "Everything"
+paths = ["/srv/media", "/srv/extra"]
+"#;
+    let file: FileConfig = toml::from_str(toml_str).expect("should parse");
+    let args = make_args(None, None, vec![]);
+    let config = Config::resolve(Some(file), &args);
+    assert_eq!(config.libraries.len(), 2);
+    assert_eq!(config.libraries[0].name, "Kids Movies");
+    assert_eq!(config.libraries[0].kinds, vec![MediaKind::Video]);
+    assert!(config.libraries[1].kinds.is_empty());
+    assert_eq!(
+        config.all_paths(),
+        vec![PathBuf::from("/srv/media"), PathBuf::from("/srv/kids"), PathBuf::from("/srv/extra")]
+    );
+}
+
+#[test]
+fn test_cli_paths_keep_named_libraries() {
+    let toml_str = "[[library]]\nname = \"Music\"\npaths = [\"/srv/music\"]\n";
+    let file: FileConfig = toml::from_str(toml_str).expect("should parse");
+    let args = make_args(None, None, vec![PathBuf::from("/tmp")]);
+    let config = Config::resolve(Some(file), &args);
+    assert_eq!(config.all_paths(), vec![PathBuf::from("/tmp"), PathBuf::from("/srv/music")]);
+}
+
+#[test]
+fn test_toml_title_rewrites() {
+    let toml_str = r#"
+normalize_titles = false
+
+[[title_rewrite]]
+pattern = '^\[[^\]]*\]\s*'
+
+[[title_rewrite]]
+pattern = "(?i)directors cut"
+replace = "Director's Cut"
+"#;
+    let file: FileConfig = toml::from_str(toml_str).expect("should parse");
+    let config = Config::resolve(Some(file), &make_args(None, None, vec![]));
+    assert!(!config.normalize_titles);
+    assert_eq!(config.title_rewrites.len(), 2);
+    assert_eq!(config.title_rewrites[0].replace, "", "replace defaults to removal");
+    assert_eq!(config.title_rewrites[1].replace, "Director's Cut");
+
+    let defaults = Config::resolve(None, &make_args(None, None, vec![]));
+    assert!(defaults.normalize_titles);
+    assert!(defaults.title_rewrites.is_empty());
+}
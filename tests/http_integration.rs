@@ -7,9 +7,12 @@ use http_body_util::BodyExt;
 use tower::ServiceExt;
 use uuid::Uuid;
 
-use udlna::http::{build_router, state::AppState};
-use udlna::media::library::{MediaItem, MediaLibrary, MediaMeta};
+use udlna::http::{build_router, soap, state::AppState};
+use udlna::media::filter::SymlinkPolicy;
+use udlna::media::library::{AlbumArt, AudioTrack, Chapter, MediaItem, MediaLibrary, MediaMeta, NamedLibrary};
 use udlna::media::mime::MediaKind;
+use udlna::media::nfo::Nfo;
+use udlna::media::subtitle::Subtitle;
 
 const TEST_UUID: &str = "550e8400-e29b-41d4-a716-446655440000";
 const TEST_NAME: &str = "Test DLNA Server";
@@ -21,6 +24,7 @@ fn make_app(items: Vec<MediaItem>) -> axum::Router {
         library: Arc::new(RwLock::new(library)),
         server_uuid: TEST_UUID.to_string(),
         server_name: TEST_NAME.to_string(),
+        browse_cache: Default::default(),
     };
     build_router(state)
 }
@@ -30,6 +34,7 @@ fn fake_item() -> MediaItem {
         id: Uuid::new_v5(&Uuid::NAMESPACE_DNS, b"test-video-item"),
         path: PathBuf::from("/fake/test.mp4"),
         file_size: 1_048_576,
+        modified: None,
         mime: "video/mp4",
         kind: MediaKind::Video,
         meta: MediaMeta::default(),
@@ -243,6 +248,668 @@ async fn cds_unknown_action_returns_soap_fault() {
     assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
 
+#[tokio::test]
+async fn cds_get_system_update_id_reflects_library() {
+    let mut library = MediaLibrary::new();
+    library.bump_update_id();
+    library.bump_update_id();
+    let state = AppState {
+        library: Arc::new(RwLock::new(library)),
+        server_uuid: TEST_UUID.to_string(),
+        server_name: TEST_NAME.to_string(),
+        browse_cache: Default::default(),
+    };
+    let response = build_router(state)
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/cds/control")
+                .header(
+                    "soapaction",
+                    "\"urn:schemas-upnp-org:service:ContentDirectory:1#GetSystemUpdateID\"",
+                )
+                .header("content-type", "text/xml; charset=\"utf-8\"")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    assert!(text.contains("<Id>3</Id>"), "Expected bumped SystemUpdateID:\n{text}");
+}
+
+// ── Folders view ──────────────────────────────────────────────────────────────
+
+fn folder_item(path: &str, kind: MediaKind, mime: &'static str) -> MediaItem {
+    MediaItem {
+        id: Uuid::new_v5(&Uuid::NAMESPACE_URL, path.as_bytes()),
+        path: PathBuf::from(path),
+        file_size: 1024,
+        modified: None,
+        mime,
+        kind,
+        meta: MediaMeta::default(),
+    }
+}
+
+/// Library with one populated root (/fake/root) and one media-free root (/fake/empty).
+fn make_folder_app() -> axum::Router {
+    let mut library = MediaLibrary::new();
+    library.roots = vec![PathBuf::from("/fake/root"), PathBuf::from("/fake/empty")];
+    library.items = vec![
+        folder_item("/fake/root/Movies/Action/film.mp4", MediaKind::Video, "video/mp4"),
+        folder_item("/fake/root/song.mp3", MediaKind::Audio, "audio/mpeg"),
+    ];
+    build_router(AppState {
+        library: Arc::new(RwLock::new(library)),
+        server_uuid: TEST_UUID.to_string(),
+        server_name: TEST_NAME.to_string(),
+        browse_cache: Default::default(),
+    })
+}
+
+fn browse_request(object_id: &str, flag: &str) -> Request<Body> {
+    let body = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+  <s:Body>
+    <u:Browse xmlns:u="urn:schemas-upnp-org:service:ContentDirectory:1">
+      <ObjectID>{object_id}</ObjectID>
+      <BrowseFlag>{flag}</BrowseFlag>
+      <Filter>*</Filter>
+      <StartingIndex>0</StartingIndex>
+      <RequestedCount>0</RequestedCount>
+    </u:Browse>
+  </s:Body>
+</s:Envelope>"#
+    );
+    Request::builder()
+        .method("POST")
+        .uri("/cds/control")
+        .header(
+            "soapaction",
+            "\"urn:schemas-upnp-org:service:ContentDirectory:1#Browse\"",
+        )
+        .header("content-type", "text/xml; charset=\"utf-8\"")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn folder_id(path: &str) -> String {
+    soap::folder_container_uuid(std::path::Path::new(path)).to_string()
+}
+
+#[tokio::test]
+async fn cds_browse_root_lists_folders_container() {
+    let response = make_folder_app()
+        .oneshot(browse_request("0", "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    let folders = soap::container_uuid(soap::CONTAINER_FOLDERS).to_string();
+    assert!(text.contains(&folders), "Expected Folders container in root:\n{text}");
+    assert!(text.contains("<TotalMatches>5</TotalMatches>"), "Expected 5 root containers:\n{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_folders_prunes_media_free_roots() {
+    let folders = soap::container_uuid(soap::CONTAINER_FOLDERS).to_string();
+    let response = make_folder_app()
+        .oneshot(browse_request(&folders, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    assert!(text.contains(&folder_id("/fake/root")), "Expected populated root:\n{text}");
+    assert!(!text.contains(&folder_id("/fake/empty")), "Empty root must be pruned:\n{text}");
+    assert!(text.contains("<TotalMatches>1</TotalMatches>"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_folder_lists_subdirectories_then_items() {
+    let root = folder_id("/fake/root");
+    let response = make_folder_app()
+        .oneshot(browse_request(&root, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    let movies = text.find(&folder_id("/fake/root/Movies")).expect("Movies folder missing");
+    let song = text.find("song").expect("song item missing");
+    assert!(movies < song, "Subdirectories must precede files:\n{text}");
+    assert!(text.contains(&format!("parentID=&quot;{root}&quot;")), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_metadata_on_nested_folder() {
+    let action = folder_id("/fake/root/Movies/Action");
+    let response = make_folder_app()
+        .oneshot(browse_request(&action, "BrowseMetadata"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    assert!(text.contains("<NumberReturned>1</NumberReturned>"), "{text}");
+    let parent = folder_id("/fake/root/Movies");
+    assert!(text.contains(&format!("parentID=&quot;{parent}&quot;")), "Wrong parentID:\n{text}");
+    assert!(text.contains("childCount=&quot;1&quot;"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_metadata_on_item_reports_its_folder() {
+    let film = Uuid::new_v5(&Uuid::NAMESPACE_URL, b"/fake/root/Movies/Action/film.mp4").to_string();
+    let response = make_folder_app()
+        .oneshot(browse_request(&film, "BrowseMetadata"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    let parent = folder_id("/fake/root/Movies/Action");
+    assert!(text.contains(&format!("parentID=&quot;{parent}&quot;")), "Wrong parentID:\n{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_unknown_folder_returns_fault() {
+    let response = make_folder_app()
+        .oneshot(browse_request(&folder_id("/fake/empty"), "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+// ── Named libraries ───────────────────────────────────────────────────────────
+
+fn make_library_app() -> axum::Router {
+    let mut library = MediaLibrary::new();
+    library.roots = vec![PathBuf::from("/fake/kids"), PathBuf::from("/fake/root")];
+    library.items = vec![
+        folder_item("/fake/kids/Cartoons/toon.mp4", MediaKind::Video, "video/mp4"),
+        folder_item("/fake/kids/lullaby.mp3", MediaKind::Audio, "audio/mpeg"),
+        folder_item("/fake/root/song.mp3", MediaKind::Audio, "audio/mpeg"),
+    ];
+    library.libraries = vec![
+        NamedLibrary {
+            name: "Kids Movies".to_string(),
+            paths: vec![PathBuf::from("/fake/kids")],
+            kinds: vec![MediaKind::Video],
+        },
+        NamedLibrary {
+            name: "Everything".to_string(),
+            paths: vec![PathBuf::from("/fake/kids"), PathBuf::from("/fake/root")],
+            kinds: vec![],
+        },
+    ];
+    build_router(AppState {
+        library: Arc::new(RwLock::new(library)),
+        server_uuid: TEST_UUID.to_string(),
+        server_name: TEST_NAME.to_string(),
+        browse_cache: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn cds_browse_root_lists_named_libraries_after_folders() {
+    let response = make_library_app()
+        .oneshot(browse_request("0", "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    assert!(text.contains("<TotalMatches>7</TotalMatches>"), "Expected 7 root containers:\n{text}");
+    let folders = text.find(&soap::container_uuid(soap::CONTAINER_FOLDERS).to_string()).unwrap();
+    let kids = text.find(&soap::library_container_uuid("Kids Movies").to_string()).unwrap();
+    let everything = text.find(&soap::library_container_uuid("Everything").to_string()).unwrap();
+    assert!(folders < kids && kids < everything, "Libraries follow Folders in config order:\n{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_single_path_library_inlines_root_and_filters_kinds() {
+    let kids = soap::library_container_uuid("Kids Movies").to_string();
+    let response = make_library_app()
+        .oneshot(browse_request(&kids, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    let cartoons = soap::library_folder_uuid("Kids Movies", std::path::Path::new("/fake/kids/Cartoons"));
+    assert!(text.contains(&cartoons.to_string()), "Expected inlined subfolder:\n{text}");
+    assert!(!text.contains("lullaby"), "Audio must be filtered from a video library:\n{text}");
+    assert!(text.contains("<TotalMatches>1</TotalMatches>"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_multi_path_library_lists_each_root() {
+    let everything = soap::library_container_uuid("Everything").to_string();
+    let response = make_library_app()
+        .oneshot(browse_request(&everything, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = body_text(response).await;
+    for root in ["/fake/kids", "/fake/root"] {
+        let id = soap::library_folder_uuid("Everything", std::path::Path::new(root));
+        assert!(text.contains(&id.to_string()), "Expected root container for {root}:\n{text}");
+    }
+    assert!(!text.contains(&folder_id("/fake/kids")), "Library folders need their own IDs:\n{text}");
+}
+
+// ── Audio tags ────────────────────────────────────────────────────────────────
+
+/// Undo the escaping of the DIDL-Lite document embedded in a Browse `<Result>`.
+fn didl(text: String) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+async fn browse_music(items: Vec<MediaItem>) -> String {
+    let music = soap::container_uuid(soap::CONTAINER_MUSIC).to_string();
+    let response = make_app(items)
+        .oneshot(browse_request(&music, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    body_text(response).await
+}
+
+#[tokio::test]
+async fn cds_browse_emits_audio_tags() {
+    let mut item = folder_item("/fake/music/01 - track.flac", MediaKind::Audio, "audio/flac");
+    item.meta = MediaMeta {
+        title: Some("Rock & Roll".to_string()),
+        artist: Some("Led Zeppelin".to_string()),
+        album: Some("IV".to_string()),
+        genre: Some("Rock".to_string()),
+        track: Some(4),
+        year: Some(1971),
+        composer: Some("Page".to_string()),
+        ..Default::default()
+    };
+    let text = didl(browse_music(vec![item]).await);
+    assert!(text.contains("<dc:title>Rock &amp; Roll</dc:title>"), "{text}");
+    assert!(text.contains("<dc:creator>Led Zeppelin</dc:creator>"), "{text}");
+    assert!(text.contains("<upnp:artist>Led Zeppelin</upnp:artist>"), "{text}");
+    assert!(text.contains("<upnp:album>IV</upnp:album>"), "{text}");
+    assert!(text.contains("<upnp:genre>Rock</upnp:genre>"), "{text}");
+    assert!(text.contains("<upnp:originalTrackNumber>4</upnp:originalTrackNumber>"), "{text}");
+    assert!(text.contains(r#"<upnp:author role="Composer">Page</upnp:author>"#), "{text}");
+    assert!(text.contains("<dc:date>1971-01-01</dc:date>"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_untagged_audio_falls_back_to_file_stem() {
+    let item = folder_item("/fake/music/01 - track.flac", MediaKind::Audio, "audio/flac");
+    let text = didl(browse_music(vec![item]).await);
+    assert!(text.contains("<dc:title>01 - track</dc:title>"), "{text}");
+    assert!(!text.contains("upnp:artist"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_emits_audio_res_attributes() {
+    let mut item = folder_item("/fake/music/01 - track.flac", MediaKind::Audio, "audio/flac");
+    item.meta = MediaMeta {
+        duration: Some("00:03:00.000".to_string()),
+        bitrate: Some(110_250),
+        sample_rate: Some(44100),
+        channels: Some(2),
+        bits_per_sample: Some(16),
+        ..Default::default()
+    };
+    let text = didl(browse_music(vec![item]).await);
+    let attrs = r#"duration="00:03:00.000" bitrate="110250" sampleFrequency="44100" nrAudioChannels="2""#;
+    assert!(text.contains(&format!(r#"{attrs} bitsPerSample="16">"#)), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_emits_video_audio_track_details() {
+    let mut item = folder_item("/fake/movies/film.mkv", MediaKind::Video, "video/x-matroska");
+    let audio = |codec: &str, language: Option<&str>, channels| AudioTrack {
+        codec: codec.to_string(),
+        language: language.map(str::to_string),
+        channels: Some(channels),
+    };
+    item.meta = MediaMeta {
+        video_codec: Some("hevc".to_string()),
+        video_bit_depth: Some(10),
+        transfer: Some("smpte2084".to_string()),
+        audio_tracks: vec![audio("eac3", Some("eng"), 6), audio("aac", Some("eng"), 2), audio("ac3", Some("fre"), 6)],
+        ..Default::default()
+    };
+    let videos = soap::container_uuid(soap::CONTAINER_VIDEOS).to_string();
+    let response = make_app(vec![item])
+        .oneshot(browse_request(&videos, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = didl(body_text(response).await);
+    assert!(text.contains(r#"nrAudioChannels="6""#), "{text}");
+    assert!(text.contains("<dc:language>eng</dc:language><dc:language>fre</dc:language><upnp:class>"), "{text}");
+    assert!(!text.contains("smpte2084"), "no standard property for the transfer: {text}");
+}
+
+#[tokio::test]
+async fn cds_browse_emits_nfo_fields() {
+    let mut item = folder_item("/fake/movies/the.matrix.1999.mkv", MediaKind::Video, "video/x-matroska");
+    item.meta = MediaMeta {
+        title: Some("tag title".to_string()),
+        genre: Some("tag genre".to_string()),
+        nfo: Some(Nfo {
+            title: Some("The Matrix".to_string()),
+            plot: Some("A hacker learns the truth.".to_string()),
+            year: Some(1999),
+            genres: vec!["Action".to_string(), "Sci-Fi".to_string()],
+            rating: Some("R".to_string()),
+            directors: vec!["Lana Wachowski".to_string()],
+            actors: vec!["Keanu Reeves".to_string(), "Carrie-Anne Moss".to_string()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let videos = soap::container_uuid(soap::CONTAINER_VIDEOS).to_string();
+    let response = make_app(vec![item])
+        .oneshot(browse_request(&videos, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = didl(body_text(response).await);
+    assert!(text.contains("<dc:title>The Matrix</dc:title>"), "{text}");
+    assert!(text.contains("<upnp:genre>Action</upnp:genre><upnp:genre>Sci-Fi</upnp:genre>"), "{text}");
+    assert!(!text.contains("tag genre"), "{text}");
+    assert!(text.contains("<dc:description>A hacker learns the truth.</dc:description>"), "plot fallback: {text}");
+    assert!(text.contains("<upnp:longDescription>A hacker learns the truth.</upnp:longDescription>"), "{text}");
+    assert!(text.contains("<upnp:rating>R</upnp:rating>"), "{text}");
+    assert!(text.contains("<upnp:director>Lana Wachowski</upnp:director>"), "{text}");
+    assert!(text.contains("<upnp:actor>Keanu Reeves</upnp:actor><upnp:actor>Carrie-Anne Moss</upnp:actor>"), "{text}");
+    assert!(text.contains("<dc:date>1999-01-01</dc:date>"), "{text}");
+}
+
+// ── Music hierarchy ───────────────────────────────────────────────────────────
+
+fn track(path: &str, artist: Option<&str>, album: &str, disc: u32, number: u32) -> MediaItem {
+    let mut item = folder_item(path, MediaKind::Audio, "audio/flac");
+    item.meta = MediaMeta {
+        title: Some(path.rsplit('/').next().unwrap().to_string()),
+        artist: artist.map(str::to_string),
+        album: Some(album.to_string()),
+        genre: Some("Jazz".to_string()),
+        disc: Some(disc),
+        track: Some(number),
+        year: Some(1959),
+        ..Default::default()
+    };
+    item
+}
+
+fn make_music_app() -> axum::Router {
+    make_app(vec![
+        track("/fake/music/so-what", Some("Miles Davis"), "Kind of Blue", 1, 1),
+        track("/fake/music/bonus", Some("miles davis"), "Kind of Blue", 2, 1),
+        track("/fake/music/blue-in-green", Some("Miles Davis"), "Kind of Blue", 1, 3),
+        track("/fake/music/freddie", Some("Miles Davis"), "Kind of Blue", 1, 2),
+        track("/fake/music/mystery", None, "Bootleg", 1, 1),
+    ])
+}
+
+async fn browse_children(app: axum::Router, id: &str) -> String {
+    let response = app.oneshot(browse_request(id, "BrowseDirectChildren")).await.unwrap();
+    didl(body_text(response).await)
+}
+
+#[tokio::test]
+async fn cds_browse_music_lists_views_before_tracks() {
+    let music = soap::container_uuid(soap::CONTAINER_MUSIC).to_string();
+    let text = browse_children(make_music_app(), &music).await;
+    let mut last = 0;
+    for view in [
+        soap::CONTAINER_ARTISTS,
+        soap::CONTAINER_ALBUM_ARTISTS,
+        soap::CONTAINER_ALBUMS,
+        soap::CONTAINER_GENRES,
+        soap::CONTAINER_YEARS,
+    ] {
+        let id = soap::music_container_uuid(&[view]).to_string();
+        let at = text.find(&id).unwrap_or_else(|| panic!("{view} missing:\n{text}"));
+        assert!(at > last, "{view} out of order:\n{text}");
+        last = at;
+    }
+    assert!(text.find("<item").unwrap() > last, "Tracks follow the views:\n{text}");
+    assert!(text.contains("<TotalMatches>10</TotalMatches>"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_artists_groups_case_insensitively_with_unknown_last() {
+    let artists = soap::music_container_uuid(&[soap::CONTAINER_ARTISTS]).to_string();
+    let text = browse_children(make_music_app(), &artists).await;
+    assert!(text.contains("<TotalMatches>2</TotalMatches>"), "{text}");
+    let miles = soap::music_container_uuid(&[soap::CONTAINER_ARTISTS, "miles davis"]).to_string();
+    let miles_at = text.find(&miles).expect("Miles Davis missing");
+    let unknown_at = text.find("Unknown Artist").expect("Unknown Artist missing");
+    assert!(miles_at < unknown_at, "{text}");
+    assert!(text.contains("<dc:title>Miles Davis</dc:title>"), "First spelling wins:\n{text}");
+    assert!(text.contains("object.container.person.musicArtist"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_album_orders_tracks_by_disc_and_number() {
+    let album = soap::music_container_uuid(&[soap::CONTAINER_ARTISTS, "miles davis", "kind of blue"]);
+    let text = browse_children(make_music_app(), &album.to_string()).await;
+    let order: Vec<usize> = ["so-what", "freddie", "blue-in-green", "bonus"]
+        .iter()
+        .map(|t| text.find(&format!("<dc:title>{t}</dc:title>")).expect(t))
+        .collect();
+    assert!(order.windows(2).all(|w| w[0] < w[1]), "Wrong track order:\n{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_genre_and_year_views() {
+    let genre = soap::music_container_uuid(&[soap::CONTAINER_GENRES, "jazz"]).to_string();
+    let genres = soap::music_container_uuid(&[soap::CONTAINER_GENRES]).to_string();
+    let text = browse_children(make_music_app(), &genres).await;
+    assert!(text.contains(&genre), "{text}");
+    assert!(text.contains("object.container.genre.musicGenre"), "{text}");
+    let year = soap::music_container_uuid(&[soap::CONTAINER_YEARS, "1959"]).to_string();
+    let text = browse_children(make_music_app(), &year).await;
+    assert!(text.contains("<TotalMatches>5</TotalMatches>"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_album_and_tracks_advertise_album_art() {
+    let mut with_art = track("/fake/music/freddie", Some("Miles Davis"), "Kind of Blue", 1, 2);
+    with_art.meta.art = Some(AlbumArt {
+        mime: "image/jpeg".to_string(),
+        width: 400,
+        height: 400,
+        path: Some(PathBuf::from("/fake/music/cover.jpg")),
+    });
+    let plain = track("/fake/music/so-what", Some("Miles Davis"), "Kind of Blue", 1, 1);
+    let art_url = format!("http://localhost:8200/art/{}", with_art.id);
+    let app = make_app(vec![plain, with_art]);
+
+    let artist = soap::music_container_uuid(&[soap::CONTAINER_ARTISTS, "miles davis"]).to_string();
+    let text = browse_children(app.clone(), &artist).await;
+    let expected = format!(r#"<upnp:albumArtURI dlna:profileID="JPEG_SM">{art_url}</upnp:albumArtURI>"#);
+    assert!(text.contains(&expected), "Album container needs art:\n{text}");
+
+    let album = soap::music_container_uuid(&[soap::CONTAINER_ARTISTS, "miles davis", "kind of blue"]);
+    let text = browse_children(app, &album.to_string()).await;
+    assert_eq!(text.matches("upnp:albumArtURI dlna").count(), 1, "Only the track with art:\n{text}");
+    assert!(text.contains(&expected), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_compilation_keeps_track_order() {
+    let mut tracks = vec![
+        track("/fake/music/second", Some("Zappa"), "Jazz Hits", 1, 2),
+        track("/fake/music/first", Some("Coltrane"), "Jazz Hits", 1, 1),
+        track("/fake/music/third", Some("Armstrong"), "Jazz Hits", 1, 3),
+    ];
+    for t in &mut tracks {
+        t.meta.album_artist = Some("Various Artists".to_string());
+    }
+    let album = soap::music_container_uuid(&[soap::CONTAINER_ALBUMS, "jazz hits"]);
+    let text = browse_children(make_app(tracks), &album.to_string()).await;
+    let order: Vec<usize> = ["first", "second", "third"]
+        .iter()
+        .map(|t| text.find(&format!("<dc:title>{t}</dc:title>")).expect(t))
+        .collect();
+    assert!(order.windows(2).all(|w| w[0] < w[1]), "Wrong track order:\n{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_same_named_albums_stay_apart() {
+    let app = make_app(vec![
+        track("/fake/music/queen", Some("Queen"), "Greatest Hits", 1, 1),
+        track("/fake/music/abba", Some("ABBA"), "Greatest Hits", 1, 1),
+    ]);
+    let albums = soap::music_container_uuid(&[soap::CONTAINER_ALBUMS]).to_string();
+    let text = browse_children(app.clone(), &albums).await;
+    assert!(text.contains("<TotalMatches>2</TotalMatches>"), "{text}");
+    assert!(text.contains("<dc:title>Greatest Hits (ABBA)</dc:title>"), "{text}");
+    assert!(text.contains("<dc:title>Greatest Hits (Queen)</dc:title>"), "{text}");
+    let queen = soap::music_container_uuid(&[soap::CONTAINER_ALBUMS, "queen", "greatest hits"]);
+    let text = browse_children(app, &queen.to_string()).await;
+    assert!(text.contains("<TotalMatches>1</TotalMatches>"), "{text}");
+    assert!(text.contains("<dc:title>queen</dc:title>"), "{text}");
+}
+
+// ── Chapters ──────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn cds_browse_lists_chapters_after_the_item() {
+    let mut book = folder_item("/fake/root/Dune.m4b", MediaKind::Audio, "audio/mp4");
+    book.meta.duration = Some("01:00:00.000".to_string());
+    book.meta.chapters = vec![
+        Chapter { start: 0.0, title: Some("Prologue".to_string()) },
+        Chapter { start: 754.5, title: None },
+    ];
+    let single = folder_item("/fake/root/short.m4b", MediaKind::Audio, "audio/mp4");
+    let mut library = MediaLibrary::new();
+    library.roots = vec![PathBuf::from("/fake/root")];
+    library.items = vec![book.clone(), single];
+    library.items[1].meta.chapters = vec![Chapter { start: 0.0, title: None }];
+    let app = build_router(AppState {
+        library: Arc::new(RwLock::new(library)),
+        server_uuid: TEST_UUID.to_string(),
+        server_name: TEST_NAME.to_string(),
+        browse_cache: Default::default(),
+    });
+
+    let root = soap::folder_container_uuid(std::path::Path::new("/fake/root")).to_string();
+    let text = browse_children(app.clone(), &root).await;
+    let chapters = soap::chapters_container_uuid(&book.id).to_string();
+    let container = format!(r#"<container id="{chapters}" parentID="{root}" restricted="1" childCount="2">"#);
+    assert!(text.contains(&container), "Chapter list missing:\n{text}");
+    assert!(text.contains("<dc:title>Dune - Chapters</dc:title>"), "{text}");
+    assert_eq!(text.matches("<container").count(), 1, "One chapter is no list:\n{text}");
+    assert!(text.find(&book.id.to_string()).unwrap() < text.find(&chapters).unwrap(), "{text}");
+
+    let text = browse_children(app.clone(), &chapters).await;
+    let url = format!("http://localhost:8200/media/{}", book.id);
+    let first = soap::chapter_uuid(&book.id, 0);
+    assert!(text.contains(&format!(r#"<item id="{first}" parentID="{chapters}""#)), "{text}");
+    assert!(text.contains("<dc:title>Prologue</dc:title><upnp:class>object.item.audioItem</upnp:class>"), "{text}");
+    assert!(text.contains(&format!(r#"size="1024" duration="00:12:34.500">{url}</res>"#)), "{text}");
+    assert!(text.contains("<dc:title>Chapter 2</dc:title>"), "{text}");
+    assert!(text.contains("<sec:dcmInfo>BM=754</sec:dcmInfo>"), "Start offset missing:\n{text}");
+    assert!(text.contains(r#"duration="00:47:25.500""#), "Last chapter runs to the end:\n{text}");
+
+    let second = soap::chapter_uuid(&book.id, 1).to_string();
+    let response = app.oneshot(browse_request(&second, "BrowseMetadata")).await.unwrap();
+    let text = didl(body_text(response).await);
+    assert!(text.contains(&format!(r#"<item id="{second}" parentID="{chapters}""#)), "{text}");
+}
+
+// ── TV shows ──────────────────────────────────────────────────────────────────
+
+fn make_tv_app() -> axum::Router {
+    make_app(vec![
+        folder_item("/fake/tv/Show.Name.S01E10.Finale.720p.mkv", MediaKind::Video, "video/x-matroska"),
+        folder_item("/fake/tv/Show.Name.S01E02.Second.720p.mkv", MediaKind::Video, "video/x-matroska"),
+        folder_item("/fake/tv/show name - 2x01 - Return.mkv", MediaKind::Video, "video/x-matroska"),
+        folder_item("/fake/tv/Another.Show.S00E01.mkv", MediaKind::Video, "video/x-matroska"),
+        folder_item("/fake/movies/The.Movie.2019.mkv", MediaKind::Video, "video/x-matroska"),
+    ])
+}
+
+#[tokio::test]
+async fn cds_browse_videos_lists_tv_shows_before_videos() {
+    let videos = soap::container_uuid(soap::CONTAINER_VIDEOS).to_string();
+    let text = browse_children(make_tv_app(), &videos).await;
+    let shows = soap::video_container_uuid(&[soap::CONTAINER_TV_SHOWS]).to_string();
+    let shows_at = text.find(&shows).unwrap_or_else(|| panic!("TV Shows missing:\n{text}"));
+    assert!(shows_at < text.find("<item").unwrap(), "{text}");
+    assert!(text.contains("<TotalMatches>6</TotalMatches>"), "{text}");
+    let movie = "<dc:title>The Movie</dc:title><upnp:class>object.item.videoItem</upnp:class><dc:date>2019-01-01";
+    assert!(text.contains(movie), "{text}");
+
+    let text = browse_children(make_tv_app(), &shows).await;
+    let another = text.find("<dc:title>Another Show</dc:title>").expect("Another Show missing");
+    let show = text.find("<dc:title>Show Name</dc:title>").expect("first spelling wins");
+    assert!(another < show, "{text}");
+    assert!(text.contains("object.container.album.videoAlbum"), "{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_season_orders_episodes_by_number() {
+    let series = soap::video_container_uuid(&[soap::CONTAINER_TV_SHOWS, "show name"]).to_string();
+    let text = browse_children(make_tv_app(), &series).await;
+    let one = text.find("<dc:title>Season 1</dc:title>").expect("Season 1 missing");
+    let two = text.find("<dc:title>Season 2</dc:title>").expect("Season 2 missing");
+    assert!(one < two, "{text}");
+
+    let season = soap::video_container_uuid(&[soap::CONTAINER_TV_SHOWS, "show name", "1"]).to_string();
+    let text = browse_children(make_tv_app(), &season).await;
+    let second = text.find("<dc:title>S01E02 - Second</dc:title>").expect("S01E02 missing");
+    let finale = text.find("<dc:title>S01E10 - Finale</dc:title>").expect("S01E10 missing");
+    assert!(second < finale, "{text}");
+    assert!(text.contains("<upnp:class>object.item.videoItem.videoBroadcast</upnp:class>"), "{text}");
+    let episode = "<upnp:seriesTitle>Show Name</upnp:seriesTitle><upnp:episodeSeason>1</upnp:episodeSeason>\
+                   <upnp:episodeNumber>2</upnp:episodeNumber>";
+    assert!(text.contains(episode), "{text}");
+
+    let specials = soap::video_container_uuid(&[soap::CONTAINER_TV_SHOWS, "another show"]).to_string();
+    let text = browse_children(make_tv_app(), &specials).await;
+    assert!(text.contains("<dc:title>Specials</dc:title>"), "{text}");
+}
+
+// ── Photos by date ────────────────────────────────────────────────────────────
+
+fn photo(path: &str, taken: &str) -> MediaItem {
+    let mut item = folder_item(path, MediaKind::Image, "image/jpeg");
+    item.meta.taken = Some(taken.to_string());
+    item
+}
+
+fn make_photo_app() -> axum::Router {
+    make_app(vec![
+        photo("/fake/photos/late.jpg", "2023-01-15T18:00:00"),
+        photo("/fake/photos/early.jpg", "2023-01-15T08:00:00"),
+        photo("/fake/photos/feb.jpg", "2023-02-01T12:00:00"),
+        photo("/fake/photos/old.jpg", "2019-07-04T18:30:05"),
+    ])
+}
+
+#[tokio::test]
+async fn cds_browse_photos_lists_years_then_photos() {
+    let photos = soap::container_uuid(soap::CONTAINER_PHOTOS).to_string();
+    let text = browse_children(make_photo_app(), &photos).await;
+    let y2019 = text.find(&soap::photo_container_uuid(&["2019"]).to_string()).expect("2019 missing");
+    let y2023 = text.find(&soap::photo_container_uuid(&["2023"]).to_string()).expect("2023 missing");
+    assert!(y2019 < y2023 && y2023 < text.find("<item").unwrap(), "{text}");
+    assert!(text.contains("<TotalMatches>6</TotalMatches>"), "{text}");
+    assert!(text.contains("<dc:date>2019-07-04T18:30:05</dc:date>"), "Capture time as dc:date:\n{text}");
+}
+
+#[tokio::test]
+async fn cds_browse_photo_day_orders_by_capture_time() {
+    let year = soap::photo_container_uuid(&["2023"]).to_string();
+    let text = browse_children(make_photo_app(), &year).await;
+    assert!(text.contains("<dc:title>2023-01</dc:title>"), "{text}");
+    assert!(text.contains("<dc:title>2023-02</dc:title>"), "{text}");
+
+    let day = soap::photo_container_uuid(&["2023", "01", "15"]).to_string();
+    let response = make_photo_app().oneshot(browse_request(&day, "BrowseMetadata")).await.unwrap();
+    let text = didl(body_text(response).await);
+    assert!(text.contains("object.container.album.photoAlbum"), "{text}");
+
+    let text = browse_children(make_photo_app(), &day).await;
+    assert!(text.contains("<TotalMatches>2</TotalMatches>"), "{text}");
+    let early = text.find("early").expect("early missing");
+    let late = text.find("late").expect("late missing");
+    assert!(early < late, "{text}");
+}
+
 // ── POST /cms/control ─────────────────────────────────────────────────────────
 
 #[tokio::test]
@@ -302,3 +969,233 @@ async fn media_unknown_id_returns_404() {
         .unwrap();
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
+
+#[cfg(unix)]
+#[tokio::test]
+async fn media_refuses_file_swapped_for_outside_symlink() {
+    let base = std::env::temp_dir().join(format!("udlna-serve-{}", std::process::id()));
+    let root = base.join("root");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(base.join("outside.mp4"), b"secret").unwrap();
+    std::fs::write(root.join("inside.mp4"), b"public").unwrap();
+    let _ = std::os::unix::fs::symlink(base.join("outside.mp4"), root.join("swapped.mp4"));
+    let root = std::fs::canonicalize(&root).unwrap();
+
+    let item = |name: &str| MediaItem {
+        id: Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes()),
+        path: root.join(name),
+        file_size: 6,
+        modified: None,
+        mime: "video/mp4",
+        kind: MediaKind::Video,
+        meta: MediaMeta::default(),
+    };
+    let (inside, swapped) = (item("inside.mp4"), item("swapped.mp4"));
+    let mut library = MediaLibrary::new();
+    library.roots = vec![root.clone()];
+    library.symlinks = SymlinkPolicy::WithinRoots;
+    library.items = vec![inside.clone(), swapped.clone()];
+    let app = build_router(AppState {
+        library: Arc::new(RwLock::new(library)),
+        server_uuid: TEST_UUID.to_string(),
+        server_name: TEST_NAME.to_string(),
+        browse_cache: Default::default(),
+    });
+
+    let get = |id: Uuid| Request::builder().uri(format!("/media/{id}")).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(get(inside.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_text(response).await, "public");
+    let response = app.oneshot(get(swapped.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+/// App serving one 1000-byte, 100-second file of `mime` from a temp directory.
+fn make_seek_app(name: &str, mime: &'static str) -> (axum::Router, Uuid) {
+    let dir = std::env::temp_dir().join(format!("udlna-seek-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let bytes: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(dir.join(name), bytes).unwrap();
+    let mut item = folder_item(&dir.join(name).to_string_lossy(), MediaKind::Audio, mime);
+    item.file_size = 1000;
+    item.meta.duration = Some("00:01:40.000".to_string());
+    let id = item.id;
+    (make_app(vec![item]), id)
+}
+
+fn time_seek(method: &str, id: Uuid, npt: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(format!("/media/{id}"))
+        .header("TimeSeekRange.dlna.org", npt)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn media_time_seek_serves_mapped_bytes() {
+    let (app, id) = make_seek_app("song.mp3", "audio/mpeg");
+    let response = app.oneshot(time_seek("GET", id, "npt=25-50")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let features = response.headers()["contentfeatures.dlna.org"].to_str().unwrap();
+    assert!(features.starts_with("DLNA.ORG_OP=11;"), "{features}");
+    assert_eq!(
+        response.headers()["timeseekrange.dlna.org"],
+        "npt=00:00:25.000-00:00:50.000/00:01:40.000 bytes=250-499/1000"
+    );
+    assert_eq!(response.headers()["content-length"], "250");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body.len(), 250);
+    assert_eq!(body[0], 250, "byte 250 of the file");
+}
+
+#[tokio::test]
+async fn media_time_seek_head_reports_range_without_body() {
+    let (app, id) = make_seek_app("head.mp3", "audio/mpeg");
+    let response = app.oneshot(time_seek("HEAD", id, "npt=0:01:30-")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["timeseekrange.dlna.org"],
+        "npt=00:01:30.000-00:01:40.000/00:01:40.000 bytes=900-999/1000"
+    );
+    assert!(response.into_body().collect().await.unwrap().to_bytes().is_empty());
+}
+
+#[tokio::test]
+async fn media_time_seek_rejects_unsupported_and_invalid_ranges() {
+    let (app, id) = make_seek_app("clip.wav", "audio/wav");
+    let response = app.oneshot(time_seek("GET", id, "npt=10-")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE, "WAV cannot start mid-file");
+
+    let (app, id) = make_seek_app("movie.mp4", "video/mp4");
+    let request = Request::builder().uri(format!("/media/{id}")).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let features = response.headers()["contentfeatures.dlna.org"].to_str().unwrap();
+    assert!(features.starts_with("DLNA.ORG_OP=01;"), "MP4 is byte-seeked: {features}");
+    let response = app.oneshot(time_seek("GET", id, "npt=10-")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE, "MP4 needs its moov box");
+
+    let (app, id) = make_seek_app("range.mp3", "audio/mpeg");
+    let response = app.clone().oneshot(time_seek("GET", id, "npt=200-")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    let response = app.oneshot(time_seek("GET", id, "bytes=0-")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// ── Subtitles ─────────────────────────────────────────────────────────────────
+
+/// A video with an English SRT sidecar, both in a temp directory.
+fn subtitled_video() -> (MediaItem, Subtitle) {
+    let dir = std::env::temp_dir().join(format!("udlna-subs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("film.mkv"), b"video").unwrap();
+    std::fs::write(dir.join("film.en.srt"), "1\n00:00:01,000 --> 00:00:02,000\nHello\n").unwrap();
+    let subtitle = Subtitle {
+        id: Uuid::new_v5(&Uuid::NAMESPACE_URL, b"film.en.srt"),
+        path: dir.join("film.en.srt"),
+        mime: "text/srt",
+        language: Some("en".to_string()),
+    };
+    let mut item = folder_item(&dir.join("film.mkv").to_string_lossy(), MediaKind::Video, "video/x-matroska");
+    item.file_size = 5;
+    item.meta.subtitles = vec![subtitle.clone()];
+    (item, subtitle)
+}
+
+#[tokio::test]
+async fn cds_browse_advertises_subtitles() {
+    let (item, subtitle) = subtitled_video();
+    let videos = soap::container_uuid(soap::CONTAINER_VIDEOS).to_string();
+    let response = make_app(vec![item])
+        .oneshot(browse_request(&videos, "BrowseDirectChildren"))
+        .await
+        .unwrap();
+    let text = didl(body_text(response).await);
+    let url = format!("http://localhost:8200/subtitle/{}", subtitle.id);
+    assert!(text.contains(r#"xmlns:sec="http://www.sec.co.kr/""#), "{text}");
+    assert!(text.contains(&format!(r#"<sec:CaptionInfoEx sec:type="srt">{url}</sec:CaptionInfoEx>"#)), "{text}");
+    assert!(text.contains(&format!(r#"</res><res protocolInfo="http-get:*:text/srt:*">{url}</res></item>"#)), "{text}");
+}
+
+#[tokio::test]
+async fn subtitle_route_serves_sidecar() {
+    let (item, subtitle) = subtitled_video();
+    let app = make_app(vec![item]);
+    let get = |id: Uuid| Request::builder().uri(format!("/subtitle/{id}")).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(get(subtitle.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/srt");
+    assert!(body_text(response).await.contains("Hello"));
+    let response = app.oneshot(get(Uuid::new_v4())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn media_announces_caption_info_when_asked() {
+    let (item, subtitle) = subtitled_video();
+    let plain = fake_item();
+    let app = make_app(vec![item.clone(), plain.clone()]);
+    let request = |method: &str, id: Uuid, ask: bool| {
+        let builder = Request::builder().method(method).uri(format!("/media/{id}")).header("host", "tv.local:8200");
+        let builder = if ask { builder.header("getCaptionInfo.sec", "1") } else { builder };
+        builder.body(Body::empty()).unwrap()
+    };
+    let expected = format!("http://tv.local:8200/subtitle/{}", subtitle.id);
+    for method in ["GET", "HEAD"] {
+        let response = app.clone().oneshot(request(method, item.id, true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["captioninfo.sec"], expected.as_str(), "{method}");
+    }
+    let response = app.clone().oneshot(request("HEAD", item.id, false)).await.unwrap();
+    assert!(response.headers().get("captioninfo.sec").is_none(), "not asked");
+    let response = app.oneshot(request("HEAD", plain.id, true)).await.unwrap();
+    assert!(response.headers().get("captioninfo.sec").is_none(), "no subtitles");
+}
+
+// ── GET /art/{id} ─────────────────────────────────────────────────────────────
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut buf = b"\x89PNG\r\n\x1a\n".to_vec();
+    buf.extend_from_slice(&13u32.to_be_bytes());
+    buf.extend_from_slice(b"IHDR");
+    buf.extend_from_slice(&width.to_be_bytes());
+    buf.extend_from_slice(&height.to_be_bytes());
+    buf.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    buf
+}
+
+#[tokio::test]
+async fn art_serves_folder_image_with_dlna_headers() {
+    let dir = std::env::temp_dir().join(format!("udlna-art-serve-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("cover.png"), png(100, 100)).unwrap();
+    let mut item = folder_item(&dir.join("song.mp3").to_string_lossy(), MediaKind::Audio, "audio/mpeg");
+    item.meta.art = Some(AlbumArt {
+        mime: "image/png".to_string(),
+        width: 100,
+        height: 100,
+        path: Some(dir.join("cover.png")),
+    });
+    let id = item.id;
+    let response = make_app(vec![item])
+        .oneshot(Request::builder().uri(format!("/art/{id}")).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/png");
+    let features = response.headers()["contentfeatures.dlna.org"].to_str().unwrap().to_string();
+    assert!(features.starts_with("DLNA.ORG_PN=PNG_TN;"), "{features}");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body.as_ref(), png(100, 100).as_slice());
+}
+
+#[tokio::test]
+async fn art_for_item_without_art_is_not_found() {
+    let item = fake_item();
+    let id = item.id;
+    let response = make_app(vec![item])
+        .oneshot(Request::builder().uri(format!("/art/{id}")).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
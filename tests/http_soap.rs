@@ -109,19 +109,19 @@ fn apply_pagination_count_exceeds_remaining_clamps() {
 
 #[test]
 fn build_protocol_info_with_profile_contains_dlna_org_pn() {
-    let info = build_protocol_info("audio/mpeg", Some("MP3"));
+    let info = build_protocol_info("audio/mpeg", Some("MP3"), false);
     assert!(info.contains("DLNA.ORG_PN=MP3"), "Expected DLNA.ORG_PN=MP3 in: {info}");
 }
 
 #[test]
 fn build_protocol_info_with_profile_contains_dlna_org_op() {
-    let info = build_protocol_info("audio/mpeg", Some("MP3"));
+    let info = build_protocol_info("audio/mpeg", Some("MP3"), false);
     assert!(info.contains("DLNA.ORG_OP=01"), "Expected DLNA.ORG_OP=01 in: {info}");
 }
 
 #[test]
 fn build_protocol_info_with_profile_contains_dlna_flags() {
-    let info = build_protocol_info("audio/mpeg", Some("MP3"));
+    let info = build_protocol_info("audio/mpeg", Some("MP3"), false);
     assert!(
         info.contains("DLNA.ORG_FLAGS=01700000000000000000000000000000"),
         "Expected DLNA.ORG_FLAGS in: {info}"
@@ -130,32 +130,38 @@ fn build_protocol_info_with_profile_contains_dlna_flags() {
 
 #[test]
 fn build_protocol_info_with_profile_starts_with_http_get_prefix() {
-    let info = build_protocol_info("audio/mpeg", Some("MP3"));
+    let info = build_protocol_info("audio/mpeg", Some("MP3"), false);
     assert!(info.starts_with("http-get:*:audio/mpeg:"), "Expected http-get:*:audio/mpeg: in: {info}");
 }
 
 #[test]
 fn build_protocol_info_none_profile_omits_dlna_org_pn() {
     // LOCKED DECISION: None profile must NOT include DLNA.ORG_PN at all (no wildcard "*")
-    let info = build_protocol_info("video/x-matroska", None);
+    let info = build_protocol_info("video/x-matroska", None, false);
     assert!(!info.contains("DLNA.ORG_PN"), "Expected NO DLNA.ORG_PN in: {info}");
 }
 
 #[test]
 fn build_protocol_info_none_profile_still_contains_dlna_org_op() {
-    let info = build_protocol_info("video/x-matroska", None);
+    let info = build_protocol_info("video/x-matroska", None, false);
     assert!(info.contains("DLNA.ORG_OP=01"), "Expected DLNA.ORG_OP=01 in: {info}");
 }
 
 #[test]
 fn build_protocol_info_none_profile_starts_with_http_get_prefix() {
-    let info = build_protocol_info("video/x-matroska", None);
+    let info = build_protocol_info("video/x-matroska", None, false);
     assert!(
         info.starts_with("http-get:*:video/x-matroska:"),
         "Expected http-get:*:video/x-matroska: in: {info}"
     );
 }
 
+#[test]
+fn build_protocol_info_time_seek_sets_dlna_org_op_11() {
+    let info = build_protocol_info("video/x-matroska", None, true);
+    assert!(info.contains("DLNA.ORG_OP=11;"), "Expected DLNA.ORG_OP=11 in: {info}");
+}
+
 // ── container_uuid ────────────────────────────────────────────────────────────
 
 #[test]
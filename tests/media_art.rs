@@ -0,0 +1,183 @@
+use std::path::PathBuf;
+use udlna::media::art::{describe, dlna_art_profile, embedded_art, folder_art, is_folder_art};
+use udlna::media::library::AlbumArt;
+use udlna::media::mkv::cover_attachment;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("udlna-art-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// PNG signature plus an IHDR chunk: enough for sniffing and dimensions.
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut buf = b"\x89PNG\r\n\x1a\n".to_vec();
+    buf.extend_from_slice(&13u32.to_be_bytes());
+    buf.extend_from_slice(b"IHDR");
+    buf.extend_from_slice(&width.to_be_bytes());
+    buf.extend_from_slice(&height.to_be_bytes());
+    buf.extend_from_slice(&[8, 6, 0, 0, 0]);
+    buf.extend_from_slice(&[0; 4]); // CRC, not checked
+    buf
+}
+
+/// EBML element with an 8-byte size field.
+fn element(id: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut buf = id.to_vec();
+    buf.push(0x01);
+    buf.extend_from_slice(&(payload.len() as u64).to_be_bytes()[1..]);
+    buf.extend_from_slice(payload);
+    buf
+}
+
+fn attached_file(name: &str, mime: &str, data: &[u8]) -> Vec<u8> {
+    let mut payload = element(&[0x46, 0x6E], name.as_bytes());
+    payload.extend(element(&[0x46, 0x60], mime.as_bytes()));
+    payload.extend(element(&[0x46, 0x5C], data));
+    element(&[0x61, 0xA7], &payload)
+}
+
+/// Matroska file whose segment (unknown size) holds `children`.
+fn mkv(children: &[Vec<u8>]) -> Vec<u8> {
+    let mut buf = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"matroska"));
+    buf.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    for child in children {
+        buf.extend_from_slice(child);
+    }
+    buf
+}
+
+fn art(mime: &str, width: u32, height: u32) -> AlbumArt {
+    AlbumArt { mime: mime.to_string(), width, height, path: None }
+}
+
+// ── Folder images ─────────────────────────────────────────────────────────────
+
+#[test]
+fn folder_art_names_are_case_insensitive() {
+    assert!(is_folder_art(std::path::Path::new("/m/Cover.JPG")));
+    assert!(is_folder_art(std::path::Path::new("/m/folder.jpeg")));
+    assert!(is_folder_art(std::path::Path::new("/m/front.png")));
+    assert!(!is_folder_art(std::path::Path::new("/m/cover.gif")));
+    assert!(!is_folder_art(std::path::Path::new("/m/back.jpg")));
+}
+
+#[test]
+fn folder_art_prefers_cover_and_reads_dimensions() {
+    let dir = temp_dir("prefer");
+    std::fs::write(dir.join("folder.png"), png(300, 300)).unwrap();
+    std::fs::write(dir.join("Cover.png"), png(120, 100)).unwrap();
+    let found = folder_art(&dir).expect("cover expected");
+    assert_eq!(found.path, Some(dir.join("Cover.png")));
+    assert_eq!((found.mime.as_str(), found.width, found.height), ("image/png", 120, 100));
+}
+
+#[test]
+fn folder_art_skips_unreadable_images() {
+    let dir = temp_dir("unreadable");
+    std::fs::write(dir.join("cover.jpg"), b"not an image").unwrap();
+    std::fs::write(dir.join("front.png"), png(64, 64)).unwrap();
+    assert_eq!(folder_art(&dir).unwrap().path, Some(dir.join("front.png")));
+}
+
+// ── Profiles ──────────────────────────────────────────────────────────────────
+
+#[test]
+fn art_profile_follows_dimensions() {
+    assert_eq!(dlna_art_profile(&art("image/jpeg", 160, 160)), Some("JPEG_TN"));
+    assert_eq!(dlna_art_profile(&art("image/jpeg", 500, 480)), Some("JPEG_SM"));
+    assert_eq!(dlna_art_profile(&art("image/jpeg", 1000, 700)), Some("JPEG_MED"));
+    assert_eq!(dlna_art_profile(&art("image/jpeg", 1400, 1400)), Some("JPEG_LRG"));
+    assert_eq!(dlna_art_profile(&art("image/png", 100, 100)), Some("PNG_TN"));
+    assert_eq!(dlna_art_profile(&art("image/png", 600, 600)), Some("PNG_LRG"));
+    assert_eq!(dlna_art_profile(&art("image/gif", 100, 100)), None);
+}
+
+#[test]
+fn describe_takes_type_from_content() {
+    let found = describe(&png(200, 150)).unwrap();
+    assert_eq!((found.mime.as_str(), found.width, found.height), ("image/png", 200, 150));
+    assert!(describe(b"ID3\x04").is_none());
+}
+
+// ── Matroska attachments ──────────────────────────────────────────────────────
+
+#[test]
+fn mkv_cover_attachment_prefers_cover_name() {
+    let dir = temp_dir("mkv");
+    let attachments = element(
+        &[0x19, 0x41, 0xA4, 0x69],
+        &[
+            attached_file("font.ttf", "application/x-truetype-font", b"font"),
+            attached_file("small.png", "image/png", &png(32, 32)),
+            attached_file("cover.png", "image/png", &png(600, 600)),
+        ]
+        .concat(),
+    );
+    let path = dir.join("movie.mkv");
+    std::fs::write(&path, mkv(&[attachments])).unwrap();
+    let cover = cover_attachment(&path).expect("cover expected");
+    assert_eq!(cover.name, "cover.png");
+    assert_eq!(embedded_art(&path, "video/x-matroska"), Some(png(600, 600)));
+}
+
+#[test]
+fn mkv_attachments_after_clusters_are_found_through_seek_head() {
+    let dir = temp_dir("mkv-seek");
+    let cluster = element(&[0x1F, 0x43, 0xB6, 0x75], &[0u8; 64]);
+    let attachments = element(&[0x19, 0x41, 0xA4, 0x69], &attached_file("cover.png", "image/png", &png(8, 8)));
+    // SeekHead size is fixed (two 8-byte-size elements around 4 + 8 bytes of payload).
+    let seek_head_len = element(
+        &[0x11, 0x4D, 0x9B, 0x74],
+        &element(&[0x4D, 0xBB], &[element(&[0x53, 0xAB], &[0; 4]), element(&[0x53, 0xAC], &[0; 8])].concat()),
+    )
+    .len();
+    let position = (seek_head_len + cluster.len()) as u64;
+    let seek = [
+        element(&[0x53, 0xAB], &[0x19, 0x41, 0xA4, 0x69]),
+        element(&[0x53, 0xAC], &position.to_be_bytes()),
+    ]
+    .concat();
+    let seek_head = element(&[0x11, 0x4D, 0x9B, 0x74], &element(&[0x4D, 0xBB], &seek));
+    assert_eq!(seek_head.len(), seek_head_len);
+    let path = dir.join("late.mkv");
+    std::fs::write(&path, mkv(&[seek_head, cluster, attachments])).unwrap();
+    assert_eq!(cover_attachment(&path).map(|a| a.data), Some(png(8, 8)));
+}
+
+#[test]
+fn mkv_without_seek_head_stops_at_first_cluster() {
+    let dir = temp_dir("mkv-noseek");
+    let cluster = element(&[0x1F, 0x43, 0xB6, 0x75], &[0u8; 64]);
+    let attachments = element(&[0x19, 0x41, 0xA4, 0x69], &attached_file("cover.png", "image/png", &png(8, 8)));
+    let path = dir.join("plain.mkv");
+    std::fs::write(&path, mkv(&[cluster, attachments])).unwrap();
+    assert!(cover_attachment(&path).is_none());
+}
+
+// ── MP4 posters ───────────────────────────────────────────────────────────────
+
+fn boxed(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    [&(8 + payload.len() as u32).to_be_bytes()[..], kind, payload].concat()
+}
+
+/// QuickTime file with an iTunes `covr` item holding `cover`, and no tracks.
+fn mov_with_cover(cover: &[u8]) -> Vec<u8> {
+    let mut mvhd = vec![0; 100];
+    mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+    let hdlr = boxed(b"hdlr", &[&[0; 8][..], b"mdir", &[0; 13]].concat());
+    let covr = boxed(b"covr", &boxed(b"data", &[&13u32.to_be_bytes()[..], &[0; 4], cover].concat()));
+    let meta = boxed(b"meta", &[&[0; 4][..], &hdlr, &boxed(b"ilst", &covr)].concat());
+    let moov = boxed(b"moov", &[boxed(b"mvhd", &mvhd), boxed(b"udta", &meta)].concat());
+    [boxed(b"ftyp", b"qt  \0\0\0\0qt  "), moov].concat()
+}
+
+#[test]
+fn quicktime_and_3gpp_posters_are_read() {
+    let dir = temp_dir("mov");
+    let path = dir.join("clip.mov");
+    std::fs::write(&path, mov_with_cover(&png(300, 300))).unwrap();
+    assert_eq!(embedded_art(&path, "video/quicktime"), Some(png(300, 300)));
+    assert_eq!(embedded_art(&path, "video/3gpp"), Some(png(300, 300)));
+}
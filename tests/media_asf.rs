@@ -0,0 +1,106 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::asf::read_info;
+use udlna::media::metadata::extract_metadata;
+use udlna::media::mime::MediaKind;
+
+const HEADER: [u8; 16] =
+    [0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0, 0xAA, 0, 0x62, 0xCE, 0x6C];
+const FILE_PROPERTIES: [u8; 16] =
+    [0xA1, 0xDC, 0xAB, 0x8C, 0x47, 0xA9, 0xCF, 0x11, 0x8E, 0xE4, 0, 0xC0, 0x0C, 0x20, 0x53, 0x65];
+const STREAM_PROPERTIES: [u8; 16] =
+    [0x91, 0x07, 0xDC, 0xB7, 0xB7, 0xA9, 0xCF, 0x11, 0x8E, 0xE6, 0, 0xC0, 0x0C, 0x20, 0x53, 0x65];
+const CONTENT_DESCRIPTION: [u8; 16] =
+    [0x33, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0, 0xAA, 0, 0x62, 0xCE, 0x6C];
+const AUDIO_MEDIA: [u8; 16] =
+    [0x40, 0x9E, 0x69, 0xF8, 0x4D, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0, 0x80, 0x5F, 0x5C, 0x44, 0x2B];
+
+fn object(guid: &[u8; 16], body: &[u8]) -> Vec<u8> {
+    [&guid[..], &(24 + body.len() as u64).to_le_bytes(), body].concat()
+}
+
+/// File Properties with a play duration and preroll, both in their on-disk units.
+fn file_properties(play_100ns: u64, preroll_ms: u64) -> Vec<u8> {
+    let mut body = vec![0; 80];
+    body[40..48].copy_from_slice(&play_100ns.to_le_bytes());
+    body[56..64].copy_from_slice(&preroll_ms.to_le_bytes());
+    object(&FILE_PROPERTIES, &body)
+}
+
+fn audio_stream(format_tag: u16, channels: u16, rate: u32, bytes_per_sec: u32) -> Vec<u8> {
+    let wfx = [
+        &format_tag.to_le_bytes()[..],
+        &channels.to_le_bytes(),
+        &rate.to_le_bytes(),
+        &bytes_per_sec.to_le_bytes(),
+        &[0x40, 0x1D, 16, 0, 0, 0],
+    ]
+    .concat();
+    let mut body = [&AUDIO_MEDIA[..], &[0; 24]].concat();
+    body.extend_from_slice(&(wfx.len() as u32).to_le_bytes());
+    body.extend_from_slice(&[0; 10]);
+    body.extend_from_slice(&wfx);
+    object(&STREAM_PROPERTIES, &body)
+}
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect()
+}
+
+fn content_description(title: &str, author: &str) -> Vec<u8> {
+    let (title, author) = (utf16(title), utf16(author));
+    let lengths = [title.len() as u16, author.len() as u16, 0, 0, 0];
+    let body = [lengths.iter().flat_map(|l| l.to_le_bytes()).collect(), title, author].concat();
+    object(&CONTENT_DESCRIPTION, &body)
+}
+
+fn asf(objects: &[Vec<u8>]) -> Vec<u8> {
+    let children = objects.concat();
+    let size = 30 + children.len() as u64;
+    let count = objects.len() as u32;
+    [&HEADER[..], &size.to_le_bytes(), &count.to_le_bytes(), &[1, 2], &children].concat()
+}
+
+// ── read_info ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn reads_duration_stream_and_description() {
+    let data = asf(&[
+        file_properties(1_815_000_000, 3000),
+        audio_stream(0x0161, 2, 44100, 16000),
+        content_description("Song", "Band"),
+    ]);
+    let info = read_info(&temp_file("song.wma", &data)).expect("ASF expected");
+    assert_eq!(info.duration, Some(178.5));
+    let audio = info.audio.expect("audio stream expected");
+    assert_eq!((audio.codec.as_str(), audio.channels, audio.sample_rate), ("wmav2", 2, 44100));
+    assert_eq!(audio.bitrate, 16000);
+    assert_eq!((info.title.as_deref(), info.author.as_deref()), (Some("Song"), Some("Band")));
+}
+
+#[test]
+fn truncated_object_ends_the_walk() {
+    let mut data = asf(&[file_properties(100_000_000, 0), audio_stream(0x0162, 6, 48000, 48000)]);
+    data.truncate(data.len() - 10);
+    assert!(read_info(&temp_file("cut.wma", &data)).is_none(), "header object shorter than its size");
+}
+
+#[test]
+fn not_asf() {
+    assert!(read_info(&temp_file("noise.wma", &[0x30; 256])).is_none());
+}
+
+// ── extract_metadata ──────────────────────────────────────────────────────────
+
+#[test]
+fn wma_metadata_has_profile_and_tags() {
+    let stream = audio_stream(0x0161, 2, 44100, 16000);
+    let data = asf(&[file_properties(600_000_000, 0), stream, content_description("T", "A")]);
+    let meta = extract_metadata(&temp_file("track.wma", &data), MediaKind::Audio, "audio/x-ms-wma").expect("metadata");
+    assert_eq!(meta.duration.as_deref(), Some("00:01:00.000"));
+    assert_eq!(meta.bitrate, Some(16000));
+    assert_eq!(meta.bits_per_sample, None, "lossy codec");
+    assert_eq!(meta.dlna_profile, Some("WMABASE"));
+    assert_eq!((meta.title.as_deref(), meta.artist.as_deref()), (Some("T"), Some("A")));
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::avi::read_info;
+use udlna::media::library::AudioTrack;
+use udlna::media::metadata::extract_metadata;
+use udlna::media::mime::MediaKind;
+
+fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut buf = id.to_vec();
+    buf.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    buf.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        buf.push(0);
+    }
+    buf
+}
+
+fn list(kind: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+    chunk(b"LIST", &[kind.to_vec(), children.concat()].concat())
+}
+
+fn le32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Main header: µs per frame, total frames, frame size.
+fn avih(frame_us: u32, frames: u32, width: u32, height: u32) -> Vec<u8> {
+    chunk(b"avih", &le32s(&[frame_us, 0, 0, 0, frames, 0, 2, 0, width, height, 0, 0, 0, 0]))
+}
+
+/// Stream header: type, handler, scale/rate/length.
+fn strh(kind: &[u8; 4], handler: &[u8; 4], scale: u32, rate: u32, length: u32) -> Vec<u8> {
+    let body = [kind.to_vec(), handler.to_vec(), le32s(&[0, 0, 0, scale, rate, 0, length, 0, 0, 0]), vec![0; 8]];
+    chunk(b"strh", &body.concat())
+}
+
+fn video_strl(compression: &[u8; 4], width: u32, height: i32) -> Vec<u8> {
+    let bih = [le32s(&[40, width, height as u32]), vec![1, 0, 24, 0], compression.to_vec(), vec![0; 20]];
+    list(b"strl", &[strh(b"vids", b"\0\0\0\0", 1001, 24000, 2400), chunk(b"strf", &bih.concat())])
+}
+
+fn audio_strl(format_tag: u16, channels: u16) -> Vec<u8> {
+    let wfx = [format_tag.to_le_bytes(), channels.to_le_bytes()].concat();
+    let wfx = [wfx, le32s(&[48000, 192000]), vec![4, 0, 16, 0]].concat();
+    list(b"strl", &[strh(b"auds", b"\0\0\0\0", 1, 48000, 0), chunk(b"strf", &wfx)])
+}
+
+fn avi(hdrl: &[Vec<u8>]) -> Vec<u8> {
+    let body = [b"AVI ".to_vec(), list(b"hdrl", hdrl), list(b"movi", &[])].concat();
+    chunk(b"RIFF", &body)
+}
+
+// ── read_info ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn reads_headers_and_streams() {
+    let path = temp_file(
+        "rip.avi",
+        &avi(&[avih(41708, 2400, 640, 272), video_strl(b"XVID", 640, 272), audio_strl(0x55, 2), audio_strl(0x2000, 6)]),
+    );
+    let info = read_info(&path).expect("AVI expected");
+    assert_eq!((info.width, info.height), (Some(640), Some(272)));
+    assert_eq!(info.video_codec.as_deref(), Some("mpeg4"));
+    assert!((info.duration.unwrap() - 100.1).abs() < 0.001);
+    let audio: Vec<_> = info.audio.iter().map(|a| (a.codec.as_str(), a.channels)).collect();
+    assert_eq!(audio, vec![("mp3", Some(2)), ("ac3", Some(6))]);
+}
+
+#[test]
+fn duration_falls_back_to_main_header() {
+    let strl = list(b"strl", &[strh(b"vids", b"MJPG", 0, 0, 0)]);
+    let path = temp_file("camera.avi", &avi(&[avih(40_000, 250, 320, 240), strl]));
+    let info = read_info(&path).unwrap();
+    assert_eq!(info.duration, Some(10.0));
+    assert_eq!(info.video_codec.as_deref(), Some("mjpeg"));
+}
+
+#[test]
+fn frame_size_falls_back_to_stream_format() {
+    let path = temp_file("topdown.avi", &avi(&[avih(40_000, 25, 0, 0), video_strl(b"H264", 1280, -720)]));
+    let info = read_info(&path).unwrap();
+    assert_eq!((info.width, info.height), (Some(1280), Some(720)));
+    assert_eq!(info.video_codec.as_deref(), Some("h264"));
+}
+
+#[test]
+fn truncated_files_and_chunks_are_rejected() {
+    let full = avi(&[avih(41708, 2400, 640, 272), video_strl(b"XVID", 640, 272), audio_strl(0x55, 2)]);
+    for len in 0..full.len() {
+        // Cut anywhere: never a panic, and nothing from a file whose header list is cut.
+        let info = read_info(&temp_file("cut.avi", &full[..len]));
+        assert!(info.is_none() || len >= full.len() - 12, "cut at {len}");
+    }
+    let short_header = chunk(b"avih", &le32s(&[41708, 0, 0]));
+    assert!(read_info(&temp_file("short.avi", &avi(&[short_header]))).is_none());
+}
+
+#[test]
+fn wave_files_are_not_avi() {
+    let path = temp_file("sound.avi", &chunk(b"RIFF", b"WAVEfmt "));
+    assert!(read_info(&path).is_none());
+}
+
+// ── extract_metadata ──────────────────────────────────────────────────────────
+
+#[test]
+fn avi_metadata_has_resolution_and_duration() {
+    let hdrl = [avih(41708, 2400, 640, 272), video_strl(b"DIVX", 640, 272), audio_strl(0x55, 2)];
+    let path = temp_file("movie.avi", &avi(&hdrl));
+    let meta = extract_metadata(&path, MediaKind::Video, "video/x-msvideo").expect("metadata expected");
+    assert_eq!(meta.duration.as_deref(), Some("00:01:40.100"));
+    assert_eq!(meta.resolution.as_deref(), Some("640x272"));
+    assert_eq!(meta.video_codec.as_deref(), Some("mpeg4"));
+    assert_eq!(meta.dlna_profile, None, "DLNA defines no AVI profiles");
+    assert_eq!(meta.audio_tracks, vec![AudioTrack { codec: "mp3".to_string(), language: None, channels: Some(2) }]);
+}
@@ -0,0 +1,154 @@
+use udlna::media::dlna::{audio_profile, image_profile, known, video_profile, Container, Video};
+use udlna::media::library::{AudioTrack, MediaMeta};
+
+fn track(codec: &str) -> AudioTrack {
+    AudioTrack { codec: codec.to_string(), ..Default::default() }
+}
+
+fn h264(profile: u8, width: u32, height: u32) -> Video<'static> {
+    Video { codec: "h264", profile, width, height }
+}
+
+fn mpeg2(width: u32, height: u32) -> Video<'static> {
+    Video { codec: "mpeg2video", profile: 0, width, height }
+}
+
+/// Audio stream parameters; `kbps` is converted to bytes per second.
+fn stream(sample_rate: u32, channels: u32, kbps: u32) -> MediaMeta {
+    MediaMeta {
+        sample_rate: Some(sample_rate),
+        channels: Some(channels),
+        bitrate: Some(kbps * 1000 / 8),
+        ..Default::default()
+    }
+}
+
+// ── Images ────────────────────────────────────────────────────────────────────
+
+#[test]
+fn jpeg_profile_follows_pixel_size() {
+    assert_eq!(image_profile("image/jpeg", 640, 480), Some("JPEG_SM"));
+    assert_eq!(image_profile("image/jpeg", 480, 640), Some("JPEG_MED"), "portrait exceeds 640x480");
+    assert_eq!(image_profile("image/jpeg", 1024, 768), Some("JPEG_MED"));
+    assert_eq!(image_profile("image/jpeg", 4000, 3000), Some("JPEG_LRG"));
+    assert_eq!(image_profile("image/jpeg", 8000, 6000), None);
+}
+
+#[test]
+fn png_and_other_images() {
+    assert_eq!(image_profile("image/png", 100, 100), Some("PNG_LRG"));
+    assert_eq!(image_profile("image/png", 5000, 100), None);
+    assert_eq!(image_profile("image/gif", 100, 100), None);
+}
+
+// ── Audio ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn mp3_profile_depends_on_sample_rate() {
+    assert_eq!(audio_profile("audio/mpeg", "mp3", &stream(44100, 2, 320)), Some("MP3"));
+    assert_eq!(audio_profile("audio/mpeg", "mp3", &stream(22050, 2, 64)), Some("MP3X"));
+    assert_eq!(audio_profile("audio/mpeg", "mp3", &MediaMeta::default()), Some("MP3"));
+}
+
+#[test]
+fn aac_profile_depends_on_container_bitrate_and_channels() {
+    assert_eq!(audio_profile("audio/mp4", "aac", &stream(44100, 2, 256)), Some("AAC_ISO_320"));
+    assert_eq!(audio_profile("audio/mp4", "aac", &stream(48000, 2, 500)), Some("AAC_ISO"));
+    assert_eq!(audio_profile("audio/mp4", "aac", &stream(48000, 6, 640)), Some("AAC_MULT5_ISO"));
+    assert_eq!(audio_profile("audio/mp4", "aac", &stream(96000, 2, 256)), None);
+    assert_eq!(audio_profile("audio/aac", "aac", &stream(44100, 2, 128)), Some("AAC_ADTS_320"));
+    assert_eq!(audio_profile("audio/mp4", "alac", &stream(44100, 2, 900)), None);
+}
+
+#[test]
+fn pcm_is_lpcm_only_at_16_bits() {
+    let cd = MediaMeta { bits_per_sample: Some(16), ..stream(44100, 2, 1411) };
+    assert_eq!(audio_profile("audio/wav", "pcm", &cd), Some("LPCM"));
+    let hires = MediaMeta { bits_per_sample: Some(24), ..stream(96000, 2, 4608) };
+    assert_eq!(audio_profile("audio/wav", "pcm", &hires), None);
+}
+
+#[test]
+fn wma_profiles() {
+    assert_eq!(audio_profile("audio/x-ms-wma", "wmav2", &stream(44100, 2, 128)), Some("WMABASE"));
+    assert_eq!(audio_profile("audio/x-ms-wma", "wmav2", &stream(48000, 2, 320)), Some("WMAFULL"));
+    assert_eq!(audio_profile("audio/x-ms-wma", "wmapro", &stream(96000, 6, 768)), Some("WMAPRO"));
+    assert_eq!(audio_profile("audio/x-ms-wma", "wmalossless", &stream(44100, 2, 900)), Some("WMALSL"));
+    assert_eq!(audio_profile("audio/flac", "flac", &stream(44100, 2, 900)), None);
+}
+
+// ── Video ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn avc_mp4_profiles() {
+    let aac = track("aac");
+    let mp4 = |video| video_profile(Container::Mp4, video, Some(&aac));
+    assert_eq!(mp4(h264(66, 320, 240)), Some("AVC_MP4_BL_CIF30_AAC_940"));
+    assert_eq!(mp4(h264(66, 640, 480)), Some("AVC_MP4_BL_L3L_SD_AAC"));
+    assert_eq!(mp4(h264(77, 720, 576)), Some("AVC_MP4_MP_SD_AAC_MULT5"));
+    assert_eq!(mp4(h264(77, 1280, 720)), Some("AVC_MP4_MP_HD_720p_AAC"));
+    assert_eq!(mp4(h264(77, 1920, 1080)), Some("AVC_MP4_MP_HD_1080i_AAC"));
+    assert_eq!(mp4(h264(100, 1920, 1080)), Some("AVC_MP4_HP_HD_AAC"));
+    assert_eq!(mp4(h264(100, 3840, 2160)), None);
+    assert_eq!(mp4(Video { codec: "hevc", profile: 1, width: 1920, height: 1080 }), None);
+}
+
+#[test]
+fn avc_mp4_profile_names_the_audio_codec() {
+    let ac3 = track("ac3");
+    assert_eq!(video_profile(Container::Mp4, h264(100, 720, 480), Some(&ac3)), Some("AVC_MP4_MP_SD_AC3"));
+    assert_eq!(video_profile(Container::Mp4, h264(100, 1920, 1080), Some(&ac3)), None);
+    assert_eq!(video_profile(Container::Mp4, h264(100, 720, 480), None), None, "no audio track");
+}
+
+#[test]
+fn ts_profiles_follow_packet_framing() {
+    let (iso, t) = (Container::Ts { timestamped: false }, Container::Ts { timestamped: true });
+    let ac3 = track("ac3");
+    assert_eq!(video_profile(iso, h264(100, 1920, 1080), Some(&ac3)), Some("AVC_TS_MP_HD_AC3_ISO"));
+    assert_eq!(video_profile(t, h264(100, 1920, 1080), Some(&ac3)), Some("AVC_TS_MP_HD_AC3_T"));
+    assert_eq!(video_profile(t, h264(77, 720, 576), Some(&track("aac"))), Some("AVC_TS_MP_SD_AAC_MULT5_T"));
+    assert_eq!(video_profile(iso, h264(100, 1920, 1080), Some(&track("dts"))), None);
+}
+
+#[test]
+fn mpeg2_profiles_by_region_and_size() {
+    let iso = Container::Ts { timestamped: false };
+    assert_eq!(video_profile(iso, mpeg2(720, 576), None), Some("MPEG_TS_SD_EU_ISO"));
+    assert_eq!(video_profile(iso, mpeg2(720, 480), None), Some("MPEG_TS_SD_NA_ISO"));
+    assert_eq!(video_profile(iso, mpeg2(1920, 1080), None), Some("MPEG_TS_HD_NA_ISO"));
+    assert_eq!(video_profile(Container::Ps, mpeg2(720, 576), None), Some("MPEG_PS_PAL"));
+    assert_eq!(video_profile(Container::Ps, mpeg2(704, 480), None), Some("MPEG_PS_NTSC"));
+    assert_eq!(video_profile(Container::Ps, mpeg2(1920, 1080), None), None);
+    let vcd = Video { codec: "mpeg1video", profile: 0, width: 352, height: 288 };
+    assert_eq!(video_profile(Container::Ps, vcd, Some(&track("mp2"))), Some("MPEG1"));
+}
+
+// ── Formats without a profile ─────────────────────────────────────────────────
+
+#[test]
+fn formats_without_a_dlna_profile() {
+    let cd = stream(44100, 2, 1411);
+    assert_eq!(audio_profile("audio/ogg", "vorbis", &cd), None);
+    assert_eq!(audio_profile("audio/flac", "flac", &cd), None);
+    assert_eq!(audio_profile("audio/wav", "pcm", &cd), None, "bit depth unknown");
+    assert_eq!(audio_profile("audio/x-ms-wma", "wmav2", &stream(44100, 2, 440)), None);
+    assert_eq!(audio_profile("video/mp4", "mp3", &cd), None, "MP3 only as audio/mpeg");
+    assert_eq!(audio_profile("totally/made-up", "aac", &cd), None);
+    assert_eq!(image_profile("application/unknown", 100, 100), None);
+    let aac = track("aac");
+    let mpeg4 = Video { codec: "mpeg4", profile: 0, width: 640, height: 480 };
+    assert_eq!(video_profile(Container::Mp4, mpeg4, Some(&aac)), None);
+    assert_eq!(video_profile(Container::Ps, h264(100, 720, 576), Some(&aac)), None);
+    let hevc = Video { codec: "hevc", profile: 1, width: 1920, height: 1080 };
+    assert_eq!(video_profile(Container::Ts { timestamped: false }, hevc, Some(&aac)), None);
+}
+
+// ── known ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn only_listed_names_are_known() {
+    assert_eq!(known("AVC_TS_MP_HD_AC3_T"), Some("AVC_TS_MP_HD_AC3_T"));
+    assert_eq!(known("AVC_TS_MP_HD_DTS_T"), None);
+    assert_eq!(known(""), None);
+}
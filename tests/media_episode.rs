@@ -0,0 +1,67 @@
+use std::path::Path;
+use udlna::media::episode::{parse, Episode};
+
+fn episode(path: &str) -> Episode {
+    parse(Path::new(path)).unwrap_or_else(|| panic!("{path} is an episode"))
+}
+
+// ── parse ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn season_episode_marker() {
+    let e = episode("/tv/Show.Name.S02E05.The.Title.1080p.WEB-DL.x264-GRP.mkv");
+    assert_eq!(e.series, "Show Name");
+    assert_eq!((e.season, e.episode, e.date.as_deref()), (2, Some(5), None));
+    assert_eq!(e.title.as_deref(), Some("The Title"));
+    assert_eq!(e.display_title(), "S02E05 - The Title");
+}
+
+#[test]
+fn multi_episode_file_is_numbered_by_its_first_episode() {
+    let e = episode("/tv/show_name_s01e01-e02_720p.mkv");
+    assert_eq!((e.series.as_str(), e.season, e.episode, e.title), ("show name", 1, Some(1), None));
+}
+
+#[test]
+fn cross_marker() {
+    let e = episode("/tv/Show Name - 3x07 - Guest Star [HDTV].avi");
+    assert_eq!((e.series.as_str(), e.season, e.episode), ("Show Name", 3, Some(7)));
+    assert_eq!(e.title.as_deref(), Some("Guest Star"));
+}
+
+#[test]
+fn date_marker() {
+    let e = episode("/tv/The.Daily.Show.2024.03.01.Some.Guest.mkv");
+    assert_eq!(e.series, "The Daily Show");
+    assert_eq!((e.season, e.episode, e.date.as_deref()), (2024, None, Some("2024-03-01")));
+    assert_eq!(e.display_title(), "2024-03-01 - Some Guest");
+}
+
+#[test]
+fn bare_dates_are_episodes_only_in_season_folders() {
+    for path in ["/Videos/Holiday/2023-06-15 14.25.30.mp4", "/Videos/Phone/2023_06_15_clip.mp4"] {
+        assert!(parse(Path::new(path)).is_none(), "{path}");
+    }
+    let e = episode("/tv/Daily Show/Season 2024/2024.03.01.mkv");
+    assert_eq!((e.series.as_str(), e.date.as_deref()), ("Daily Show", Some("2024-03-01")));
+}
+
+#[test]
+fn series_from_folder_above_season_folder() {
+    let e = episode("/tv/Some Show/Season 1/S01E03 - Third.mkv");
+    assert_eq!((e.series.as_str(), e.episode, e.title.as_deref()), ("Some Show", Some(3), Some("Third")));
+    assert_eq!(episode("/tv/Other Show/Specials/s00e01.mkv").series, "Other Show");
+}
+
+#[test]
+fn movies_are_not_episodes() {
+    for path in [
+        "/movies/The.Movie.2019.1080p.BluRay.x264-GRP.mkv",
+        "/movies/Film 1920x1080.mp4",
+        "/movies/2001.A.Space.Odyssey.mkv",
+        "/movies/Series7.mkv",
+        "/S01E01.mkv",
+    ] {
+        assert!(parse(Path::new(path)).is_none(), "{path}");
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+use udlna::media::index::{MetadataIndex, INDEX_VERSION};
+use udlna::media::library::{MediaItem, MediaMeta};
+use udlna::media::mime::MediaKind;
+
+fn temp_index_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("udlna-test-{}-{}", std::process::id(), name))
+        .join("index.json")
+}
+
+fn mtime() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+}
+
+fn item(path: &str) -> MediaItem {
+    MediaItem {
+        id: Uuid::new_v5(&Uuid::NAMESPACE_URL, path.as_bytes()),
+        path: PathBuf::from(path),
+        file_size: 4096,
+        modified: Some(mtime()),
+        mime: "audio/mpeg",
+        kind: MediaKind::Audio,
+        meta: MediaMeta {
+            duration: Some("00:03:00.000".to_string()),
+            dlna_profile: Some("MP3"),
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn load_missing_index_is_empty() {
+    let index = MetadataIndex::load(&temp_index_path("missing"));
+    assert!(index.is_empty());
+}
+
+#[test]
+fn save_then_lookup_returns_cached_meta() {
+    let path = temp_index_path("roundtrip");
+    MetadataIndex::save(&[item("/m/song.mp3")], &path).unwrap();
+    let index = MetadataIndex::load(&path);
+    assert_eq!(index.len(), 1);
+    let (mime, meta) = index.lookup(&PathBuf::from("/m/song.mp3"), 4096, mtime()).unwrap();
+    assert_eq!(mime, "audio/mpeg");
+    assert_eq!(meta.duration.as_deref(), Some("00:03:00.000"));
+    assert_eq!(meta.dlna_profile, Some("MP3"));
+    assert_eq!(index.hits(), 1);
+}
+
+#[test]
+fn index_of_library_items_needs_no_file() {
+    let mut unknown = item("/m/new.mp3");
+    unknown.modified = None;
+    let index = MetadataIndex::from_items(&[item("/m/song.mp3"), unknown]);
+    assert_eq!(index.len(), 1, "Items without an mtime cannot be checked");
+    assert!(index.lookup(&PathBuf::from("/m/song.mp3"), 4096, mtime()).is_some());
+    assert!(index.lookup(&PathBuf::from("/m/song.mp3"), 1, mtime()).is_none());
+}
+
+#[test]
+fn merged_save_keeps_entries_of_roots_not_scanned() {
+    let path = temp_index_path("merge");
+    MetadataIndex::save(&[item("/m/song.mp3"), item("/m/gone.mp3"), item("/usb/other.mp3")], &path).unwrap();
+    let roots = [PathBuf::from("/m")];
+    MetadataIndex::load(&path).save_merged(&[item("/m/song.mp3"), item("/m/new.mp3")], &roots, &path).unwrap();
+    let index = MetadataIndex::load(&path);
+    assert_eq!(index.len(), 3);
+    assert!(index.lookup(&PathBuf::from("/usb/other.mp3"), 4096, mtime()).is_some());
+    assert!(index.lookup(&PathBuf::from("/m/new.mp3"), 4096, mtime()).is_some());
+    assert!(index.lookup(&PathBuf::from("/m/gone.mp3"), 4096, mtime()).is_none(), "pruned below a scanned root");
+}
+
+#[test]
+fn lookup_misses_when_size_or_mtime_changed() {
+    let path = temp_index_path("stale");
+    MetadataIndex::save(&[item("/m/song.mp3")], &path).unwrap();
+    let index = MetadataIndex::load(&path);
+    let p = PathBuf::from("/m/song.mp3");
+    assert!(index.lookup(&p, 4097, mtime()).is_none());
+    assert!(index.lookup(&p, 4096, mtime() + Duration::from_secs(1)).is_none());
+    assert_eq!(index.hits(), 0);
+}
+
+#[test]
+fn outdated_version_is_discarded() {
+    let path = temp_index_path("version");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let stale = format!(r#"{{"version":{},"entries":[]}}"#, INDEX_VERSION + 1);
+    std::fs::write(&path, stale).unwrap();
+    assert!(MetadataIndex::load(&path).is_empty());
+}
+
+#[test]
+fn corrupt_index_is_discarded() {
+    let path = temp_index_path("corrupt");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, b"not json").unwrap();
+    assert!(MetadataIndex::load(&path).is_empty());
+}
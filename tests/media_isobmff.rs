@@ -0,0 +1,253 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::isobmff::{read_chapters, read_tracks};
+use udlna::media::library::{AudioTrack, Chapter};
+use udlna::media::metadata::extract_metadata;
+use udlna::media::mime::MediaKind;
+use udlna::media::mkv::TrackKind;
+
+/// x264 SPS for 1280x720 High profile, level 3.1.
+const SPS_720P: [u8; 26] = [
+    0x67, 0x64, 0x00, 0x1F, 0xAC, 0xD9, 0x40, 0x50, 0x05, 0xBB, 0x01, 0x10, 0x00, 0x00, 0x03, 0x00, 0x10, 0x00, 0x00,
+    0x03, 0x03, 0xC0, 0xF1, 0x83, 0x19, 0x60,
+];
+
+fn boxed(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    [&(8 + payload.len() as u32).to_be_bytes()[..], kind, payload].concat()
+}
+
+/// ISO 639-2 code packed into 15 bits.
+fn language(code: &str) -> u16 {
+    code.bytes().fold(0, |packed, c| packed << 5 | (c - 0x60) as u16)
+}
+
+/// A `trak` whose single sample entry is `entry`, with `stts` runs of (count, delta).
+fn trak(id: u32, handler: &[u8; 4], lang: &str, entry: Vec<u8>, stts: &[(u32, u32)]) -> Vec<u8> {
+    let mut tkhd = vec![0; 84];
+    tkhd[12..16].copy_from_slice(&id.to_be_bytes());
+    let mut mdhd = vec![0; 24];
+    mdhd[12..16].copy_from_slice(&24_000u32.to_be_bytes());
+    mdhd[20..22].copy_from_slice(&language(lang).to_be_bytes());
+    let hdlr = [&[0; 8][..], handler, &[0; 13]].concat();
+    let stsd = boxed(b"stsd", &[&[0, 0, 0, 0, 0, 0, 0, 1][..], &entry].concat());
+    let runs: Vec<u8> = stts.iter().flat_map(|(n, d)| [n.to_be_bytes(), d.to_be_bytes()].concat()).collect();
+    let stts = boxed(b"stts", &[&[0; 4][..], &(stts.len() as u32).to_be_bytes(), &runs].concat());
+    let stbl = boxed(b"stbl", &[stsd, stts].concat());
+    let mdia = [boxed(b"mdhd", &mdhd), boxed(b"hdlr", &hdlr), boxed(b"minf", &stbl)].concat();
+    boxed(b"trak", &[boxed(b"tkhd", &tkhd), boxed(b"mdia", &mdia)].concat())
+}
+
+fn visual_entry(kind: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+    boxed(kind, &[vec![0; 78], children.concat()].concat())
+}
+
+fn audio_entry(kind: &[u8; 4], channels: u16, children: &[Vec<u8>]) -> Vec<u8> {
+    let mut fields = vec![0; 28];
+    fields[16..18].copy_from_slice(&channels.to_be_bytes());
+    boxed(kind, &[fields, children.concat()].concat())
+}
+
+/// hvcC for Main 10, level 5.1, without parameter set arrays.
+fn hvcc() -> Vec<u8> {
+    boxed(b"hvcC", &[1, 0x02, 0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 153, 0xF0, 0, 0xFC, 0xFD, 0xFA, 0xFA, 0, 0, 0x0F, 0])
+}
+
+/// colr nclx: BT.2020 primaries and matrix with `transfer`.
+fn colr(transfer: u16) -> Vec<u8> {
+    boxed(b"colr", &[&b"nclx"[..], &9u16.to_be_bytes(), &transfer.to_be_bytes(), &9u16.to_be_bytes(), &[0]].concat())
+}
+
+/// esds for AAC-LC at 48 kHz with channel configuration 6.
+fn esds() -> Vec<u8> {
+    let asc = [0x05, 0x02, 0x11, 0xB0];
+    let config = [&[0x04, 17, 0x40, 0x15, 0, 0, 0][..], &[0; 8], &asc].concat();
+    let es = [&[0x03, config.len() as u8 + 3, 0, 1, 0][..], &config].concat();
+    boxed(b"esds", &[&[0; 4][..], &es].concat())
+}
+
+/// A `trak` with full sample tables: `stts` runs, one `stsz` size per sample, and all
+/// samples in one chunk at `chunk_offset`. `chapters` adds a `tref/chap` reference.
+fn table_trak(
+    id: u32,
+    handler: &[u8; 4],
+    stts: &[(u32, u32)],
+    sizes: &[u32],
+    chunk_offset: u32,
+    chapters: Option<u32>,
+) -> Vec<u8> {
+    let mut tkhd = vec![0; 84];
+    tkhd[12..16].copy_from_slice(&id.to_be_bytes());
+    let mut mdhd = vec![0; 24];
+    mdhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+    let hdlr = [&[0; 8][..], handler, &[0; 13]].concat();
+    let table = |kind: &[u8; 4], prefix: &[u8], entries: &[u32]| {
+        let values: Vec<u8> = entries.iter().flat_map(|v| v.to_be_bytes()).collect();
+        boxed(kind, &[&[0; 4][..], prefix, &values].concat())
+    };
+    let runs: Vec<u32> = stts.iter().flat_map(|&(n, d)| [n, d]).collect();
+    let stbl = [
+        table(b"stts", &(stts.len() as u32).to_be_bytes(), &runs),
+        table(b"stsc", &1u32.to_be_bytes(), &[1, sizes.len() as u32, 1]),
+        table(b"stsz", &[&[0; 4][..], &(sizes.len() as u32).to_be_bytes()].concat(), sizes),
+        table(b"stco", &1u32.to_be_bytes(), &[chunk_offset]),
+    ]
+    .concat();
+    let mdia = [boxed(b"mdhd", &mdhd), boxed(b"hdlr", &hdlr), boxed(b"minf", &boxed(b"stbl", &stbl))].concat();
+    let tref = chapters.map(|to| boxed(b"tref", &boxed(b"chap", &to.to_be_bytes()))).unwrap_or_default();
+    boxed(b"trak", &[boxed(b"tkhd", &tkhd), tref, boxed(b"mdia", &mdia)].concat())
+}
+
+// ── read_tracks ───────────────────────────────────────────────────────────────
+
+#[test]
+fn reads_hevc_hdr_track_and_audio_tracks() {
+    let moov = boxed(
+        b"moov",
+        &[
+            trak(1, b"vide", "und", visual_entry(b"hvc1", &[hvcc(), colr(16)]), &[(240, 1001)]),
+            trak(2, b"soun", "fre", audio_entry(b"ac-3", 2, &[]), &[]),
+            trak(3, b"soun", "eng", audio_entry(b"mp4a", 2, &[esds()]), &[]),
+        ]
+        .concat(),
+    );
+    let data = [boxed(b"ftyp", b"isom"), boxed(b"mdat", &[0; 32]), moov].concat();
+    let tracks = read_tracks(&temp_file("hdr.mp4", &data)).expect("moov expected");
+    assert_eq!(tracks.len(), 3);
+
+    let video = &tracks[0];
+    assert_eq!((video.id, video.kind, video.codec.as_str()), (1, TrackKind::Video, "hevc"));
+    let config = video.config.expect("hvcC expected");
+    assert_eq!((config.profile, config.level, config.bit_depth), (2, 153, 10));
+    assert_eq!(video.transfer, Some(16));
+    assert_eq!(video.frame_rate.map(|f| (f * 1000.0).round()), Some(23976.0));
+    assert_eq!(video.language, None, "und");
+
+    let audio: Vec<_> = tracks[1..].iter().map(|t| (t.codec.as_str(), t.language.as_deref(), t.channels)).collect();
+    assert_eq!(audio, vec![("ac3", Some("fre"), Some(2)), ("aac", Some("eng"), Some(6))]);
+}
+
+#[test]
+fn not_mp4() {
+    assert!(read_tracks(&temp_file("noise.mp4", &[0xFF; 64])).is_none());
+    let no_moov = [boxed(b"ftyp", b"isom"), boxed(b"mdat", &[0; 16])].concat();
+    assert!(read_tracks(&temp_file("empty.mp4", &no_moov)).is_none());
+    let endless = [&1u32.to_be_bytes()[..], b"free", &u64::MAX.to_be_bytes()].concat();
+    assert!(read_tracks(&temp_file("largesize.mp4", &endless)).is_none());
+}
+
+#[test]
+fn overflowing_frame_durations_have_no_frame_rate() {
+    let stts = [(u32::MAX, u32::MAX), (u32::MAX, u32::MAX)];
+    let moov = boxed(b"moov", &trak(1, b"vide", "und", visual_entry(b"avc1", &[]), &stts));
+    let tracks = read_tracks(&temp_file("long-frames.mp4", &[boxed(b"ftyp", b"isom"), moov].concat())).unwrap();
+    assert_eq!(tracks[0].frame_rate, None);
+}
+
+// ── read_chapters ─────────────────────────────────────────────────────────────
+
+#[test]
+fn reads_quicktime_chapter_track() {
+    // Text samples: a 16-bit length, then the title.
+    let titles = ["Opening", "", "Credits"];
+    let samples: Vec<Vec<u8>> =
+        titles.iter().map(|t| [&(t.len() as u16).to_be_bytes()[..], t.as_bytes()].concat()).collect();
+    let sizes: Vec<u32> = samples.iter().map(|s| s.len() as u32).collect();
+    // ftyp (12 bytes), then the mdat header: the samples start at byte 20.
+    let moov = boxed(
+        b"moov",
+        &[
+            table_trak(1, b"soun", &[(1, 180_000)], &[4], 20, Some(2)),
+            table_trak(2, b"text", &[(1, 60_000), (1, 90_000), (1, 30_000)], &sizes, 20, None),
+        ]
+        .concat(),
+    );
+    let data = [boxed(b"ftyp", b"M4B "), boxed(b"mdat", &samples.concat()), moov].concat();
+    let chapters = read_chapters(&temp_file("book.m4b", &data));
+    let found: Vec<_> = chapters.iter().map(|c| (c.start, c.title.as_deref())).collect();
+    assert_eq!(found, vec![(0.0, Some("Opening")), (60.0, None), (150.0, Some("Credits"))]);
+}
+
+#[test]
+fn reads_nero_chapter_list() {
+    let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+    for (start, title) in [(0u64, "Part One"), (754_000_000, "Part Two")] {
+        chpl.extend_from_slice(&start.to_be_bytes());
+        chpl.push(title.len() as u8);
+        chpl.extend_from_slice(title.as_bytes());
+    }
+    let moov = boxed(b"moov", &boxed(b"udta", &boxed(b"chpl", &chpl)));
+    let data = [boxed(b"ftyp", b"isom"), moov].concat();
+    assert_eq!(
+        read_chapters(&temp_file("nero.mp4", &data)),
+        vec![
+            Chapter { start: 0.0, title: Some("Part One".to_string()) },
+            Chapter { start: 75.4, title: Some("Part Two".to_string()) },
+        ]
+    );
+    let plain = [boxed(b"ftyp", b"isom"), boxed(b"moov", &[])].concat();
+    assert!(read_chapters(&temp_file("plain.mp4", &plain)).is_empty());
+}
+
+// ── extract_metadata ──────────────────────────────────────────────────────────
+
+#[test]
+fn mp4_metadata_has_codec_details_and_audio_tracks() {
+    use mp4::{AacConfig, AvcConfig, MediaConfig, Mp4Config, Mp4Sample, TrackConfig, TrackType};
+
+    let config = Mp4Config {
+        major_brand: "isom".parse().unwrap(),
+        minor_version: 512,
+        compatible_brands: vec!["isom".parse().unwrap(), "mp41".parse().unwrap()],
+        timescale: 1000,
+    };
+    let mut writer = mp4::Mp4Writer::write_start(std::io::Cursor::new(Vec::new()), &config).unwrap();
+    writer
+        .add_track(&TrackConfig {
+            track_type: TrackType::Video,
+            timescale: 25,
+            language: "und".to_string(),
+            media_conf: MediaConfig::AvcConfig(AvcConfig {
+                width: 1280,
+                height: 720,
+                seq_param_set: SPS_720P.to_vec(),
+                pic_param_set: vec![0x68, 0xEB, 0xE3, 0xCB, 0x22, 0xC0],
+            }),
+        })
+        .unwrap();
+    writer
+        .add_track(&TrackConfig {
+            track_type: TrackType::Audio,
+            timescale: 48000,
+            language: "ger".to_string(),
+            media_conf: MediaConfig::AacConfig(AacConfig::default()),
+        })
+        .unwrap();
+    let sample = |start_time, duration| Mp4Sample {
+        start_time,
+        duration,
+        rendering_offset: 0,
+        is_sync: true,
+        bytes: vec![0; 16].into(),
+    };
+    for i in 0..50 {
+        writer.write_sample(1, &sample(i, 1)).unwrap();
+    }
+    writer.write_sample(2, &sample(0, 96000)).unwrap();
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+
+    let meta = extract_metadata(&temp_file("clip.mp4", &data), MediaKind::Video, "video/mp4").expect("metadata");
+    assert_eq!(meta.resolution.as_deref(), Some("1280x720"));
+    assert_eq!(meta.video_codec.as_deref(), Some("h264"));
+    assert_eq!(meta.video_profile.as_deref(), Some("High"));
+    assert_eq!(meta.video_level.as_deref(), Some("3.1"));
+    assert_eq!(meta.video_bit_depth, Some(8));
+    assert_eq!(meta.frame_rate, Some(25.0));
+    assert_eq!(meta.transfer, None);
+    assert_eq!(
+        meta.audio_tracks,
+        vec![AudioTrack { codec: "aac".to_string(), language: Some("ger".to_string()), channels: Some(2) }]
+    );
+    assert_eq!(meta.dlna_profile, Some("AVC_MP4_MP_HD_720p_AAC"));
+}
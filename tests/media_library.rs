@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+use uuid::Uuid;
+use udlna::media::library::{MediaItem, MediaLibrary, MediaMeta};
+use udlna::media::mime::MediaKind;
+
+fn item(path: &str, file_size: u64) -> MediaItem {
+    MediaItem {
+        id: Uuid::new_v5(&Uuid::NAMESPACE_URL, path.as_bytes()),
+        path: PathBuf::from(path),
+        file_size,
+        modified: None,
+        mime: "video/mp4",
+        kind: MediaKind::Video,
+        meta: MediaMeta::default(),
+    }
+}
+
+#[test]
+fn new_library_update_id_starts_at_one() {
+    assert_eq!(MediaLibrary::new().update_id, 1);
+}
+
+#[test]
+fn upsert_new_item_reports_change() {
+    let mut lib = MediaLibrary::new();
+    assert!(lib.upsert(item("/m/a.mp4", 10)));
+    assert_eq!(lib.items.len(), 1);
+}
+
+#[test]
+fn upsert_identical_item_reports_no_change() {
+    let mut lib = MediaLibrary::new();
+    lib.upsert(item("/m/a.mp4", 10));
+    assert!(!lib.upsert(item("/m/a.mp4", 10)));
+    assert_eq!(lib.items.len(), 1);
+}
+
+#[test]
+fn upsert_modified_item_replaces_in_place() {
+    let mut lib = MediaLibrary::new();
+    lib.upsert(item("/m/a.mp4", 10));
+    assert!(lib.upsert(item("/m/a.mp4", 20)));
+    assert_eq!(lib.items.len(), 1);
+    assert_eq!(lib.items[0].file_size, 20);
+}
+
+#[test]
+fn remove_under_directory_removes_descendants_only() {
+    let mut lib = MediaLibrary::new();
+    lib.upsert(item("/m/show/e1.mp4", 1));
+    lib.upsert(item("/m/show/e2.mp4", 1));
+    lib.upsert(item("/m/showcase.mp4", 1));
+    assert_eq!(lib.remove_under(&PathBuf::from("/m/show")), 2);
+    assert_eq!(lib.items.len(), 1);
+}
+
+#[test]
+fn upsert_finds_items_after_removal_and_extend() {
+    let mut lib = MediaLibrary::new();
+    lib.extend([item("/m/a.mp4", 1), item("/m/b.mp4", 1), item("/m/c.mp4", 1)]);
+    lib.remove_under(&PathBuf::from("/m/a.mp4"));
+    assert!(lib.upsert(item("/m/c.mp4", 2)));
+    assert!(!lib.upsert(item("/m/b.mp4", 1)));
+    assert_eq!(lib.items.len(), 2);
+    assert_eq!(lib.items[1].file_size, 2);
+}
+
+#[test]
+fn upsert_sees_items_assigned_directly() {
+    let mut lib = MediaLibrary::new();
+    lib.upsert(item("/m/a.mp4", 1));
+    lib.upsert(item("/m/b.mp4", 1));
+    lib.items.reverse();
+    assert!(lib.upsert(item("/m/a.mp4", 2)));
+    assert_eq!(lib.items.len(), 2);
+    assert_eq!(lib.items[1].file_size, 2);
+    lib.items = vec![item("/m/z.mp4", 1)];
+    assert!(!lib.upsert(item("/m/z.mp4", 1)));
+    assert_eq!(lib.items.len(), 1);
+}
+
+#[test]
+fn bump_update_id_wraps_to_one() {
+    let mut lib = MediaLibrary::new();
+    lib.update_id = u32::MAX;
+    lib.bump_update_id();
+    assert_eq!(lib.update_id, 1);
+}
+
+#[test]
+fn diff_counts_added_removed_and_changed() {
+    let mut old = MediaLibrary::new();
+    old.upsert(item("/m/kept.mp4", 1));
+    old.upsert(item("/m/gone.mp4", 1));
+    old.upsert(item("/m/edited.mp4", 1));
+    let mut new = MediaLibrary::new();
+    new.upsert(item("/m/kept.mp4", 1));
+    new.upsert(item("/m/edited.mp4", 2));
+    new.upsert(item("/m/new.mp4", 1));
+    let diff = old.diff(&new);
+    assert_eq!((diff.added, diff.removed, diff.changed), (1, 1, 1));
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn diff_of_identical_libraries_is_empty() {
+    let mut lib = MediaLibrary::new();
+    lib.upsert(item("/m/a.mp4", 1));
+    let mut same = MediaLibrary::new();
+    same.upsert(item("/m/a.mp4", 1));
+    assert!(lib.diff(&same).is_empty());
+}
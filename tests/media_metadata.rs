@@ -1,7 +1,11 @@
+mod common;
+
+use common::temp_file;
 use std::path::PathBuf;
 use uuid::Uuid;
 use udlna::media::metadata::{
-    build_machine_namespace, dlna_profile_for, extract_metadata, format_upnp_duration, media_item_id,
+    build_machine_namespace, date_from_file_name, extract_metadata, format_upnp_duration,
+    media_item_id, parse_upnp_duration,
 };
 use udlna::media::mime::MediaKind;
 
@@ -43,81 +47,13 @@ fn duration_max_realistic() {
     assert_eq!(format_upnp_duration(secs, 0.999), "99:59:59.999");
 }
 
-// ── dlna_profile_for ─────────────────────────────────────────────────────────
-
-#[test]
-fn profile_audio_mpeg_is_mp3() {
-    assert_eq!(dlna_profile_for("audio/mpeg"), Some("MP3"));
-}
-
-#[test]
-fn profile_audio_mp4_is_aac() {
-    assert_eq!(dlna_profile_for("audio/mp4"), Some("AAC_ISO_320"));
-}
-
-#[test]
-fn profile_image_jpeg_is_jpeg_lrg() {
-    assert_eq!(dlna_profile_for("image/jpeg"), Some("JPEG_LRG"));
-}
-
-#[test]
-fn profile_image_png_is_png_lrg() {
-    assert_eq!(dlna_profile_for("image/png"), Some("PNG_LRG"));
-}
-
-#[test]
-fn profile_audio_ogg_is_none() {
-    assert_eq!(dlna_profile_for("audio/ogg"), None);
-}
-
-#[test]
-fn profile_audio_flac_is_none() {
-    assert_eq!(dlna_profile_for("audio/flac"), None);
-}
-
-#[test]
-fn profile_audio_wav_is_none() {
-    assert_eq!(dlna_profile_for("audio/wav"), None);
-}
-
-#[test]
-fn profile_audio_wma_is_none() {
-    assert_eq!(dlna_profile_for("audio/x-ms-wma"), None);
-}
-
-#[test]
-fn profile_video_mp4_is_none_phase2() {
-    assert_eq!(dlna_profile_for("video/mp4"), None);
-}
-
-#[test]
-fn profile_video_mkv_is_none() {
-    assert_eq!(dlna_profile_for("video/x-matroska"), None);
-}
-
-#[test]
-fn profile_video_avi_is_none() {
-    assert_eq!(dlna_profile_for("video/x-msvideo"), None);
-}
-
 #[test]
-fn profile_video_ts_is_none() {
-    assert_eq!(dlna_profile_for("video/MP2T"), None);
-}
-
-#[test]
-fn profile_image_gif_is_none() {
-    assert_eq!(dlna_profile_for("image/gif"), None);
-}
-
-#[test]
-fn profile_application_unknown_is_none() {
-    assert_eq!(dlna_profile_for("application/unknown"), None);
-}
-
-#[test]
-fn profile_arbitrary_string_is_none() {
-    assert_eq!(dlna_profile_for("totally/made-up"), None);
+fn parse_duration_round_trips() {
+    assert_eq!(parse_upnp_duration("01:23:45.500"), Some(5025.5));
+    assert_eq!(parse_upnp_duration(&format_upnp_duration(272, 0.841)), Some(272.841));
+    assert_eq!(parse_upnp_duration("0:00:07"), Some(7.0));
+    assert_eq!(parse_upnp_duration("00:61:00"), None);
+    assert_eq!(parse_upnp_duration("soon"), None);
 }
 
 // ── build_machine_namespace / media_item_id ───────────────────────────────────
@@ -191,3 +127,185 @@ fn extract_metadata_subtitle_returns_none() {
     let result = extract_metadata(&path, MediaKind::Subtitle, "text/srt");
     assert!(result.is_none());
 }
+
+// ── audio tags ────────────────────────────────────────────────────────────────
+
+/// Minimal PCM WAV carrying a RIFF `LIST/INFO` chunk with the given sub-chunks.
+fn write_tagged_wav(path: &std::path::Path, info: &[(&[u8; 4], &str)]) {
+    let mut list = b"INFO".to_vec();
+    for (id, value) in info {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        list.extend_from_slice(*id);
+        list.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        list.extend_from_slice(&data);
+    }
+    let data_len = 1600u32;
+    let mut body = b"WAVEfmt ".to_vec();
+    body.extend_from_slice(&16u32.to_le_bytes());
+    body.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    body.extend_from_slice(&1u16.to_le_bytes()); // mono
+    body.extend_from_slice(&8000u32.to_le_bytes());
+    body.extend_from_slice(&16000u32.to_le_bytes());
+    body.extend_from_slice(&2u16.to_le_bytes());
+    body.extend_from_slice(&16u16.to_le_bytes());
+    body.extend_from_slice(b"LIST");
+    body.extend_from_slice(&(list.len() as u32).to_le_bytes());
+    body.extend_from_slice(&list);
+    body.extend_from_slice(b"data");
+    body.extend_from_slice(&data_len.to_le_bytes());
+    body.resize(body.len() + data_len as usize, 0);
+    let mut buf = b"RIFF".to_vec();
+    buf.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&body);
+    std::fs::write(path, buf).unwrap();
+}
+
+#[test]
+fn extract_audio_reads_riff_info_tags() {
+    let path = temp_file("tagged.wav", &[]);
+    write_tagged_wav(
+        &path,
+        &[
+            (b"INAM", "Blue in Green"),
+            (b"IART", "Miles Davis"),
+            (b"IPRD", "Kind of Blue"),
+            (b"IGNR", "Jazz"),
+            (b"ICRD", "1959-08-17"),
+            (b"IPRT", "3/5"),
+            (b"IMUS", "Bill Evans"),
+        ],
+    );
+    let meta = extract_metadata(&path, MediaKind::Audio, "audio/wav").expect("wav should probe");
+    assert_eq!(meta.title.as_deref(), Some("Blue in Green"));
+    assert_eq!(meta.artist.as_deref(), Some("Miles Davis"));
+    assert_eq!(meta.album.as_deref(), Some("Kind of Blue"));
+    assert_eq!(meta.genre.as_deref(), Some("Jazz"));
+    assert_eq!(meta.year, Some(1959));
+    assert_eq!(meta.track, Some(3));
+    assert_eq!(meta.composer.as_deref(), Some("Bill Evans"));
+    assert_eq!(meta.duration.as_deref(), Some("00:00:00.100"));
+}
+
+#[test]
+fn extract_audio_reads_stream_parameters() {
+    let path = temp_file("params.wav", &[]);
+    write_tagged_wav(&path, &[]);
+    let meta = extract_metadata(&path, MediaKind::Audio, "audio/wav").expect("wav should probe");
+    assert_eq!(meta.sample_rate, Some(8000));
+    assert_eq!(meta.channels, Some(1));
+    assert_eq!(meta.bits_per_sample, Some(16));
+    // 16000 bytes/s of PCM plus the headers, over 0.1 s.
+    let size = std::fs::metadata(&path).unwrap().len() as u32;
+    assert_eq!(meta.bitrate, Some(size * 10));
+    assert_eq!(meta.dlna_profile, Some("LPCM"));
+}
+
+#[test]
+fn extract_audio_ignores_unusable_tag_values() {
+    let path = temp_file("junk-tags.wav", &[]);
+    write_tagged_wav(&path, &[(b"INAM", "   "), (b"ICRD", "unknown"), (b"IPRT", "0")]);
+    let meta = extract_metadata(&path, MediaKind::Audio, "audio/wav").expect("wav should probe");
+    assert_eq!(meta.title, None);
+    assert_eq!(meta.year, None);
+    assert_eq!(meta.track, None);
+}
+
+// ── photo metadata ────────────────────────────────────────────────────────────
+
+/// Baseline JPEG header (SOF0, one component) with an optional EXIF APP1 segment.
+fn write_jpeg(path: &std::path::Path, width: u16, height: u16, fields: &[exif::Field]) {
+    let mut buf = vec![0xFF, 0xD8];
+    if !fields.is_empty() {
+        let mut writer = exif::experimental::Writer::new();
+        for field in fields {
+            writer.push_field(field);
+        }
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+        buf.extend_from_slice(&[0xFF, 0xE1]);
+        buf.extend_from_slice(&((2 + 6 + tiff.len()) as u16).to_be_bytes());
+        buf.extend_from_slice(b"Exif\0\0");
+        buf.extend_from_slice(&tiff);
+    }
+    buf.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x0B, 0x08]);
+    buf.extend_from_slice(&height.to_be_bytes());
+    buf.extend_from_slice(&width.to_be_bytes());
+    buf.extend_from_slice(&[0x01, 0x01, 0x11, 0x00, 0xFF, 0xD9]);
+    std::fs::write(path, buf).unwrap();
+}
+
+fn ascii_field(tag: exif::Tag, text: &str) -> exif::Field {
+    exif::Field {
+        tag,
+        ifd_num: exif::In::PRIMARY,
+        value: exif::Value::Ascii(vec![text.as_bytes().to_vec()]),
+    }
+}
+
+#[test]
+fn extract_image_reads_exif() {
+    let path = temp_file("IMG_20200101_000000.jpg", &[]);
+    let degrees = exif::Rational { num: 1, denom: 1 };
+    write_jpeg(
+        &path,
+        640,
+        480,
+        &[
+            ascii_field(exif::Tag::DateTimeOriginal, "2019:07:04 18:30:05"),
+            ascii_field(exif::Tag::Make, "Canon"),
+            ascii_field(exif::Tag::Model, "EOS 5D"),
+            exif::Field {
+                tag: exif::Tag::Orientation,
+                ifd_num: exif::In::PRIMARY,
+                value: exif::Value::Short(vec![6]),
+            },
+            exif::Field {
+                tag: exif::Tag::GPSLatitude,
+                ifd_num: exif::In::PRIMARY,
+                value: exif::Value::Rational(vec![degrees; 3]),
+            },
+        ],
+    );
+    let meta = extract_metadata(&path, MediaKind::Image, "image/jpeg").expect("jpeg should parse");
+    assert_eq!(meta.resolution.as_deref(), Some("640x480"));
+    assert_eq!(meta.dlna_profile, Some("JPEG_SM"));
+    assert_eq!(meta.taken.as_deref(), Some("2019-07-04T18:30:05"), "EXIF wins over the file name");
+    assert_eq!(meta.orientation, Some(6));
+    assert_eq!(meta.camera_make.as_deref(), Some("Canon"));
+    assert_eq!(meta.camera_model.as_deref(), Some("EOS 5D"));
+    assert!(meta.gps);
+}
+
+#[test]
+fn extract_image_without_exif_uses_file_name_date() {
+    let path = temp_file("PXL_20230115_093012345.jpg", &[]);
+    write_jpeg(&path, 100, 50, &[]);
+    let meta = extract_metadata(&path, MediaKind::Image, "image/jpeg").expect("jpeg should parse");
+    assert_eq!(meta.taken.as_deref(), Some("2023-01-15T09:30:12"));
+    assert_eq!(meta.orientation, None);
+    assert!(!meta.gps);
+}
+
+#[test]
+fn file_name_dates() {
+    let cases = [
+        ("IMG_20230101_142530.jpg", Some("2023-01-01T14:25:30")),
+        ("20230101-142530.jpg", Some("2023-01-01T14:25:30")),
+        ("20230101142530.jpg", Some("2023-01-01T14:25:30")),
+        ("IMG-20230101-WA0003.jpg", Some("2023-01-01T00:00:00")),
+        ("Screenshot_2023-01-02-14-25-30.png", Some("2023-01-02T14:25:30")),
+        ("Photo 2023-01-03 14.25.30.jpg", Some("2023-01-03T14:25:30")),
+        ("IMG_20231345_000000.jpg", None),
+        ("DSC01234.jpg", None),
+        ("1672531200.jpg", None),
+        ("holiday.jpg", None),
+    ];
+    for (name, expected) in cases {
+        assert_eq!(date_from_file_name(name).as_deref(), expected, "{name}");
+    }
+}
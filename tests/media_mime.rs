@@ -51,3 +51,10 @@ fn test_mkv_mime_is_matroska() {
     let (_, mime) = classify(Path::new("video.mkv")).unwrap();
     assert_eq!(mime, "video/x-matroska");
 }
+
+#[test]
+fn test_m4b_audiobook_classified_as_audio() {
+    let (kind, mime) = classify(Path::new("book.m4b")).unwrap();
+    assert_eq!(kind, MediaKind::Audio);
+    assert_eq!(mime, "audio/mp4");
+}
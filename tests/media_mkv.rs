@@ -0,0 +1,263 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::library::{AudioTrack, Chapter};
+use udlna::media::metadata::extract_metadata;
+use udlna::media::mime::MediaKind;
+use udlna::media::mkv::{codec_name, read_info, TrackKind};
+
+/// EBML element with an 8-byte size field.
+fn element(id: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut buf = id.to_vec();
+    buf.push(0x01);
+    buf.extend_from_slice(&(payload.len() as u64).to_be_bytes()[1..]);
+    buf.extend_from_slice(payload);
+    buf
+}
+
+fn uint(id: &[u8], value: u64) -> Vec<u8> {
+    element(id, &value.to_be_bytes())
+}
+
+/// Matroska file whose segment (unknown size) holds `children`.
+fn mkv(children: &[Vec<u8>]) -> Vec<u8> {
+    let mut buf = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"matroska"));
+    buf.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    for child in children {
+        buf.extend_from_slice(child);
+    }
+    buf
+}
+
+/// Info element: TimestampScale and a 64-bit float Duration in ticks.
+fn info(scale: u64, ticks: f64) -> Vec<u8> {
+    element(
+        &[0x15, 0x49, 0xA9, 0x66],
+        &[uint(&[0x2A, 0xD7, 0xB1], scale), element(&[0x44, 0x89], &ticks.to_be_bytes())].concat(),
+    )
+}
+
+fn video_track(number: u64, codec: &str, width: u64, height: u64) -> Vec<u8> {
+    let video = element(&[0xE0], &[uint(&[0xB0], width), uint(&[0xBA], height)].concat());
+    let entry = [uint(&[0xD7], number), uint(&[0x83], 1), element(&[0x86], codec.as_bytes()), video].concat();
+    element(&[0xAE], &entry)
+}
+
+/// 10-bit HDR10 HEVC track at 23.976 fps: hvcC CodecPrivate, DefaultDuration and Colour.
+fn hdr_video_track(number: u64) -> Vec<u8> {
+    let hvcc = [1, 0x02, 0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 153, 0xF0, 0, 0xFC, 0xFD, 0xFA, 0xFA, 0, 0, 0x0F, 0];
+    let colour = element(&[0x55, 0xB0], &[uint(&[0x55, 0xB2], 10), uint(&[0x55, 0xBA], 16)].concat());
+    let video = element(&[0xE0], &[uint(&[0xB0], 3840), uint(&[0xBA], 2160), colour].concat());
+    let entry = [
+        uint(&[0xD7], number),
+        uint(&[0x83], 1),
+        element(&[0x86], b"V_MPEGH/ISO/HEVC"),
+        element(&[0x63, 0xA2], &hvcc),
+        uint(&[0x23, 0xE3, 0x83], 41_708_333),
+        video,
+    ]
+    .concat();
+    element(&[0xAE], &entry)
+}
+
+fn audio_track(number: u64, codec: &str, language: Option<&str>, channels: u64) -> Vec<u8> {
+    let audio = element(&[0xE1], &[element(&[0xB5], &48000f32.to_be_bytes()), uint(&[0x9F], channels)].concat());
+    let mut entry = [uint(&[0xD7], number), uint(&[0x83], 2), element(&[0x86], codec.as_bytes())].concat();
+    if let Some(language) = language {
+        entry.extend(element(&[0x22, 0xB5, 0x9C], language.as_bytes()));
+    }
+    entry.extend(audio);
+    element(&[0xAE], &entry)
+}
+
+fn tracks(entries: &[Vec<u8>]) -> Vec<u8> {
+    element(&[0x16, 0x54, 0xAE, 0x6B], &entries.concat())
+}
+
+fn cluster() -> Vec<u8> {
+    element(&[0x1F, 0x43, 0xB6, 0x75], &[0u8; 64])
+}
+
+/// ChapterAtom starting at `ms` milliseconds, optionally titled and hidden.
+fn chapter(ms: u64, title: Option<&str>, hidden: bool) -> Vec<u8> {
+    let mut atom = uint(&[0x91], ms * 1_000_000);
+    if hidden {
+        atom.extend(uint(&[0x98], 1));
+    }
+    if let Some(title) = title {
+        atom.extend(element(&[0x80], &element(&[0x85], title.as_bytes())));
+    }
+    element(&[0xB6], &atom)
+}
+
+fn chapters(editions: &[Vec<Vec<u8>>]) -> Vec<u8> {
+    let editions: Vec<u8> = editions.iter().flat_map(|atoms| element(&[0x45, 0xB9], &atoms.concat())).collect();
+    element(&[0x10, 0x43, 0xA7, 0x70], &editions)
+}
+
+// ── read_info ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn reads_duration_and_tracks() {
+    let path = temp_file(
+        "tracks.mkv",
+        &mkv(&[
+            info(1_000_000, 5_025_500.0),
+            tracks(&[
+                video_track(1, "V_MPEGH/ISO/HEVC", 3840, 2160),
+                audio_track(2, "A_EAC3", Some("ger"), 6),
+                audio_track(3, "A_AAC", None, 2),
+            ]),
+            cluster(),
+        ]),
+    );
+    let found = read_info(&path).expect("tracks expected");
+    assert_eq!(found.duration, Some(5025.5));
+    assert_eq!(found.tracks.len(), 3);
+    let video = &found.tracks[0];
+    assert_eq!(video.kind, TrackKind::Video);
+    assert_eq!((video.width, video.height), (Some(3840), Some(2160)));
+    let audio = &found.tracks[1];
+    assert_eq!((audio.kind, audio.language.as_str(), audio.channels), (TrackKind::Audio, "ger", Some(6)));
+    assert_eq!(audio.sample_rate, Some(48000.0));
+    assert_eq!(found.tracks[2].language, "eng", "Matroska's default language");
+}
+
+#[test]
+fn duration_uses_timestamp_scale() {
+    let path = temp_file(
+        "scale.webm",
+        &mkv(&[info(1_000, 90_000_000.0), tracks(&[video_track(1, "V_VP9", 640, 360)])]),
+    );
+    assert_eq!(read_info(&path).unwrap().duration, Some(90.0));
+}
+
+#[test]
+fn language_ietf_overrides_language() {
+    let entry = [
+        uint(&[0xD7], 1),
+        uint(&[0x83], 2),
+        element(&[0x86], b"A_OPUS"),
+        element(&[0x22, 0xB5, 0x9C], b"por"),
+        element(&[0x22, 0xB5, 0x9D], b"pt-BR"),
+    ]
+    .concat();
+    let path = temp_file("ietf.mkv", &mkv(&[tracks(&[element(&[0xAE], &entry)])]));
+    assert_eq!(read_info(&path).unwrap().tracks[0].language, "pt-BR");
+}
+
+#[test]
+fn tracks_after_clusters_are_found_through_seek_head() {
+    let body = tracks(&[video_track(1, "V_AV1", 1920, 1080)]);
+    // SeekHead with one 4-byte ID and one 8-byte position, each in 8-byte-size elements.
+    let seek_head_len = 4 + 8 + 2 + 8 + (2 + 8 + 4) + (2 + 8 + 8);
+    let position = (seek_head_len + cluster().len()) as u64;
+    let seek = [element(&[0x53, 0xAB], &[0x16, 0x54, 0xAE, 0x6B]), element(&[0x53, 0xAC], &position.to_be_bytes())];
+    let seek_head = element(&[0x11, 0x4D, 0x9B, 0x74], &element(&[0x4D, 0xBB], &seek.concat()));
+    assert_eq!(seek_head.len(), seek_head_len);
+    let path = temp_file("late.mkv", &mkv(&[seek_head, cluster(), body]));
+    assert_eq!(read_info(&path).unwrap().tracks[0].width, Some(1920));
+}
+
+#[test]
+fn reads_visible_chapters_of_first_edition() {
+    let path = temp_file(
+        "chapters.mkv",
+        &mkv(&[
+            info(1_000_000, 600_000.0),
+            tracks(&[video_track(1, "V_MPEG4/ISO/AVC", 1920, 1080)]),
+            chapters(&[
+                vec![chapter(0, Some("Intro"), false), chapter(5_000, None, true), chapter(90_500, None, false)],
+                vec![chapter(0, Some("Director's cut"), false)],
+            ]),
+            cluster(),
+        ]),
+    );
+    assert_eq!(
+        read_info(&path).unwrap().chapters,
+        vec![Chapter { start: 0.0, title: Some("Intro".to_string()) }, Chapter { start: 90.5, title: None }]
+    );
+}
+
+/// SeekHead whose entries point `id` at each segment-relative position.
+fn seek_head(entries: &[(&[u8], u64)]) -> Vec<u8> {
+    let seeks: Vec<u8> = entries
+        .iter()
+        .flat_map(|(id, position)| {
+            let seek = [element(&[0x53, 0xAB], id), element(&[0x53, 0xAC], &position.to_be_bytes())].concat();
+            element(&[0x4D, 0xBB], &seek)
+        })
+        .collect();
+    element(&[0x11, 0x4D, 0x9B, 0x74], &seeks)
+}
+
+#[test]
+fn self_referencing_seek_head_terminates() {
+    const TRACKS: &[u8] = &[0x16, 0x54, 0xAE, 0x6B];
+    // The first SeekHead points at itself, at the second one past the cluster, and past
+    // the end of the address space; the second one points at itself and back.
+    let first_len = seek_head(&[(TRACKS, 0), (TRACKS, 0), (TRACKS, u64::MAX)]).len() as u64;
+    let second = first_len + cluster().len() as u64;
+    let path = temp_file(
+        "loop.mkv",
+        &mkv(&[
+            seek_head(&[(TRACKS, 0), (TRACKS, second), (TRACKS, u64::MAX)]),
+            cluster(),
+            seek_head(&[(TRACKS, second), (TRACKS, 0)]),
+        ]),
+    );
+    assert!(read_info(&path).is_none());
+}
+
+#[test]
+fn rejects_non_matroska() {
+    let path = temp_file("fake.mkv", b"RIFF\0\0\0\0AVI LIST");
+    assert!(read_info(&path).is_none());
+}
+
+#[test]
+fn codec_names() {
+    assert_eq!(codec_name("V_MPEG4/ISO/AVC"), "h264");
+    assert_eq!(codec_name("V_MPEG4/ISO/ASP"), "mpeg4");
+    assert_eq!(codec_name("A_AAC/MPEG4/LC"), "aac");
+    assert_eq!(codec_name("A_DTS/EXPRESS"), "dts");
+    assert_eq!(codec_name("S_TEXT/UTF8"), "S_TEXT/UTF8");
+}
+
+// ── extract_metadata ──────────────────────────────────────────────────────────
+
+#[test]
+fn mkv_metadata_has_resolution_duration_and_audio_tracks() {
+    let path = temp_file(
+        "movie.mkv",
+        &mkv(&[
+            info(1_000_000, 5_025_500.0),
+            tracks(&[
+                video_track(1, "V_MPEG4/ISO/AVC", 1920, 800),
+                audio_track(2, "A_AC3", Some("und"), 6),
+            ]),
+        ]),
+    );
+    let meta = extract_metadata(&path, MediaKind::Video, "video/x-matroska").expect("metadata expected");
+    assert_eq!(meta.duration.as_deref(), Some("01:23:45.500"));
+    assert_eq!(meta.resolution.as_deref(), Some("1920x800"));
+    assert_eq!(meta.video_codec.as_deref(), Some("h264"));
+    assert_eq!(meta.dlna_profile, None, "DLNA defines no Matroska profiles");
+    assert_eq!(
+        meta.audio_tracks,
+        vec![AudioTrack { codec: "ac3".to_string(), language: None, channels: Some(6) }]
+    );
+}
+
+#[test]
+fn mkv_metadata_has_hdr_video_details() {
+    let path = temp_file("hdr.mkv", &mkv(&[tracks(&[hdr_video_track(1), audio_track(2, "A_EAC3", Some("ger"), 6)])]));
+    let meta = extract_metadata(&path, MediaKind::Video, "video/x-matroska").expect("metadata expected");
+    assert_eq!(meta.video_codec.as_deref(), Some("hevc"));
+    assert_eq!(meta.video_profile.as_deref(), Some("Main 10"));
+    assert_eq!(meta.video_level.as_deref(), Some("5.1"));
+    assert_eq!(meta.video_bit_depth, Some(10));
+    assert_eq!(meta.frame_rate, Some(23.976));
+    assert_eq!(meta.transfer.as_deref(), Some("smpte2084"));
+    assert_eq!(meta.audio_tracks[0].language.as_deref(), Some("ger"));
+}
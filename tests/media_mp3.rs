@@ -0,0 +1,114 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::metadata::extract_metadata;
+use udlna::media::mime::MediaKind;
+use udlna::media::mp3::{read_info, DurationSource};
+
+/// MPEG-1 Layer III, 128 kbps, 44.1 kHz, stereo: 417-byte frames of 1152 samples.
+const HEADER: [u8; 4] = [0xFF, 0xFB, 0x90, 0x00];
+const FRAME_LEN: usize = 417;
+const FRAME_SECONDS: f64 = 1152.0 / 44100.0;
+
+fn frame() -> Vec<u8> {
+    let mut frame = HEADER.to_vec();
+    frame.resize(FRAME_LEN, 0);
+    frame
+}
+
+fn frames(count: usize) -> Vec<u8> {
+    frame().repeat(count)
+}
+
+/// A frame carrying `tag` right after the side information (offset 36).
+fn tag_frame(tag: &[u8]) -> Vec<u8> {
+    let mut frame = frame();
+    frame[36..36 + tag.len()].copy_from_slice(tag);
+    frame
+}
+
+fn xing(frames: u32, bytes: u32) -> Vec<u8> {
+    [&b"Xing"[..], &3u32.to_be_bytes(), &frames.to_be_bytes(), &bytes.to_be_bytes()].concat()
+}
+
+fn id3v2(body_len: usize) -> Vec<u8> {
+    let mut tag = b"ID3\x04\x00\x00\x00\x00\x00".to_vec();
+    tag.push(body_len as u8);
+    tag.resize(10 + body_len, 0);
+    tag
+}
+
+// ── Headers ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn xing_header_gives_frame_count_and_bitrate() {
+    let data = [id3v2(64), tag_frame(&xing(1000, 417_000)), frames(10)].concat();
+    let info = read_info(&temp_file("xing.mp3", &data)).expect("info expected");
+    assert_eq!(info.source, DurationSource::Header);
+    assert!((info.duration - 1000.0 * FRAME_SECONDS).abs() < 1e-6);
+    assert_eq!(info.bitrate, (417_000.0 * 8.0 / info.duration).round() as u32);
+    assert_eq!((info.sample_rate, info.channels), (44100, 2));
+}
+
+#[test]
+fn lame_tag_removes_encoder_delay_and_padding() {
+    let flags = 0x0Fu32;
+    let mut lame = [&b"Xing"[..], &flags.to_be_bytes(), &1000u32.to_be_bytes(), &417_000u32.to_be_bytes()].concat();
+    lame.extend_from_slice(&[0; 104]); // TOC and quality
+    lame.extend_from_slice(b"LAME3.100");
+    lame.extend_from_slice(&[0; 12]);
+    lame.extend_from_slice(&[0x24, 0x04, 0x80]); // delay 576, padding 1152
+    let info = read_info(&temp_file("lame.mp3", &[tag_frame(&lame), frames(4)].concat())).unwrap();
+    assert!((info.duration - (1000.0 * 1152.0 - 1728.0) / 44100.0).abs() < 1e-6);
+}
+
+#[test]
+fn vbri_header_gives_frame_count() {
+    let vbri = [&b"VBRI\x00\x01\x00\x00\x00\x4B"[..], &200_000u32.to_be_bytes(), &500u32.to_be_bytes()].concat();
+    let info = read_info(&temp_file("vbri.mp3", &[tag_frame(&vbri), frames(4)].concat())).unwrap();
+    assert_eq!(info.source, DurationSource::Header);
+    assert!((info.duration - 500.0 * FRAME_SECONDS).abs() < 1e-6);
+    assert_eq!(info.bitrate, (200_000.0 * 8.0 / info.duration).round() as u32);
+}
+
+// ── Frame scanning ────────────────────────────────────────────────────────────
+
+#[test]
+fn frames_are_counted_without_a_header() {
+    let data = [id3v2(20), frames(100), b"TAG".to_vec(), vec![0; 125]].concat();
+    let info = read_info(&temp_file("cbr.mp3", &data)).unwrap();
+    assert_eq!(info.source, DurationSource::Scan);
+    assert!((info.duration - 100.0 * FRAME_SECONDS).abs() < 1e-6);
+    assert_eq!(info.bitrate, (FRAME_LEN as f64 * 8.0 / FRAME_SECONDS).round() as u32);
+}
+
+#[test]
+fn scanning_reads_past_the_first_frames() {
+    // 1000 frames (407 KiB) is well beyond what is read to look for a header.
+    let info = read_info(&temp_file("long.mp3", &frames(1000))).unwrap();
+    assert_eq!(info.source, DurationSource::Scan);
+    assert!((info.duration - 1000.0 * FRAME_SECONDS).abs() < 1e-6);
+}
+
+#[test]
+fn scanning_skips_junk_and_false_syncs() {
+    let data = [vec![0xFF, 0xFB, 0x90, 0x00, 1, 2, 3], frames(50), vec![0x55; 300], frames(50)].concat();
+    let info = read_info(&temp_file("junk.mp3", &data)).unwrap();
+    assert!((info.duration - 100.0 * FRAME_SECONDS).abs() < 1e-6);
+}
+
+#[test]
+fn not_mpeg_audio() {
+    assert!(read_info(&temp_file("noise.mp3", &[0x12; 4096])).is_none());
+}
+
+// ── extract_metadata ──────────────────────────────────────────────────────────
+
+#[test]
+fn mp3_metadata_uses_xing_duration() {
+    let data = [tag_frame(&xing(2297, 957_849)), frames(20)].concat();
+    let meta = extract_metadata(&temp_file("track.mp3", &data), MediaKind::Audio, "audio/mpeg").expect("metadata");
+    assert_eq!(meta.duration.as_deref(), Some("00:01:00.003"));
+    assert!(meta.bitrate.is_some_and(|b| (15_900..16_100).contains(&b)), "bytes per second");
+    assert_eq!((meta.sample_rate, meta.channels), (Some(44100), Some(2)));
+}
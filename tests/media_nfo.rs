@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use udlna::media::nfo::{is_sidecar, parse, read_sidecar};
+
+const MOVIE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes" ?>
+<movie>
+    <title>Tom &amp; Jerry: The Movie</title>
+    <originaltitle>Tom and Jerry</originaltitle>
+    <rating>5.4</rating>
+    <outline>Cat &#38; mouse.</outline>
+    <plot><![CDATA[Tom and Jerry team up <again>.]]></plot>
+    <tagline>Friends for life</tagline>
+    <mpaa>Rated G</mpaa>
+    <genre>Animation</genre>
+    <genre>Family</genre>
+    <genre>Animation</genre>
+    <director>Phil Roman</director>
+    <premiered>1992-10-01</premiered>
+    <actor>
+        <name>Richard Kind</name>
+        <role>Tom</role>
+    </actor>
+    <actor>
+        <name>Dana Hill</name>
+        <role>Jerry</role>
+        <thumb>https://example.org/hill.jpg</thumb>
+    </actor>
+</movie>
+https://www.themoviedb.org/movie/12233
+"#;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("udlna-nfo-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// ── parse ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn parses_kodi_movie() {
+    let nfo = parse(MOVIE).expect("movie expected");
+    assert_eq!(nfo.title.as_deref(), Some("Tom & Jerry: The Movie"));
+    assert_eq!(nfo.outline.as_deref(), Some("Cat & mouse."), "outline wins over tagline");
+    assert_eq!(nfo.plot.as_deref(), Some("Tom and Jerry team up <again>."));
+    assert_eq!(nfo.year, Some(1992), "from premiered");
+    assert_eq!(nfo.genres, vec!["Animation", "Family"]);
+    assert_eq!(nfo.rating.as_deref(), Some("G"));
+    assert_eq!(nfo.directors, vec!["Phil Roman"]);
+    assert_eq!(nfo.actors, vec!["Richard Kind", "Dana Hill"]);
+}
+
+#[test]
+fn parses_episode_details() {
+    let text = "<episodedetails><title>Pilot</title><aired>2008-01-20</aired><year>2007</year>\
+                <mpaa>US:TV-14</mpaa></episodedetails>";
+    let nfo = parse(text).expect("episode expected");
+    assert_eq!(nfo.title.as_deref(), Some("Pilot"));
+    assert_eq!(nfo.year, Some(2007), "year wins over aired");
+    assert_eq!(nfo.rating.as_deref(), Some("TV-14"));
+}
+
+#[test]
+fn other_documents_are_not_descriptions() {
+    assert!(parse("https://www.imdb.com/title/tt0104254/").is_none(), "scraper URL only");
+    assert!(parse("<tvshow><title>Show</title></tvshow>").is_none());
+    assert!(parse("<movie><title>Broken</movie>").is_none());
+}
+
+// ── read_sidecar ──────────────────────────────────────────────────────────────
+
+#[test]
+fn basename_sidecar_wins_over_movie_nfo() {
+    let dir = temp_dir("sidecar");
+    std::fs::write(dir.join("movie.nfo"), "<movie><title>Folder</title></movie>").unwrap();
+    std::fs::write(dir.join("film.nfo"), "<movie><title>Own</title></movie>").unwrap();
+    std::fs::write(dir.join("other.mp4"), b"").unwrap();
+    let title = |name: &str| read_sidecar(&dir.join(name)).and_then(|n| n.title);
+    assert_eq!(title("film.mkv").as_deref(), Some("Own"));
+    assert_eq!(title("other.mp4").as_deref(), Some("Folder"));
+    assert!(read_sidecar(&temp_dir("bare").join("film.mkv")).is_none());
+}
+
+#[test]
+fn movie_nfo_skips_extras_next_to_the_movie() {
+    let dir = temp_dir("extras").join("Heat (1995)");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("movie.nfo"), "<movie><title>Heat</title></movie>").unwrap();
+    for video in ["heat (1995).mkv", "Heat-trailer.mkv", "Heat-sample.mkv"] {
+        std::fs::write(dir.join(video), b"").unwrap();
+    }
+    let title = |name: &str| read_sidecar(&dir.join(name)).and_then(|n| n.title);
+    assert_eq!(title("heat (1995).mkv").as_deref(), Some("Heat"), "named like the folder");
+    assert_eq!(title("Heat-trailer.mkv"), None);
+    assert_eq!(title("Heat-sample.mkv"), None);
+}
+
+#[test]
+fn sidecar_names() {
+    assert!(is_sidecar(&PathBuf::from("/m/movie.nfo")));
+    assert!(is_sidecar(&PathBuf::from("/m/Film.NFO")));
+    assert!(!is_sidecar(&PathBuf::from("/m/film.mkv")));
+}
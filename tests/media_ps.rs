@@ -0,0 +1,127 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::metadata::extract_metadata;
+use udlna::media::mime::MediaKind;
+use udlna::media::ps::read_info;
+
+const SCR_HZ: u64 = 90_000;
+
+/// MPEG-2 pack header with the SCR at `seconds`.
+fn pack(seconds: u64) -> Vec<u8> {
+    let s = seconds * SCR_HZ;
+    vec![
+        0,
+        0,
+        1,
+        0xBA,
+        0x44 | ((s >> 30 & 0x07) as u8) << 3 | (s >> 28 & 0x03) as u8,
+        (s >> 20) as u8,
+        0x04 | ((s >> 15 & 0x1F) as u8) << 3 | (s >> 13 & 0x03) as u8,
+        (s >> 5) as u8,
+        0x04 | ((s & 0x1F) as u8) << 3,
+        0x01,
+        0x01,
+        0x89,
+        0xC3,
+        0xF8,
+    ]
+}
+
+/// MPEG-1 pack header with the SCR at `seconds`.
+fn mpeg1_pack(seconds: u64) -> Vec<u8> {
+    let s = seconds * SCR_HZ;
+    vec![
+        0,
+        0,
+        1,
+        0xBA,
+        0x21 | ((s >> 30 & 0x07) as u8) << 1,
+        (s >> 22) as u8,
+        0x01 | ((s >> 15 & 0x7F) as u8) << 1,
+        (s >> 7) as u8,
+        0x01 | ((s & 0x7F) as u8) << 1,
+        0x80,
+        0x00,
+        0x01,
+    ]
+}
+
+/// MPEG-2 PES packet with an empty optional header.
+fn pes(stream_id: u8, payload: &[u8]) -> Vec<u8> {
+    let len = (3 + payload.len()) as u16;
+    [&[0, 0, 1, stream_id][..], &len.to_be_bytes(), &[0x80, 0x00, 0x00], payload].concat()
+}
+
+/// MPEG-1 PES packet without timestamps.
+fn mpeg1_pes(stream_id: u8, payload: &[u8]) -> Vec<u8> {
+    let len = (1 + payload.len()) as u16;
+    [&[0, 0, 1, stream_id][..], &len.to_be_bytes(), &[0x0F], payload].concat()
+}
+
+/// Sequence header for `width`x`height`, then a sequence extension when `mpeg2`.
+fn sequence(width: u32, height: u32, mpeg2: bool) -> Vec<u8> {
+    let size = [(width >> 4) as u8, ((width & 0x0F) << 4 | height >> 8) as u8, height as u8];
+    let mut es = [&[0, 0, 1, 0xB3][..], &size, &[0x33, 0xFF, 0xFF, 0xE0, 0x18]].concat();
+    if mpeg2 {
+        es.extend_from_slice(&[0, 0, 1, 0xB5, 0x14, 0x8A, 0x00, 0x01, 0x00, 0x00]);
+    }
+    es
+}
+
+fn dvd(start: u64, end: u64) -> Vec<u8> {
+    [
+        pack(start),
+        pes(0xE0, &sequence(720, 576, true)),
+        pes(0xBD, &[0x80, 1, 0, 1, 0x0B, 0x77]),
+        pes(0xC0, &[0xFF, 0xFD]),
+        pes(0xBD, &[0x80, 1, 0, 1, 0x0B, 0x77]),
+        pack(end),
+        pes(0xE0, &[0; 64]),
+    ]
+    .concat()
+}
+
+// ── read_info ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn reads_dvd_program_stream() {
+    let info = read_info(&temp_file("dvd.mpg", &dvd(2, 92))).expect("pack header expected");
+    assert_eq!(info.duration, Some(90.0));
+    assert_eq!(info.video_codec, Some("mpeg2video"));
+    assert_eq!(info.size, Some((720, 576)));
+    let audio: Vec<_> = info.audio.iter().map(|a| a.codec.as_str()).collect();
+    assert_eq!(audio, vec!["ac3", "mp2"]);
+}
+
+#[test]
+fn reads_mpeg1_system_stream() {
+    let data = [
+        mpeg1_pack(0),
+        mpeg1_pes(0xE0, &sequence(352, 288, false)),
+        mpeg1_pes(0xC0, &[0xFF, 0xFD]),
+        mpeg1_pack(30),
+    ]
+    .concat();
+    let info = read_info(&temp_file("vcd.mpg", &data)).expect("pack header expected");
+    assert_eq!(info.duration, Some(30.0));
+    assert_eq!(info.video_codec, Some("mpeg1video"));
+    assert_eq!(info.size, Some((352, 288)));
+}
+
+#[test]
+fn not_a_program_stream() {
+    assert!(read_info(&temp_file("noise.mpg", &[0u8; 4096])).is_none());
+}
+
+// ── extract_metadata ──────────────────────────────────────────────────────────
+
+#[test]
+fn ps_metadata_has_resolution_and_profile() {
+    let path = temp_file("movie.mpg", &dvd(0, 60));
+    let meta = extract_metadata(&path, MediaKind::Video, "video/mpeg").expect("metadata expected");
+    assert_eq!(meta.duration.as_deref(), Some("00:01:00.000"));
+    assert_eq!(meta.resolution.as_deref(), Some("720x576"));
+    assert_eq!(meta.video_codec.as_deref(), Some("mpeg2video"));
+    assert_eq!(meta.dlna_profile, Some("MPEG_PS_PAL"));
+}
@@ -1,5 +1,11 @@
 use std::path::PathBuf;
-use udlna::media::scanner::scan;
+use udlna::media::filter::{ScanFilter, SymlinkPolicy};
+use udlna::media::library::NamedLibrary;
+use udlna::media::mime::MediaKind;
+use udlna::media::scanner::{
+    default_threads, innermost_root, kind_limits, scan, scan_into, scan_subtree, scan_with, scan_with_stats,
+    ScanConfig,
+};
 
 #[test]
 fn scan_nonexistent_path_returns_empty_library() {
@@ -14,3 +20,382 @@ fn scan_empty_paths_returns_empty_library() {
     let library = scan(&paths);
     assert_eq!(library.items.len(), 0);
 }
+
+fn temp_media_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("udlna-scan-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write a minimal valid 8 kHz mono PCM WAV (0.1 s of silence) that symphonia can probe.
+fn write_wav(path: &std::path::Path) {
+    let samples = 800u32;
+    let data_len = samples * 2;
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"RIFF");
+    buf.extend_from_slice(&(36 + data_len).to_le_bytes());
+    buf.extend_from_slice(b"WAVEfmt ");
+    buf.extend_from_slice(&16u32.to_le_bytes());
+    buf.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    buf.extend_from_slice(&1u16.to_le_bytes()); // mono
+    buf.extend_from_slice(&8000u32.to_le_bytes());
+    buf.extend_from_slice(&16000u32.to_le_bytes());
+    buf.extend_from_slice(&2u16.to_le_bytes());
+    buf.extend_from_slice(&16u16.to_le_bytes());
+    buf.extend_from_slice(b"data");
+    buf.extend_from_slice(&data_len.to_le_bytes());
+    buf.resize(buf.len() + data_len as usize, 0);
+    std::fs::write(path, buf).unwrap();
+}
+
+#[test]
+fn parallel_scan_order_is_deterministic() {
+    let dir = temp_media_dir("order");
+    for name in ["d.wav", "a.wav", "c.wav", "b.wav", "e.wav"] {
+        write_wav(&dir.join(name));
+    }
+    let config = ScanConfig { threads: Some(4), ..Default::default() };
+    let names = |lib: &udlna::media::library::MediaLibrary| -> Vec<String> {
+        lib.items
+            .iter()
+            .map(|it| it.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    };
+    let first = names(&scan_with(std::slice::from_ref(&dir), &config));
+    assert_eq!(first, ["a.wav", "b.wav", "c.wav", "d.wav", "e.wav"]);
+    let single = ScanConfig { threads: Some(1), ..Default::default() };
+    assert_eq!(names(&scan_with(std::slice::from_ref(&dir), &single)), first);
+}
+
+#[test]
+fn default_threads_is_at_least_one() {
+    assert!(default_threads(&std::env::temp_dir()) >= 1);
+}
+
+#[test]
+fn scan_into_publishes_items_and_bumps_update_id() {
+    let dir = temp_media_dir("into");
+    write_wav(&dir.join("a.wav"));
+    write_wav(&dir.join("b.wav"));
+    let library = std::sync::RwLock::new(udlna::media::library::MediaLibrary::new());
+    scan_into(std::slice::from_ref(&dir), &ScanConfig::default(), &library);
+    let lib = library.read().unwrap();
+    assert_eq!(lib.items.len(), 2);
+    assert!(lib.update_id > 1, "each published batch should bump SystemUpdateID");
+}
+
+#[test]
+fn extensionless_media_is_sniffed() {
+    let dir = temp_media_dir("sniff");
+    write_wav(&dir.join("RECORDING"));
+    let lib = scan(std::slice::from_ref(&dir));
+    assert_eq!(lib.items.len(), 1);
+    assert_eq!(lib.items[0].mime, "audio/wav");
+}
+
+// ── Filters ───────────────────────────────────────────────────────────────────
+
+fn file_names(lib: &udlna::media::library::MediaLibrary) -> Vec<String> {
+    let mut names: Vec<String> = lib
+        .items
+        .iter()
+        .map(|it| it.path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+fn filtered(include: &[&str], exclude: &[&str], skip_hidden: bool) -> ScanConfig {
+    let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    ScanConfig {
+        filter: ScanFilter::new(&owned(include), &owned(exclude), skip_hidden).unwrap(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn exclude_globs_skip_files_and_directories() {
+    let dir = temp_media_dir("exclude");
+    std::fs::create_dir_all(dir.join("Movie/Samples")).unwrap();
+    write_wav(&dir.join("Movie/keep.wav"));
+    write_wav(&dir.join("Movie/Samples/sample.wav"));
+    write_wav(&dir.join("Movie/copying.partial.wav"));
+    let config = filtered(&[], &["**/Samples/**", "*.partial.wav"], true);
+    let (lib, stats) = scan_with_stats(std::slice::from_ref(&dir), &config);
+    assert_eq!(file_names(&lib), ["keep.wav"]);
+    assert_eq!(stats.excluded, 2);
+}
+
+#[test]
+fn include_globs_restrict_files() {
+    let dir = temp_media_dir("include");
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    write_wav(&dir.join("sub/a.wav"));
+    write_wav(&dir.join("sub/b.wav"));
+    let config = filtered(&["**/a.wav"], &[], true);
+    assert_eq!(file_names(&scan_with(std::slice::from_ref(&dir), &config)), ["a.wav"]);
+}
+
+#[test]
+fn hidden_files_skipped_unless_disabled() {
+    let dir = temp_media_dir("hidden");
+    std::fs::create_dir_all(dir.join(".private")).unwrap();
+    write_wav(&dir.join("shown.wav"));
+    write_wav(&dir.join(".dot.wav"));
+    write_wav(&dir.join(".private/inner.wav"));
+    let roots = std::slice::from_ref(&dir);
+    assert_eq!(file_names(&scan_with(roots, &filtered(&[], &[], true))), ["shown.wav"]);
+    assert_eq!(scan_with(roots, &filtered(&[], &[], false)).items.len(), 3);
+}
+
+#[test]
+fn nomedia_marker_hides_directory() {
+    let dir = temp_media_dir("nomedia");
+    std::fs::create_dir_all(dir.join("private")).unwrap();
+    write_wav(&dir.join("public.wav"));
+    write_wav(&dir.join("private/secret.wav"));
+    std::fs::write(dir.join("private/.nomedia"), "").unwrap();
+    let lib = scan_with(std::slice::from_ref(&dir), &ScanConfig::default());
+    assert_eq!(file_names(&lib), ["public.wav"]);
+}
+
+#[test]
+fn udlnaignore_uses_gitignore_rules() {
+    let dir = temp_media_dir("udlnaignore");
+    std::fs::create_dir_all(dir.join("extras")).unwrap();
+    write_wav(&dir.join("a.wav"));
+    write_wav(&dir.join("b.wav"));
+    write_wav(&dir.join("extras/c.wav"));
+    write_wav(&dir.join("extras/keep.wav"));
+    std::fs::write(dir.join(".udlnaignore"), "b.wav\nextras/\n").unwrap();
+    let lib = scan_with(std::slice::from_ref(&dir), &ScanConfig::default());
+    assert_eq!(file_names(&lib), ["a.wav"]);
+
+    // A nested ignore file can re-include what a parent one excluded.
+    std::fs::write(dir.join(".udlnaignore"), "*.wav\n").unwrap();
+    std::fs::write(dir.join("extras/.udlnaignore"), "!keep.wav\n").unwrap();
+    let lib = scan_with(std::slice::from_ref(&dir), &ScanConfig::default());
+    assert_eq!(file_names(&lib), ["keep.wav"]);
+}
+
+#[test]
+fn scan_subtree_applies_ancestor_rules() {
+    let dir = temp_media_dir("subtree");
+    std::fs::create_dir_all(dir.join("Show/Samples")).unwrap();
+    write_wav(&dir.join("Show/ep.wav"));
+    write_wav(&dir.join("Show/Samples/clip.wav"));
+    std::fs::write(dir.join(".udlnaignore"), "ep.wav\n").unwrap();
+    let config = filtered(&[], &["**/Samples/**"], true);
+    let root = std::fs::canonicalize(&dir).unwrap();
+    assert!(scan_subtree(std::slice::from_ref(&root), &root, &root.join("Show"), &config, None).is_empty());
+}
+
+#[test]
+fn invalid_glob_is_an_error() {
+    assert!(ScanFilter::new(&[], &["a[".to_string()], true).is_err());
+}
+
+// ── Walk policy ───────────────────────────────────────────────────────────────
+
+/// Root with `inside/a.wav`, a link `in_link` → `inside`, and a link `out_link` to a
+/// directory outside the root.
+#[cfg(unix)]
+fn symlink_fixture(name: &str) -> PathBuf {
+    let base = temp_media_dir(name);
+    let root = base.join("root");
+    let outside = base.join("outside");
+    std::fs::create_dir_all(root.join("inside")).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    write_wav(&root.join("inside/a.wav"));
+    write_wav(&outside.join("secret.wav"));
+    let _ = std::os::unix::fs::symlink(root.join("inside"), root.join("in_link"));
+    let _ = std::os::unix::fs::symlink(&outside, root.join("out_link"));
+    root
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_followed_only_within_roots_by_default() {
+    let root = symlink_fixture("symlink-within");
+    let (lib, stats) = scan_with_stats(std::slice::from_ref(&root), &ScanConfig::default());
+    // in_link resolves to the same canonical file as inside/a.wav
+    assert!(lib.items.iter().all(|it| it.path.file_name().unwrap() == "a.wav"));
+    assert!(!lib.items.is_empty());
+    assert_eq!(stats.excluded, 1, "out_link should be excluded");
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink_policy_never_and_always() {
+    let root = symlink_fixture("symlink-policy");
+    let roots = std::slice::from_ref(&root);
+    let never = ScanConfig { symlinks: SymlinkPolicy::Never, ..Default::default() };
+    let (lib, stats) = scan_with_stats(roots, &never);
+    assert_eq!(lib.items.len(), 1);
+    assert_eq!(stats.excluded, 2);
+    let always = ScanConfig { symlinks: SymlinkPolicy::Always, ..Default::default() };
+    assert!(file_names(&scan_with(roots, &always)).contains(&"secret.wav".to_string()));
+}
+
+#[test]
+fn max_depth_limits_walk() {
+    let dir = temp_media_dir("depth");
+    std::fs::create_dir_all(dir.join("one/two")).unwrap();
+    write_wav(&dir.join("top.wav"));
+    write_wav(&dir.join("one/mid.wav"));
+    write_wav(&dir.join("one/two/deep.wav"));
+    let config = ScanConfig { max_depth: Some(2), ..Default::default() };
+    assert_eq!(file_names(&scan_with(std::slice::from_ref(&dir), &config)), ["mid.wav", "top.wav"]);
+}
+
+#[test]
+fn symlink_policy_allows_resolved_paths() {
+    let roots = [PathBuf::from("/srv/media")];
+    let inside = std::path::Path::new("/srv/media/a.mkv");
+    let linked = std::path::Path::new("/srv/media/link.mkv");
+    let outside = std::path::Path::new("/etc/passwd");
+    assert!(SymlinkPolicy::WithinRoots.allows(linked, inside, &roots));
+    assert!(!SymlinkPolicy::WithinRoots.allows(linked, outside, &roots));
+    assert!(!SymlinkPolicy::Never.allows(linked, inside, &roots));
+    assert!(SymlinkPolicy::Never.allows(inside, inside, &roots));
+    assert!(SymlinkPolicy::Always.allows(linked, outside, &roots));
+}
+
+// ── Named libraries ───────────────────────────────────────────────────────────
+
+fn named(name: &str, paths: &[&PathBuf], kinds: &[MediaKind]) -> NamedLibrary {
+    NamedLibrary {
+        name: name.to_string(),
+        paths: paths.iter().map(|p| (*p).clone()).collect(),
+        kinds: kinds.to_vec(),
+    }
+}
+
+#[test]
+fn kind_limits_only_for_roots_restricted_everywhere() {
+    let kids = temp_media_dir("limits-kids");
+    let shared = temp_media_dir("limits-shared");
+    let open = temp_media_dir("limits-open");
+    let libraries = vec![
+        named("Kids", &[&kids, &shared], &[MediaKind::Video]),
+        named("Songs", &[&kids], &[MediaKind::Audio]),
+        named("All", &[&shared], &[]),
+        named("Open", &[&open], &[MediaKind::Image]),
+    ];
+    let limits = kind_limits(std::slice::from_ref(&open), &libraries);
+    let kids = std::fs::canonicalize(&kids).unwrap();
+    assert_eq!(limits, vec![(kids, vec![MediaKind::Video, MediaKind::Audio])]);
+}
+
+#[test]
+fn library_kinds_restrict_scan() {
+    let dir = temp_media_dir("library-kinds");
+    write_wav(&dir.join("song.wav"));
+    let libraries = vec![named("Kids Movies", &[&dir], &[MediaKind::Video])];
+    let config = ScanConfig {
+        kind_limits: kind_limits(&[], &libraries),
+        libraries,
+        ..Default::default()
+    };
+    let (lib, stats) = scan_with_stats(std::slice::from_ref(&dir), &config);
+    assert!(lib.items.is_empty());
+    assert_eq!(stats.excluded, 1);
+    assert_eq!(lib.libraries[0].paths, vec![std::fs::canonicalize(&dir).unwrap()]);
+}
+
+#[test]
+fn nested_library_scanned_once_with_its_own_kinds() {
+    let dir = temp_media_dir("nested-library");
+    let films = dir.join("films");
+    std::fs::create_dir_all(&films).unwrap();
+    write_wav(&dir.join("song.wav"));
+    write_wav(&films.join("score.wav"));
+    write_mkv(&films.join("film.mkv"));
+    let libraries = vec![named("Films", &[&films], &[MediaKind::Video])];
+    let config = ScanConfig {
+        kind_limits: kind_limits(std::slice::from_ref(&dir), &libraries),
+        libraries,
+        ..Default::default()
+    };
+    let (lib, stats) = scan_with_stats(&[dir, films], &config);
+    assert_eq!(file_names(&lib), vec!["film.mkv", "song.wav"]);
+    assert_eq!(stats.excluded, 1, "score.wav is outside the video-only library's kinds");
+}
+
+#[test]
+fn innermost_root_prefers_nested_root() {
+    let outer = PathBuf::from("/srv/media");
+    let inner = PathBuf::from("/srv/media/films");
+    let roots = vec![outer.clone(), inner.clone()];
+    assert_eq!(innermost_root(&roots, &inner.join("a.mkv")), Some(&inner));
+    assert_eq!(innermost_root(&roots, &outer.join("b.mkv")), Some(&outer));
+    assert_eq!(innermost_root(&roots, std::path::Path::new("/elsewhere/c.mkv")), None);
+}
+
+// ── Album art ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn scan_attaches_folder_art_to_audio() {
+    let dir = temp_media_dir("folder-art");
+    write_wav(&dir.join("track.wav"));
+    let mut cover = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    cover.extend_from_slice(&[0, 0, 0, 64, 0, 0, 0, 48, 8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    std::fs::write(dir.join("Folder.png"), &cover).unwrap();
+    let lib = scan(std::slice::from_ref(&dir));
+    let track = lib.items.iter().find(|i| i.kind == MediaKind::Audio).expect("track indexed");
+    let art = track.meta.art.as_ref().expect("folder art expected");
+    assert_eq!(art.path, Some(std::fs::canonicalize(&dir).unwrap().join("Folder.png")));
+    assert_eq!((art.width, art.height), (64, 48));
+    let image = lib.items.iter().find(|i| i.kind == MediaKind::Image).expect("cover is also a photo");
+    assert!(image.meta.art.is_none());
+}
+
+// ── Sidecars ──────────────────────────────────────────────────────────────────
+
+/// Write a Matroska file with a single H.264 track and no frames.
+fn write_mkv(path: &std::path::Path) {
+    let element = |id: &[u8], payload: &[u8]| [id, &[0x80 | payload.len() as u8], payload].concat();
+    let entry = [element(&[0xD7], &[1]), element(&[0x83], &[1]), element(&[0x86], b"V_MPEG4/ISO/AVC")].concat();
+    let tracks = element(&[0x16, 0x54, 0xAE, 0x6B], &element(&[0xAE], &entry));
+    let header = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"matroska"));
+    std::fs::write(path, [header, element(&[0x18, 0x53, 0x80, 0x67], &tracks)].concat()).unwrap();
+}
+
+#[test]
+fn scan_reads_nfo_sidecar_of_videos() {
+    let dir = temp_media_dir("nfo");
+    write_mkv(&dir.join("film.mkv"));
+    std::fs::write(dir.join("film.nfo"), "<movie><title>The Film</title><year>1999</year></movie>").unwrap();
+    let lib = scan(std::slice::from_ref(&dir));
+    assert_eq!(lib.items.len(), 1, "the sidecar is not an item");
+    let nfo = lib.items[0].meta.nfo.as_ref().expect("sidecar expected");
+    assert_eq!((nfo.title.as_deref(), nfo.year), (Some("The Film"), Some(1999)));
+}
+
+#[test]
+fn scan_attaches_subtitles_to_videos() {
+    let dir = temp_media_dir("subtitles");
+    write_mkv(&dir.join("film.mkv"));
+    std::fs::write(dir.join("film.en.srt"), "1\n00:00:01,000 --> 00:00:02,000\nHello\n").unwrap();
+    let lib = scan(std::slice::from_ref(&dir));
+    assert_eq!(lib.items.len(), 1, "the subtitle is not an item");
+    let subtitles = &lib.items[0].meta.subtitles;
+    assert_eq!(subtitles.len(), 1);
+    assert_eq!((subtitles[0].mime, subtitles[0].language.as_deref()), ("text/srt", Some("en")));
+}
+
+#[test]
+fn filtered_out_subtitles_are_not_attached() {
+    let dir = temp_media_dir("subtitle-filter");
+    write_mkv(&dir.join("film.mkv"));
+    for name in ["film.en.srt", "film.de.vtt"] {
+        std::fs::write(dir.join(name), "").unwrap();
+    }
+    let languages = |config: &ScanConfig| {
+        let lib = scan_with(std::slice::from_ref(&dir), config);
+        lib.items[0].meta.subtitles.iter().map(|s| s.language.clone().unwrap()).collect::<Vec<_>>()
+    };
+    assert_eq!(languages(&filtered(&[], &[], true)), ["de", "en"]);
+    assert_eq!(languages(&filtered(&[], &["*.srt"], true)), ["de"]);
+    assert_eq!(languages(&filtered(&["*.mkv", "*.srt"], &[], true)), ["en"]);
+}
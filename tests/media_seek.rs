@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+use udlna::media::library::{MediaItem, MediaMeta};
+use udlna::media::mime::MediaKind;
+use udlna::media::seek::{byte_offset, parse_npt, seekable_duration, supports, NptRange};
+
+fn item(mime: &'static str, duration: Option<&str>) -> MediaItem {
+    MediaItem {
+        id: uuid::Uuid::nil(),
+        path: PathBuf::from("/media/file"),
+        file_size: 1_000_000,
+        modified: None,
+        mime,
+        kind: MediaKind::Video,
+        meta: MediaMeta { duration: duration.map(str::to_string), ..Default::default() },
+    }
+}
+
+// ── parse_npt ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn parses_open_and_closed_ranges() {
+    assert_eq!(parse_npt("npt=90.5-"), Some(NptRange { start: 90.5, end: None }));
+    assert_eq!(parse_npt("npt=0:01:30.5-0:02:00"), Some(NptRange { start: 90.5, end: Some(120.0) }));
+    assert_eq!(parse_npt(" npt=10-20 "), Some(NptRange { start: 10.0, end: Some(20.0) }));
+}
+
+#[test]
+fn rejects_malformed_ranges() {
+    for value in ["bytes=0-100", "npt=abc-", "npt=20-10", "npt=-10", "npt=10", "npt=0:75:00-"] {
+        assert_eq!(parse_npt(value), None, "{value}");
+    }
+}
+
+// ── seekable_duration ─────────────────────────────────────────────────────────
+
+#[test]
+fn seekable_needs_duration_and_format() {
+    assert_eq!(seekable_duration(&item("video/MP2T", Some("01:00:00.000"))), Some(3600.0));
+    assert_eq!(seekable_duration(&item("video/MP2T", None)), None);
+    assert_eq!(seekable_duration(&item("audio/wav", Some("00:03:00.000"))), None);
+    for mime in ["video/MP2T", "video/mpeg", "audio/mpeg", "audio/aac"] {
+        assert!(supports(mime), "{mime}");
+    }
+    // Streams that need their headers: renderers byte-seek these.
+    for mime in ["video/mp4", "video/quicktime", "audio/mp4", "video/x-matroska", "video/webm", "audio/flac"] {
+        assert!(!supports(mime), "{mime}");
+    }
+    assert!(!supports("video/x-msvideo") && !supports("audio/x-ms-wma") && !supports("audio/ogg"));
+}
+
+// ── byte_offset ───────────────────────────────────────────────────────────────
+
+#[test]
+fn unindexed_formats_map_proportionally() {
+    let path = Path::new("/media/song.mp3");
+    assert_eq!(byte_offset(path, "audio/mpeg", 1_000_000, 100.0, 0.0), 0);
+    assert_eq!(byte_offset(path, "audio/mpeg", 1_000_000, 100.0, 25.0), 250_000);
+    assert_eq!(byte_offset(path, "audio/mpeg", 1_000_000, 100.0, 500.0), 1_000_000);
+}
+
+#[test]
+fn transport_streams_map_to_whole_packets() {
+    assert_eq!(byte_offset(Path::new("/media/a.ts"), "video/MP2T", 1_000_000, 100.0, 25.0), 249_852);
+    assert_eq!(byte_offset(Path::new("/media/a.m2ts"), "video/MP2T", 1_000_000, 100.0, 25.0), 249_984);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::mime::MediaKind;
+use udlna::media::sniff::{classify_file, sniff};
+
+fn ts_packets(stride: usize, offset: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; stride * 4];
+    for i in 0..4 {
+        buf[offset + i * stride] = 0x47;
+    }
+    buf
+}
+
+// ── Signatures ────────────────────────────────────────────────────────────────
+
+#[test]
+fn sniff_recognizes_containers() {
+    assert_eq!(sniff(b"\0\0\0\x20ftypisom\0\0\0\0"), Some("video/mp4"));
+    assert_eq!(sniff(b"\0\0\0\x20ftypM4A \0\0\0\0"), Some("audio/mp4"));
+    assert_eq!(sniff(b"\0\0\0\x14ftypqt  \0\0\0\0"), Some("video/quicktime"));
+    assert_eq!(sniff(b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81\x01\x42\x82\x84webm"), Some("video/webm"));
+    assert_eq!(sniff(b"\x1a\x45\xdf\xa3\x9f\x42\x82\x88matroska"), Some("video/x-matroska"));
+    assert_eq!(sniff(b"RIFF\0\0\0\0WAVEfmt "), Some("audio/wav"));
+    assert_eq!(sniff(b"RIFF\0\0\0\0AVI LIST"), Some("video/x-msvideo"));
+    assert_eq!(sniff(b"fLaC\0\0\0\x22"), Some("audio/flac"));
+    assert_eq!(sniff(b"OggS\0\x02\0\0\0\0\0\0\0\0\x01\x1e\x01vorbis"), Some("audio/ogg"));
+    assert_eq!(sniff(b"OggS\0\x02\0\0\0\0\0\0\0\0\x01\x2a\x80theora"), Some("video/ogg"));
+    assert_eq!(sniff(b"ID3\x04\0\0\0\0\0\0\xff\xfb\x90\x00"), Some("audio/mpeg"));
+    assert_eq!(sniff(&[0xFF, 0xFB, 0x90, 0x00]), Some("audio/mpeg"));
+    assert_eq!(sniff(&[0xFF, 0xF1, 0x50, 0x80]), Some("audio/aac"));
+    assert_eq!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+    assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0"), Some("image/png"));
+    assert_eq!(sniff(b"GIF89a\x01\0"), Some("image/gif"));
+    assert_eq!(sniff(&ts_packets(188, 0)), Some("video/MP2T"));
+    assert_eq!(sniff(&ts_packets(192, 4)), Some("video/MP2T"));
+}
+
+/// An ID3v2.4 tag with a `body`-sized padding body, and a footer if `footer` is set.
+fn id3_tag(body: usize, footer: bool) -> Vec<u8> {
+    let size = [(body >> 21) as u8 & 0x7F, (body >> 14) as u8 & 0x7F, (body >> 7) as u8 & 0x7F, body as u8 & 0x7F];
+    let mut tag = [b"ID3\x04\0".as_slice(), &[if footer { 0x10 } else { 0 }], &size].concat();
+    tag.resize(tag.len() + body, 0);
+    if footer {
+        tag.extend_from_slice(&[b"3DI\x04\0\x10".as_slice(), &size].concat());
+    }
+    tag
+}
+
+#[test]
+fn sniff_looks_past_id3_tag() {
+    let aac = [id3_tag(200, false), vec![0xFF, 0xF1, 0x50, 0x80]].concat();
+    assert_eq!(sniff(&aac), Some("audio/aac"));
+    let flac = [id3_tag(300, true), b"fLaC\0\0\0\x22".to_vec()].concat();
+    assert_eq!(sniff(&flac), Some("audio/flac"));
+    assert_eq!(sniff(&id3_tag(2000, false)[..1024]), None, "tag runs past the header");
+}
+
+#[test]
+fn sniff_unknown_content_is_none() {
+    assert_eq!(sniff(b"hello world, not media"), None);
+    assert_eq!(sniff(b""), None);
+    assert_eq!(sniff(b"\0\0\0\x18ftypheic\0\0\0\0"), None);
+}
+
+// ── classify_file ─────────────────────────────────────────────────────────────
+
+#[test]
+fn content_overrides_wrong_extension() {
+    let path = temp_file("really_ts.mp4", &ts_packets(188, 0));
+    assert_eq!(classify_file(&path), Some((MediaKind::Video, "video/MP2T")));
+}
+
+#[test]
+fn extension_decides_within_mp4_family() {
+    let path = temp_file("song.m4a", b"\0\0\0\x20ftypisom\0\0\0\0");
+    assert_eq!(classify_file(&path), Some((MediaKind::Audio, "audio/mp4")));
+}
+
+#[test]
+fn unrecognized_content_keeps_extension() {
+    let path = temp_file("odd.mp3", b"junk before the first frame");
+    assert_eq!(classify_file(&path), Some((MediaKind::Audio, "audio/mpeg")));
+}
+
+#[test]
+fn extensionless_file_classified_by_content() {
+    let path = temp_file("DCIM0001", b"RIFF\0\0\0\0AVI LIST");
+    assert_eq!(classify_file(&path), Some((MediaKind::Video, "video/x-msvideo")));
+    let other = temp_file("README", b"plain text");
+    assert_eq!(classify_file(&other), None);
+}
+
+#[test]
+fn id3_tagged_aac_and_flac_keep_their_type() {
+    let aac = temp_file("tagged.aac", &[id3_tag(50, false), vec![0xFF, 0xF1, 0x50, 0x80]].concat());
+    assert_eq!(classify_file(&aac), Some((MediaKind::Audio, "audio/aac")));
+    // Cover art pushes the stream past the first read.
+    let flac = temp_file("tagged-flac", &[id3_tag(64 * 1024, false), b"fLaC\0\0\0\x22".to_vec()].concat());
+    assert_eq!(classify_file(&flac), Some((MediaKind::Audio, "audio/flac")));
+}
+
+#[test]
+fn unknown_extension_is_not_sniffed() {
+    let path = temp_file("notes.txt", b"RIFF\0\0\0\0WAVEfmt ");
+    assert_eq!(classify_file(&path), None);
+}
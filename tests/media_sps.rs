@@ -0,0 +1,193 @@
+use udlna::media::sps::{
+    av1_config, avc_config, find_h264_sps, find_hevc_sps, find_mpeg2_size, h264_sps, hevc_config, hevc_sps, level_name,
+    profile_name, transfer_name, vp9_config, Sps,
+};
+
+/// MSB-first bit writer with Exp-Golomb codes.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u64, n: usize) -> &mut Self {
+        for i in (0..n).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            let bit = (value >> i) & 1;
+            *self.bytes.last_mut().unwrap() |= (bit as u8) << (7 - self.bits % 8);
+            self.bits += 1;
+        }
+        self
+    }
+
+    fn ue(&mut self, value: u32) -> &mut Self {
+        let code = value as u64 + 1;
+        let len = 64 - code.leading_zeros() as usize;
+        self.put(0, len - 1).put(code, len)
+    }
+
+    fn se(&mut self, value: i32) -> &mut Self {
+        let code = if value > 0 { 2 * value as u32 - 1 } else { 2 * value.unsigned_abs() };
+        self.ue(code)
+    }
+
+    /// RBSP trailing bits, then emulation prevention.
+    fn finish(&mut self) -> Vec<u8> {
+        self.put(1, 1);
+        let mut out = Vec::new();
+        let mut zeros = 0;
+        for &b in &self.bytes {
+            if zeros >= 2 && b <= 3 {
+                out.push(3);
+                zeros = 0;
+            }
+            zeros = if b == 0 { zeros + 1 } else { 0 };
+            out.push(b);
+        }
+        out
+    }
+}
+
+/// 1080p H.264 SPS: 120x68 macroblocks cropped by 8 lines at the bottom.
+fn h264_1080p(profile: u8) -> Vec<u8> {
+    let mut w = BitWriter::default();
+    w.put(profile as u64, 8).put(0, 8).put(40, 8).ue(0);
+    if profile == 100 {
+        w.ue(1).ue(2).ue(2).put(0, 1).put(0, 1); // 4:2:0, 10-bit, no scaling lists
+    }
+    w.ue(0).ue(2).ue(1).put(0, 1); // frame num, POC type 2, ref frames, gaps
+    w.ue(119).ue(67).put(1, 1).put(1, 1); // size, frame_mbs_only, direct_8x8
+    w.put(1, 1).ue(0).ue(0).ue(0).ue(4); // cropping
+    w.put(0, 1); // no VUI
+    [vec![0x67], w.finish()].concat()
+}
+
+/// 2160p HEVC Main 10 SPS, level 5.1.
+fn hevc_2160p() -> Vec<u8> {
+    let mut w = BitWriter::default();
+    w.put(0, 4).put(0, 3).put(1, 1);
+    w.put(0, 2).put(0, 1).put(2, 5).put(1 << 29, 32).put(0, 48).put(153, 8);
+    w.ue(0).ue(1).ue(3840).ue(2160).put(0, 1).ue(2);
+    [vec![0x42, 0x01], w.finish()].concat()
+}
+
+/// avcC with one SPS and no PPS.
+fn avcc(sps: &[u8]) -> Vec<u8> {
+    [&[1, sps[1], 0, sps[3], 0xFF, 0xE1][..], &(sps.len() as u16).to_be_bytes(), sps, &[0]].concat()
+}
+
+/// hvcC for Main 10 level 5.1 whose single array holds `nal_type` units.
+fn hvcc(nal_type: u8, nal: &[u8]) -> Vec<u8> {
+    let mut record = vec![1, 0x02, 0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 153];
+    record.extend_from_slice(&[0xF0, 0, 0xFC, 0xFD, 0xFA, 0xFA, 0, 0, 0x0F, 1]);
+    record.extend_from_slice(&[0x80 | nal_type, 0, 1]);
+    record.extend_from_slice(&(nal.len() as u16).to_be_bytes());
+    record.extend_from_slice(nal);
+    record
+}
+
+// ── H.264 ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn h264_baseline_size_applies_cropping() {
+    let sps = h264_sps(&h264_1080p(66)).expect("SPS expected");
+    assert_eq!(sps, Sps { profile: 66, level: 40, bit_depth: 8, width: 1920, height: 1080 });
+}
+
+#[test]
+fn h264_high_profile_reads_bit_depth() {
+    let sps = h264_sps(&h264_1080p(100)).expect("SPS expected");
+    assert_eq!((sps.profile, sps.bit_depth, sps.width, sps.height), (100, 10, 1920, 1080));
+}
+
+#[test]
+fn h264_sps_is_found_after_other_nal_units() {
+    let stream = [&[0, 0, 0, 1, 0x09, 0xF0][..], &[0, 0, 0, 1], &h264_1080p(66), &[0, 0, 1, 0x68, 0xCE]].concat();
+    assert_eq!(find_h264_sps(&stream).map(|s| s.width), Some(1920));
+}
+
+#[test]
+fn truncated_sps_is_rejected() {
+    assert!(h264_sps(&h264_1080p(66)[..6]).is_none());
+}
+
+#[test]
+fn out_of_range_scaling_delta_is_rejected() {
+    let mut w = BitWriter::default();
+    w.put(100, 8).put(0, 8).put(40, 8).ue(0);
+    w.ue(1).ue(2).ue(2).put(0, 1).put(1, 1); // 4:2:0, 10-bit, scaling matrix present
+    w.put(1, 1).se(i32::MAX); // first list, delta_scale far outside -128..=127
+    assert_eq!(h264_sps(&[vec![0x67], w.finish()].concat()), None);
+}
+
+// ── HEVC ──────────────────────────────────────────────────────────────────────
+
+#[test]
+fn hevc_sps_reads_profile_level_and_size() {
+    let sps = hevc_sps(&hevc_2160p()).expect("SPS expected");
+    assert_eq!(sps, Sps { profile: 2, level: 153, bit_depth: 10, width: 3840, height: 2160 });
+    let stream = [&[0, 0, 0, 1][..], &hevc_2160p()].concat();
+    assert_eq!(find_hevc_sps(&stream), Some(sps));
+}
+
+#[test]
+fn hevc_config_without_sps_uses_record_header() {
+    let sps = hevc_config(&hvcc(33, &hevc_2160p())).expect("record expected");
+    assert_eq!((sps.width, sps.height), (3840, 2160));
+    let header = hevc_config(&hvcc(32, &[0x40, 0x01, 0x0C])).expect("record expected");
+    assert_eq!(header, Sps { profile: 2, level: 153, bit_depth: 10, width: 0, height: 0 });
+}
+
+// ── MPEG-2 ────────────────────────────────────────────────────────────────────
+
+#[test]
+fn mpeg2_sequence_header_size() {
+    let stream = [0xFF, 0, 0, 1, 0xB3, 0x2D, 0x02, 0x40, 0x33];
+    assert_eq!(find_mpeg2_size(&stream), Some((720, 576)));
+}
+
+// ── Configuration records ─────────────────────────────────────────────────────
+
+#[test]
+fn avc_config_reads_first_sps() {
+    let sps = avc_config(&avcc(&h264_1080p(100))).expect("record expected");
+    assert_eq!((sps.profile, sps.level, sps.bit_depth, sps.width), (100, 40, 10, 1920));
+    assert!(avc_config(&[1, 100, 0, 40, 0xFF, 0xE0]).is_none(), "no SPS in the record");
+}
+
+#[test]
+fn av1_and_vp9_records() {
+    // Main profile, seq_level_idx 13 (5.1), high_bitdepth.
+    let av1 = av1_config(&[0x81, 0x0D, 0x4C, 0]).expect("av1C expected");
+    assert_eq!((av1.profile, av1.level, av1.bit_depth), (0, 13, 10));
+    // Profile 2, level 5.0, 10-bit 4:2:0, BT.2020 with PQ.
+    let vp9 = vp9_config(&[1, 0, 0, 0, 2, 50, 0xA2, 9, 16, 9, 0, 0]).expect("vpcC expected");
+    assert_eq!((vp9.profile, vp9.level, vp9.bit_depth), (2, 50, 10));
+    assert!(av1_config(&[0x01, 0x0D, 0x4C]).is_none());
+}
+
+// ── Names ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn profile_and_level_names() {
+    assert_eq!(profile_name("h264", 100), Some("High"));
+    assert_eq!(profile_name("hevc", 2), Some("Main 10"));
+    assert_eq!(profile_name("av1", 0), Some("Main"));
+    assert_eq!(profile_name("mpeg2video", 0), None);
+    assert_eq!(level_name("h264", 41).as_deref(), Some("4.1"));
+    assert_eq!(level_name("h264", 9).as_deref(), Some("1b"));
+    assert_eq!(level_name("hevc", 153).as_deref(), Some("5.1"));
+    assert_eq!(level_name("hevc", 120).as_deref(), Some("4"));
+    assert_eq!(level_name("av1", 13).as_deref(), Some("5.1"));
+    assert_eq!(level_name("av1", 31), None);
+}
+
+#[test]
+fn transfer_names() {
+    assert_eq!(transfer_name(16), Some("smpte2084"));
+    assert_eq!(transfer_name(18), Some("arib-std-b67"));
+    assert_eq!(transfer_name(2), None, "unspecified");
+}
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+use udlna::media::subtitle::{is_sidecar, SidecarIndex, Subtitle};
+use uuid::Uuid;
+
+fn temp_dir(name: &str, files: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("udlna-subtitle-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    for file in files {
+        std::fs::write(dir.join(file), b"").unwrap();
+    }
+    dir
+}
+
+/// The sidecars of `video`, looked up among everything in `dir`.
+fn sidecars(video: &Path) -> Vec<Subtitle> {
+    let video = std::fs::canonicalize(video.parent().unwrap()).unwrap().join(video.file_name().unwrap());
+    let files: Vec<PathBuf> =
+        std::fs::read_dir(video.parent().unwrap()).unwrap().map(|e| e.unwrap().path()).collect();
+    SidecarIndex::new(&files).sidecars(&video, &Uuid::nil())
+}
+
+fn found(dir: &Path, video: &str) -> Vec<(String, Option<String>)> {
+    sidecars(&dir.join(video))
+        .into_iter()
+        .map(|s| (s.path.file_name().unwrap().to_string_lossy().into_owned(), s.language))
+        .collect()
+}
+
+// ── sidecars ──────────────────────────────────────────────────────────────────
+
+#[test]
+fn matches_basename_and_language_suffixes() {
+    let dir = temp_dir(
+        "languages",
+        &["movie.mkv", "movie.srt", "movie.en.srt", "movie.pt-BR.forced.vtt", "movie.SDH.de.srt", "movie2.srt"],
+    );
+    assert_eq!(
+        found(&dir, "movie.mkv"),
+        vec![
+            ("movie.srt".to_string(), None),
+            ("movie.SDH.de.srt".to_string(), Some("de".to_string())),
+            ("movie.en.srt".to_string(), Some("en".to_string())),
+            ("movie.pt-BR.forced.vtt".to_string(), Some("pt-BR".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn subtitles_of_a_longer_sibling_name_are_left_to_it() {
+    let dir = temp_dir("siblings", &["show.mkv", "show.part2.mkv", "show.en.srt", "show.part2.en.srt"]);
+    assert_eq!(found(&dir, "show.mkv"), vec![("show.en.srt".to_string(), Some("en".to_string()))]);
+    assert_eq!(found(&dir, "show.part2.mkv"), vec![("show.part2.en.srt".to_string(), Some("en".to_string()))]);
+}
+
+#[test]
+fn subtitle_ids_and_formats() {
+    let dir = temp_dir("ids", &["clip.mp4", "clip.vtt", "clip.fr.srt"]);
+    let subtitles = sidecars(&dir.join("clip.mp4"));
+    assert_eq!(subtitles.iter().map(|s| s.format()).collect::<Vec<_>>(), vec!["vtt", "srt"]);
+    assert_ne!(subtitles[0].id, subtitles[1].id);
+    assert!(sidecars(&dir.join("other.mp4")).is_empty());
+}
+
+#[test]
+fn sidecar_names() {
+    assert!(is_sidecar(Path::new("/m/movie.en.srt")));
+    assert!(is_sidecar(Path::new("/m/Movie.VTT")));
+    assert!(!is_sidecar(Path::new("/m/movie.mkv")));
+}
@@ -0,0 +1,59 @@
+use udlna::media::mime::MediaKind;
+use udlna::media::title::{RewriteRule, TitleNormalizer};
+
+fn video(name: &str) -> (String, Option<i32>) {
+    let clean = TitleNormalizer::default().normalize(name, MediaKind::Video);
+    (clean.title, clean.year)
+}
+
+fn rule(pattern: &str, replace: &str) -> RewriteRule {
+    RewriteRule { pattern: pattern.to_string(), replace: replace.to_string() }
+}
+
+// ── normalize ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn release_names_become_title_and_year() {
+    assert_eq!(video("The.Movie.2019.1080p.BluRay.x264-GRP"), ("The Movie".to_string(), Some(2019)));
+    assert_eq!(video("Some_Film_[1080p]_[x265]"), ("Some Film".to_string(), None));
+    assert_eq!(video("Blade Runner 2049 (2017) [WEB-DL]"), ("Blade Runner 2049".to_string(), Some(2017)));
+}
+
+#[test]
+fn plain_names_are_kept() {
+    assert_eq!(video("Mr. Robot Pilot"), ("Mr. Robot Pilot".to_string(), None), "dots kept between spaced words");
+    assert_eq!(video("1917"), ("1917".to_string(), None), "a year-only name is the title");
+    assert_eq!(video("2012.2009.720p"), ("2012".to_string(), Some(2009)));
+    assert_eq!(video("1080p"), ("1080p".to_string(), None), "nothing left falls back to the name");
+}
+
+#[test]
+fn other_kinds_keep_their_names() {
+    let titles = TitleNormalizer::default();
+    let song = titles.normalize("Prince - 1999", MediaKind::Audio);
+    assert_eq!((song.title.as_str(), song.year), ("Prince - 1999", None));
+    assert_eq!(titles.normalize("IMG_1234", MediaKind::Image).title, "IMG_1234");
+}
+
+#[test]
+fn rewrite_rules_run_first_and_in_order() {
+    let rules = [rule(r"^\[[^\]]*\]\s*", ""), rule("(?i)directors.cut", "Director's Cut")];
+    let titles = TitleNormalizer::new(true, &rules).unwrap();
+    let clean = titles.normalize("[Group] Film Directors.Cut 1999 720p", MediaKind::Video);
+    assert_eq!((clean.title.as_str(), clean.year), ("Film Director's Cut", Some(1999)));
+    let audio = titles.normalize("[Label] Song", MediaKind::Audio);
+    assert_eq!(audio.title, "Song", "rules apply to every kind");
+}
+
+#[test]
+fn disabled_cleanup_keeps_raw_names() {
+    let titles = TitleNormalizer::new(false, &[rule("x264", "")]).unwrap();
+    assert_eq!(titles.normalize("The.Movie.2019.x264", MediaKind::Video).title, "The.Movie.2019.");
+    assert_ne!(titles, TitleNormalizer::default());
+}
+
+#[test]
+fn invalid_pattern_is_an_error() {
+    let err = TitleNormalizer::new(true, &[rule("(unclosed", "")]).unwrap_err();
+    assert!(err.to_string().contains("(unclosed"), "{err}");
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::temp_file;
+use udlna::media::library::AudioTrack;
+use udlna::media::metadata::extract_metadata;
+use udlna::media::mime::MediaKind;
+use udlna::media::ts::read_info;
+
+const PMT_PID: u16 = 0x1000;
+const VIDEO_PID: u16 = 0x100;
+const PCR_HZ: u64 = 27_000_000;
+
+/// One 188-byte packet, padded with adaptation field stuffing.
+fn packet(pid: u16, unit_start: bool, pcr: Option<u64>, payload: &[u8]) -> Vec<u8> {
+    let mut buf = vec![0x47, (unit_start as u8) << 6 | (pid >> 8) as u8, pid as u8, 0x30];
+    let mut adaptation = Vec::new();
+    if let Some(pcr) = pcr {
+        let (base, ext) = (pcr / 300, pcr % 300);
+        adaptation.extend_from_slice(&[
+            0x10,
+            (base >> 25) as u8,
+            (base >> 17) as u8,
+            (base >> 9) as u8,
+            (base >> 1) as u8,
+            ((base & 1) as u8) << 7 | 0x7E | (ext >> 8) as u8,
+            ext as u8,
+        ]);
+    }
+    let af_len = 183 - payload.len();
+    if af_len > 0 && adaptation.is_empty() {
+        adaptation.push(0x00);
+    }
+    adaptation.resize(af_len, 0xFF);
+    buf.push(af_len as u8);
+    buf.extend(adaptation);
+    buf.extend_from_slice(payload);
+    assert_eq!(buf.len(), 188);
+    buf
+}
+
+/// PSI section with pointer field and a (zero) CRC.
+fn psi(table_id: u8, body: &[u8]) -> Vec<u8> {
+    let len = body.len() + 4;
+    [&[0, table_id, 0xB0 | (len >> 8) as u8, len as u8][..], body, &[0; 4]].concat()
+}
+
+fn pat() -> Vec<u8> {
+    psi(0x00, &[0, 1, 0xC1, 0, 0, 0, 1, 0xE0 | (PMT_PID >> 8) as u8, PMT_PID as u8])
+}
+
+fn pmt(streams: &[(u8, u16, Vec<u8>)]) -> Vec<u8> {
+    let mut body = vec![0, 1, 0xC1, 0, 0, 0xE0 | (VIDEO_PID >> 8) as u8, VIDEO_PID as u8, 0xF0, 0];
+    for (stream_type, pid, descriptors) in streams {
+        body.extend_from_slice(&[*stream_type, 0xE0 | (pid >> 8) as u8, *pid as u8, 0xF0, descriptors.len() as u8]);
+        body.extend_from_slice(descriptors);
+    }
+    psi(0x02, &body)
+}
+
+fn language(code: &str) -> Vec<u8> {
+    [&[0x0A, 4][..], code.as_bytes(), &[0]].concat()
+}
+
+/// 720x576 MPEG-2 video: PES header followed by a sequence header.
+fn mpeg2_pes() -> Vec<u8> {
+    let es = [0, 0, 1, 0xB3, 0x2D, 0x02, 0x40, 0x33, 0xFF, 0xFF, 0xE0, 0x18];
+    [&[0, 0, 1, 0xE0, 0, 0, 0x80, 0x80, 0x05, 0x21, 0, 1, 0, 1][..], &es].concat()
+}
+
+/// Transport stream with PCRs at `start` and `end` seconds.
+fn transport_stream(start: u64, end: u64) -> Vec<Vec<u8>> {
+    let streams = [
+        (0x02, VIDEO_PID, Vec::new()),
+        (0x0F, 0x101, language("eng")),
+        (0x06, 0x102, [language("deu"), vec![0x6A, 1, 0]].concat()),
+        (0x06, 0x103, vec![0x59, 0]), // DVB subtitles: not audio
+    ];
+    vec![
+        packet(0, true, None, &pat()),
+        packet(PMT_PID, true, None, &pmt(&streams)),
+        packet(VIDEO_PID, true, Some(start * PCR_HZ), &mpeg2_pes()),
+        packet(0x101, true, None, &[0xFF; 20]),
+        packet(VIDEO_PID, false, Some(end * PCR_HZ), &[0; 30]),
+    ]
+}
+
+// ── read_info ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn reads_program_duration_and_size() {
+    let path = temp_file("broadcast.ts", &transport_stream(10, 70).concat());
+    let info = read_info(&path).expect("PAT/PMT expected");
+    assert_eq!(info.duration, Some(60.0));
+    assert_eq!(info.video_codec, Some("mpeg2video"));
+    assert_eq!(info.video.map(|v| (v.width, v.height)), Some((720, 576)));
+    let audio: Vec<_> = info.audio.iter().map(|a| (a.codec.as_str(), a.language.as_deref())).collect();
+    assert_eq!(audio, vec![("aac", Some("eng")), ("ac3", Some("deu"))]);
+    assert!(!info.timestamped);
+}
+
+#[test]
+fn m2ts_framing_is_detected() {
+    let packets: Vec<u8> = transport_stream(0, 5)
+        .into_iter()
+        .flat_map(|p| [vec![0; 4], p].concat())
+        .collect();
+    let path = temp_file("camcorder.m2ts", &packets);
+    let info = read_info(&path).expect("PAT/PMT expected");
+    assert_eq!(info.duration, Some(5.0));
+    assert_eq!(info.video.map(|v| v.width), Some(720));
+    assert!(info.timestamped);
+}
+
+#[test]
+fn pcr_wraparound_is_handled() {
+    let wrap = (1u64 << 33) / 90_000; // seconds until the PCR base wraps
+    let mut packets = transport_stream(wrap - 2, 0);
+    packets.truncate(3);
+    packets.push(packet(VIDEO_PID, false, Some(PCR_HZ), &[0; 30]));
+    let path = temp_file("wrap.ts", &packets.concat());
+    let duration = read_info(&path).unwrap().duration.unwrap();
+    assert!((duration - 3.0).abs() < 1.0, "duration {duration}");
+}
+
+#[test]
+fn not_a_transport_stream() {
+    let path = temp_file("random.ts", &[0u8; 4096]);
+    assert!(read_info(&path).is_none());
+}
+
+// ── extract_metadata ──────────────────────────────────────────────────────────
+
+#[test]
+fn ts_metadata_has_resolution_and_duration() {
+    let path = temp_file("show.ts", &transport_stream(1, 3601).concat());
+    let meta = extract_metadata(&path, MediaKind::Video, "video/MP2T").expect("metadata expected");
+    assert_eq!(meta.duration.as_deref(), Some("01:00:00.000"));
+    assert_eq!(meta.resolution.as_deref(), Some("720x576"));
+    assert_eq!(meta.video_codec.as_deref(), Some("mpeg2video"));
+    let aac = AudioTrack { codec: "aac".to_string(), language: Some("eng".to_string()), channels: None };
+    assert_eq!(meta.audio_tracks[0], aac);
+    assert_eq!(meta.dlna_profile, Some("MPEG_TS_SD_EU_ISO"));
+}